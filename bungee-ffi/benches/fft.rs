@@ -0,0 +1,109 @@
+//! Compares the engine's built-in kissfft with RustFFT
+//!
+//! Run with `cargo bench --features rustfft`. Each backend stretches the same ten seconds of
+//! stereo noise; the raw transform throughput of RustFFT is also reported.
+
+use std::time::{Duration, Instant};
+
+use bungee_ffi::{Fft, FftBackend, Request, RustFft, SampleRates, Strided, StretcherConfig};
+
+const RATE: i32 = 44100;
+const CHANNELS: usize = 2;
+const SECONDS: usize = 10;
+const RUNS: usize = 5;
+
+fn noise(frames: usize) -> Vec<f32> {
+    // xorshift, so that every run and backend sees the same signal
+    let mut state = 0x2545_f491_u32;
+    (0..frames * CHANNELS)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as f32 / u32::MAX as f32 - 0.5
+        })
+        .collect()
+}
+
+// Stretch the whole of `input` at `speed` and return the time taken
+fn stretch(fft: Fft, input: &[f32], speed: f64) -> Result<Duration, bungee_ffi::BungeeError> {
+    let rates = SampleRates { input: RATE, output: RATE };
+    let mut stretcher = StretcherConfig::new().fft(fft).build(rates, CHANNELS as i32)?;
+    let frames = input.len() / CHANNELS;
+
+    let start = Instant::now();
+    let mut request = Request {
+        position: 0.0,
+        speed,
+        pitch: 1.0,
+        reset: true,
+        preserve_formants: false,
+    };
+    stretcher.preroll(&mut request)?;
+
+    let mut grain = Vec::new();
+    while request.position < frames as f64 {
+        let (begin, end) = stretcher.specify_grain()?;
+        let grain_frames = (end - begin).max(0) as usize;
+        grain.clear();
+        grain.resize(grain_frames * CHANNELS, 0.0);
+        for c in 0..CHANNELS {
+            for f in 0..grain_frames {
+                let frame = begin as i64 + f as i64;
+                if (0..frames as i64).contains(&frame) {
+                    grain[c * grain_frames + f] = input[frame as usize * CHANNELS + c];
+                }
+            }
+        }
+        stretcher.analyse_grain(&Strided::planar(&grain, CHANNELS)?)?;
+        std::hint::black_box(stretcher.synthesise_grain()?);
+        stretcher.next(&mut request)?;
+    }
+    Ok(start.elapsed())
+}
+
+fn best_of<F: FnMut() -> Duration>(mut f: F) -> Duration {
+    (0..RUNS).map(|_| f()).min().unwrap()
+}
+
+fn main() {
+    let input = noise(SECONDS * RATE as usize);
+
+    for speed in [0.5, 1.0, 2.0] {
+        for fft in [Fft::Kiss, Fft::RustFft] {
+            match stretch(fft, &input, speed) {
+                Ok(_) => {
+                    let time = best_of(|| stretch(fft, &input, speed).unwrap());
+                    println!(
+                        "stretch {SECONDS} s stereo at speed {speed}, {fft:?}: {:.1} ms",
+                        time.as_secs_f64() * 1e3
+                    );
+                }
+                Err(error) => println!("stretch with {fft:?} skipped: {error}"),
+            }
+        }
+    }
+
+    let mut fft = RustFft::new();
+    for log2_length in [9, 10, 11, 12] {
+        let n = 1usize << log2_length;
+        fft.prepare(log2_length, false);
+        fft.prepare(log2_length, true);
+        let mut time = input[..n].to_vec();
+        let mut frequency = vec![0.0; n + 2];
+        let count = (1 << 22) >> log2_length;
+        let elapsed = best_of(|| {
+            let start = Instant::now();
+            for _ in 0..count {
+                fft.forward(log2_length, &time, &mut frequency);
+                fft.inverse(log2_length, &mut time, &frequency);
+                time.iter_mut().for_each(|x| *x /= n as f32);
+            }
+            start.elapsed()
+        });
+        println!(
+            "RustFft forward and inverse, length {n}: {:.2} us",
+            elapsed.as_secs_f64() * 1e6 / count as f64
+        );
+    }
+}
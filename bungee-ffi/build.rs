@@ -1,25 +1,47 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+// Engine sources from ../src, compiled as C++ behind the pure C layer in bungee_basic.c
+const ENGINE_SOURCES: &[&str] = &[
+    "Assert.cpp",
+    "Automation.cpp",
+    "Basic.cpp",
+    "Formants.cpp",
+    "Fourier.cpp",
+    "Grain.cpp",
+    "Grains.cpp",
+    "Input.cpp",
+    "Output.cpp",
+    "Partials.cpp",
+    "Stretch.cpp",
+    "Synthesis.cpp",
+    "Timing.cpp",
+    "Window.cpp",
+];
 
 fn main() {
+    // The pure-rust engine has no C to compile and no header to bind
+    if env::var_os("CARGO_FEATURE_PURE_RUST").is_some() {
+        return;
+    }
+
     // Tell cargo to invalidate the built crate whenever the headers change
     println!("cargo:rerun-if-changed=../bungee/bungee_c.h");
-    println!("cargo:rerun-if-changed=../CMakeLists.txt");
+    println!("cargo:rerun-if-changed=../bungee/Bungee.h");
+    println!("cargo:rerun-if-changed=../bungee/bungee_error.c");
+    println!("cargo:rerun-if-changed=../bungee/bungee_sample.c");
+    println!("cargo:rerun-if-changed=../bungee/bungee_window.c");
 
-    // Build the C code using CMake
-    let dst = cmake::Config::new("..")
-        .define("CMAKE_BUILD_TYPE", "Debug")
-        .define("CMAKE_EXPORT_COMPILE_COMMANDS", "ON")
-        .build();
+    let root = PathBuf::from("..");
 
-    // Link against the Bungee library
-    println!("cargo:rustc-link-search={}", dst.join("lib").display());
-    println!("cargo:rustc-link-search={}", dst.join("library").display());
-    println!("cargo:rustc-link-search={}", dst.join("build").display());
-    println!("cargo:rustc-link-lib=static=bungee_c");
+    if env::var_os("CARGO_FEATURE_C_STUB").is_some() {
+        build_stub(&root);
+    } else {
+        build_engine(&root);
+    }
 
     // Link against math library
-    if cfg!(target_os = "linux") {
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux") {
         println!("cargo:rustc-link-lib=m");
     }
 
@@ -27,9 +49,9 @@ fn main() {
     let bindings = bindgen::Builder::default()
         .header("../bungee/bungee_c.h")
         .clang_arg("-I..")  // Root include path
-        .clang_arg("-DBUNGEE_DEBUG")  // Enable debug mode
         .allowlist_type("bungee_.*")
         .allowlist_function("bungee_.*")
+        .allowlist_var("BUNGEE_.*")
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
         .generate()
         .expect("Unable to generate bindings");
@@ -38,8 +60,69 @@ fn main() {
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
+}
 
-    // Output compile commands for debugging
-    println!("cargo:warning=Compile commands available at: {}", 
-             dst.join("compile_commands.json").display());
-} 
\ No newline at end of file
+/// Builds the minimal pure C fallback (`c-stub` feature)
+fn build_stub(root: &Path) {
+    println!("cargo:rerun-if-changed=../bungee/bungee_c.c");
+
+    c_layer(root)
+        .file(root.join("bungee/bungee_c.c"))
+        .file(root.join("bungee/bungee_error.c"))
+        .file(root.join("bungee/bungee_sample.c"))
+        .file(root.join("bungee/bungee_window.c"))
+        .compile("bungee_c");
+}
+
+/// Builds the Bungee::Basic phase-vocoder engine with its Eigen and KissFFT dependencies
+fn build_engine(root: &Path) {
+    let submodules = root.join("submodules");
+    let eigen = submodules.join("eigen");
+    let kissfft = submodules.join("kissfft");
+    for dependency in [&eigen, &kissfft] {
+        if !dependency.exists() {
+            panic!(
+                "{} not found: run `git submodule update --init --recursive`, or enable the `c-stub` feature",
+                dependency.display()
+            );
+        }
+    }
+
+    println!("cargo:rerun-if-changed=../bungee/bungee_basic.c");
+    println!("cargo:rerun-if-changed=../src");
+
+    // Archives are listed dependents first: C layer, then engine, then KissFFT
+    c_layer(root)
+        .file(root.join("bungee/bungee_basic.c"))
+        .file(root.join("bungee/bungee_error.c"))
+        .file(root.join("bungee/bungee_sample.c"))
+        .file(root.join("bungee/bungee_window.c"))
+        .compile("bungee_c");
+
+    cc::Build::new()
+        .cpp(true)
+        .std("c++20")
+        .files(ENGINE_SOURCES.iter().map(|file| root.join("src").join(file)))
+        .include(root)
+        .include(root.join("src"))
+        .include(&eigen)
+        .include(&submodules)
+        .warnings(false)
+        .compile("bungee_engine");
+
+    cc::Build::new()
+        .files(["kiss_fft.c", "kiss_fftr.c"].map(|file| kissfft.join(file)))
+        .include(&kissfft)
+        .warnings(false)
+        .compile("kissfft");
+}
+
+/// Common settings for the C layer; BUNGEE_LOG output is only enabled for debug builds
+fn c_layer(root: &Path) -> cc::Build {
+    let mut build = cc::Build::new();
+    build.include(root);
+    if env::var("PROFILE").as_deref() == Ok("debug") {
+        build.define("BUNGEE_DEBUG", None);
+    }
+    build
+}
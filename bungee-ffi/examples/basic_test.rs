@@ -1,4 +1,4 @@
-use bungee_ffi::{Stretcher, SampleRates, Request};
+use bungee_ffi::{Interleaved, Stretcher, SampleRates, Request};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Starting basic time-stretching test...");
@@ -38,77 +38,56 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         speed: 1.5,
         pitch: 1.0,
         reset: true,
+        preserve_formants: false,
     };
     
     // Preroll with initial request
-    stretcher.preroll(&request)?;
+    stretcher.preroll(&mut request)?;
     println!("Request configured: speed={}, pitch={}", request.speed, request.pitch);
     
-    // Create output buffer (1.5x size for time stretching)
-    let output_size = (num_samples as f32 * 1.5) as usize;
-    let mut output = vec![0.0f32; output_size];
-    let mut output_pos = 0;
+    let mut output = Vec::new();
+    let mut grain = Vec::new();
     
     println!("\n=== Starting grain processing ===\n");
     
     // Process in grains
-    while !stretcher.is_flushed() && output_pos < output_size {
+    loop {
         // Get required input range for this grain
-        let (begin, end) = stretcher.specify_grain(&input, num_samples/2)?;
+        let (begin, end) = stretcher.specify_grain()?;
         println!("\nGrain boundaries: begin={}, end={}", begin, end);
         
-        // Skip if this is a flush grain
-        if begin >= end {
-            println!("Skipping flush grain");
-            continue;
+        // Copy the grain, padding with silence outside the input
+        grain.clear();
+        for position in begin..end {
+            if position >= 0 && (position as usize) < num_samples {
+                let position = position as usize;
+                grain.extend_from_slice(&input[position * 2..position * 2 + 2]);
+            } else {
+                grain.extend_from_slice(&[0.0, 0.0]);
+            }
         }
         
-        // Get the input slice for this grain
-        let begin = begin as usize * 2; // *2 for stereo
-        let end = end as usize * 2;     // *2 for stereo
-        let input_slice = if begin < input.len() && end <= input.len() {
-            println!("Processing input slice: {}..{} ({} samples)", 
-                    begin, end, (end - begin) / 2);
-            &input[begin..end]
-        } else {
-            println!("Input slice out of bounds: {}..{}", begin, end);
-            break;
-        };
-        
-        // Check input slice for non-zero values
-        let non_zero = input_slice.iter().any(|&x| x != 0.0);
-        println!("Input contains non-zero values: {}", non_zero);
-        
-        // Analyze the grain
-        stretcher.analyse_grain(input_slice, 1)?;
-        
-        // Prepare output chunk
-        let remaining = output_size - output_pos;
-        let mut output_chunk = bungee_ffi::bungee_output_chunk_t {
-            data: output[output_pos..].as_mut_ptr(),
-            frame_count: (remaining / 2) as i32,
-            channel_stride: 2,
-        };
-        println!("Output chunk prepared: remaining={}, frame_count={}, channel_stride={}", 
-                remaining, output_chunk.frame_count, output_chunk.channel_stride);
+        // Analyze the grain, which is interleaved stereo like the input
+        stretcher.analyse_grain(&Interleaved::new(&grain, 2)?)?;
         
         // Synthesize the grain
-        stretcher.synthesise_grain(&mut output_chunk)?;
-        let frames_generated = output_chunk.frame_count as usize * 2; // *2 for stereo
-        println!("Synthesized {} output frames", frames_generated / 2);
-        
-        // Check output for non-zero values
-        let output_slice = &output[output_pos..output_pos + frames_generated];
-        let non_zero_output = output_slice.iter().any(|&x| x != 0.0);
-        println!("Output contains non-zero values: {}", non_zero_output);
+        let output_chunk = stretcher.synthesise_grain()?;
+        println!("Synthesized {} output frames", output_chunk.frame_count());
+        output.extend_from_slice(output_chunk.interleaved());
         
-        output_pos += frames_generated;
-        
-        // Prepare next grain
+        // Prepare next grain, flushing with NaN positions once the input is consumed
         request.reset = false;
         stretcher.next(&mut request)?;
+        if request.position >= num_samples as f64 {
+            request.position = f64::NAN;
+        }
         println!("Advanced to next grain: position={}", request.position);
+        
+        if stretcher.is_flushed() {
+            break;
+        }
     }
+    let output_pos = output.len();
     
     println!("\n=== Processing complete ===");
     println!("Processed {} samples into {} samples", num_samples, output_pos);
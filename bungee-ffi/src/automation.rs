@@ -0,0 +1,177 @@
+//! Breakpoint envelopes that drive speed and pitch per grain
+
+use crate::{
+    bungee_breakpoint_t, bungee_curve_BUNGEE_CURVE_EXPONENTIAL, bungee_curve_BUNGEE_CURVE_HOLD,
+    bungee_curve_BUNGEE_CURVE_LINEAR, bungee_curve_t, bungee_parameter_BUNGEE_PARAMETER_PITCH,
+    bungee_parameter_BUNGEE_PARAMETER_SPEED, bungee_parameter_t, bungee_time_base_BUNGEE_TIME_BASE_INPUT,
+    bungee_time_base_BUNGEE_TIME_BASE_OUTPUT, bungee_time_base_t,
+};
+
+/// Shape of the segment from one breakpoint to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    /// Keep the breakpoint's value until the next breakpoint
+    Hold,
+    Linear,
+    /// Constant ratio per unit time; linear unless both values are non-zero with the same sign
+    Exponential,
+}
+
+/// Request field controlled by an envelope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    Speed,
+    Pitch,
+}
+
+/// Clock on which breakpoint times are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBase {
+    /// Input frames, as in `Request::position`
+    Input,
+    /// Output frames synthesised since the last request with `reset` set
+    Output,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Breakpoint {
+    pub time: f64,
+    pub value: f64,
+    /// Shape of the segment that starts at this breakpoint
+    pub curve: Curve,
+}
+
+/// Envelope of breakpoints in non-decreasing time order
+///
+/// Before the first and after the last breakpoint the envelope holds their values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub time_base: TimeBase,
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+impl Envelope {
+    /// Create an empty envelope
+    pub fn new(time_base: TimeBase) -> Self {
+        Self {
+            time_base,
+            breakpoints: Vec::new(),
+        }
+    }
+
+    /// Append a breakpoint
+    pub fn point(mut self, time: f64, value: f64, curve: Curve) -> Self {
+        self.breakpoints.push(Breakpoint { time, value, curve });
+        self
+    }
+}
+
+impl From<Curve> for bungee_curve_t {
+    fn from(curve: Curve) -> Self {
+        match curve {
+            Curve::Hold => bungee_curve_BUNGEE_CURVE_HOLD,
+            Curve::Linear => bungee_curve_BUNGEE_CURVE_LINEAR,
+            Curve::Exponential => bungee_curve_BUNGEE_CURVE_EXPONENTIAL,
+        }
+    }
+}
+
+impl From<Parameter> for bungee_parameter_t {
+    fn from(parameter: Parameter) -> Self {
+        match parameter {
+            Parameter::Speed => bungee_parameter_BUNGEE_PARAMETER_SPEED,
+            Parameter::Pitch => bungee_parameter_BUNGEE_PARAMETER_PITCH,
+        }
+    }
+}
+
+impl From<TimeBase> for bungee_time_base_t {
+    fn from(time_base: TimeBase) -> Self {
+        match time_base {
+            TimeBase::Input => bungee_time_base_BUNGEE_TIME_BASE_INPUT,
+            TimeBase::Output => bungee_time_base_BUNGEE_TIME_BASE_OUTPUT,
+        }
+    }
+}
+
+impl From<Breakpoint> for bungee_breakpoint_t {
+    fn from(breakpoint: Breakpoint) -> Self {
+        Self {
+            time: breakpoint.time,
+            value: breakpoint.value,
+            curve: breakpoint.curve.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interleaved, Request, SampleRates, Stretcher};
+
+    /// Run 200 grains under a speed `envelope`, checking the speed of every request
+    /// against `expected` at the envelope's clock
+    fn check_speed(envelope: &Envelope, expected: impl Fn(f64) -> f64) {
+        let rates = SampleRates { input: 44100, output: 44100 };
+        let mut stretcher = Stretcher::new(rates, 1).unwrap();
+        stretcher.set_automation(Parameter::Speed, envelope).unwrap();
+
+        let mut request = Request {
+            position: 0.0,
+            speed: 1.0,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        stretcher.preroll(&mut request).unwrap();
+        assert_eq!(request.speed, expected(0.0));
+
+        let mut output = 0.0;
+        for _ in 0..200 {
+            let (begin, end) = stretcher.specify_grain().unwrap();
+            stretcher.analyse_grain(&Interleaved::new(&vec![0.0; (end - begin) as usize], 1).unwrap()).unwrap();
+            output += stretcher.synthesise_grain().unwrap().frame_count() as f64;
+            stretcher.next(&mut request).unwrap();
+
+            let time = match envelope.time_base {
+                TimeBase::Input => request.position,
+                TimeBase::Output => output,
+            };
+            let speed = expected(time);
+            assert!((request.speed - speed).abs() < 1e-9, "speed {} at time {time}, expected {speed}", request.speed);
+        }
+    }
+
+    #[test]
+    fn hold_keeps_each_value_until_the_next_breakpoint() {
+        let envelope = Envelope::new(TimeBase::Input).point(0.0, 1.0, Curve::Hold).point(10000.0, 2.0, Curve::Hold);
+        check_speed(&envelope, |t| if t < 10000.0 { 1.0 } else { 2.0 });
+    }
+
+    #[test]
+    fn linear_interpolates_between_breakpoints() {
+        let envelope = Envelope::new(TimeBase::Input).point(0.0, 0.5, Curve::Linear).point(20000.0, 1.5, Curve::Hold);
+        check_speed(&envelope, |t| 0.5 + t.clamp(0.0, 20000.0) / 20000.0);
+    }
+
+    #[test]
+    fn exponential_interpolates_in_ratio() {
+        let envelope = Envelope::new(TimeBase::Input).point(0.0, 0.5, Curve::Exponential).point(20000.0, 2.0, Curve::Hold);
+        check_speed(&envelope, |t| 0.5 * 4f64.powf(t.clamp(0.0, 20000.0) / 20000.0));
+    }
+
+    #[test]
+    fn output_time_base_counts_synthesised_frames() {
+        // At speed 0.25 the input clock would barely reach the second breakpoint
+        let envelope = Envelope::new(TimeBase::Output).point(5000.0, 0.25, Curve::Linear).point(15000.0, 1.25, Curve::Hold);
+        check_speed(&envelope, |t| 0.25 + (t.clamp(5000.0, 15000.0) - 5000.0) / 10000.0);
+    }
+
+    #[test]
+    fn pitch_breakpoints_are_checked() {
+        let rates = SampleRates { input: 44100, output: 44100 };
+        let mut stretcher = Stretcher::new(rates, 1).unwrap();
+        let envelope = Envelope::new(TimeBase::Input).point(0.0, 1.0, Curve::Linear).point(1000.0, 100.0, Curve::Hold);
+        assert!(stretcher.set_automation(Parameter::Pitch, &envelope).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! Command-line audio speed and pitch changer for WAV files
+//!
+//! Rust counterpart of the C++ tool in cmd/main.cpp, built on `bungee_ffi::offline`.
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use bungee_ffi::{offline, SampleRates};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+
+const USAGE: &str = "\
+Bungee audio speed and pitch changer
+
+Usage: bungee [options] <input.wav> <output.wav>
+
+Options:
+  -s, --speed <ratio>        output speed, 1 is unchanged, 2 is twice as fast,
+                             -1 plays backwards (default 1)
+  -p, --pitch <ratio>        pitch as a frequency multiplier, 1 is unchanged (default 1)
+      --semitones <n>        pitch shift in semitones, alternative to --pitch
+      --position <start:end> input range to process in seconds; either end may be omitted
+      --output-rate <Hz>     output sample rate (default: input sample rate)
+  -h, --help                 show this help
+
+Input may be 8, 16, 24 or 32-bit integer or 32-bit float WAV; output uses the same format.";
+
+#[derive(Debug)]
+struct Options {
+    input: PathBuf,
+    output: PathBuf,
+    speed: f64,
+    pitch: f64,
+    start: Option<f64>,
+    end: Option<f64>,
+    output_rate: Option<u32>,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("bungee: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("bungee: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut files = Vec::new();
+    let mut speed = 1.0;
+    let mut pitch = None;
+    let mut semitones = None;
+    let mut position = None;
+    let mut output_rate = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{arg} requires a value"));
+        match arg.as_str() {
+            "-s" | "--speed" => speed = number(arg, value()?)?,
+            "-p" | "--pitch" => pitch = Some(number(arg, value()?)?),
+            "--semitones" => semitones = Some(number(arg, value()?)?),
+            "--position" => position = Some(value()?.clone()),
+            "--output-rate" => {
+                let value = value()?;
+                output_rate = Some(value.parse().map_err(|_| format!("invalid {arg}: {value}"))?);
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("unknown option {arg}")),
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [input, output] = <[PathBuf; 2]>::try_from(files)
+        .map_err(|_| "expected an input and an output file".to_string())?;
+
+    if speed == 0.0 || !speed.is_finite() {
+        return Err(format!("speed must be non-zero: {speed}"));
+    }
+    let pitch = match (pitch, semitones) {
+        (Some(_), Some(_)) => return Err("--pitch and --semitones are alternatives".into()),
+        (Some(pitch), None) => pitch,
+        (None, Some(semitones)) => 2f64.powf(semitones / 12.0),
+        (None, None) => 1.0,
+    };
+    if !(pitch > 0.0 && pitch.is_finite()) {
+        return Err(format!("pitch must be positive: {pitch}"));
+    }
+
+    let (start, end) = match position {
+        Some(position) => {
+            let (start, end) = position
+                .split_once(':')
+                .ok_or_else(|| format!("--position expects <start:end>, got {position}"))?;
+            let seconds = |text: &str| -> Result<Option<f64>, String> {
+                if text.is_empty() {
+                    Ok(None)
+                } else {
+                    number("--position", text).map(Some)
+                }
+            };
+            (seconds(start)?, seconds(end)?)
+        }
+        None => (None, None),
+    };
+
+    Ok(Options {
+        input,
+        output,
+        speed,
+        pitch,
+        start,
+        end,
+        output_rate,
+    })
+}
+
+fn number(option: &str, value: &str) -> Result<f64, String> {
+    value.parse().map_err(|_| format!("invalid {option}: {value}"))
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut reader = WavReader::open(&options.input)?;
+    let spec = reader.spec();
+    let input = read_samples(&mut reader)?;
+
+    let channels = spec.channels as usize;
+    let frames = input.len() / channels;
+    let rates = SampleRates {
+        input: spec.sample_rate as i32,
+        output: options.output_rate.unwrap_or(spec.sample_rate) as i32,
+    };
+
+    let to_frame = |seconds: f64| ((seconds * spec.sample_rate as f64).round().max(0.0) as usize).min(frames);
+    let start = options.start.map_or(0, to_frame);
+    let end = options.end.map_or(frames, to_frame);
+    if start > end {
+        return Err(format!("--position start is after end: {start} > {end} frames").into());
+    }
+
+    let output = offline::process_range(&input, channels, rates, options.speed, options.pitch, start..end)?;
+
+    let spec = WavSpec {
+        sample_rate: rates.output as u32,
+        ..spec
+    };
+    write_samples(&options.output, spec, &output)?;
+
+    println!(
+        "{}: {} frames at {} Hz -> {}: {} frames at {} Hz",
+        options.input.display(),
+        end - start,
+        rates.input,
+        options.output.display(),
+        output.len() / channels,
+        rates.output
+    );
+    Ok(())
+}
+
+/// Read all samples as interleaved f32 in the range [-1, 1)
+fn read_samples<R: std::io::Read>(reader: &mut WavReader<R>) -> Result<Vec<f32>, hound::Error> {
+    let spec = reader.spec();
+    match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>().collect(),
+        SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 * scale))
+                .collect()
+        }
+    }
+}
+
+/// Write interleaved f32 samples in the format given by `spec`, clipping integer output
+fn write_samples(path: &PathBuf, spec: WavSpec, samples: &[f32]) -> Result<(), hound::Error> {
+    let mut writer = WavWriter::create(path, spec)?;
+    match spec.sample_format {
+        SampleFormat::Float => {
+            for &sample in samples {
+                writer.write_sample(sample)?;
+            }
+        }
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            let (min, max) = (-scale, scale - 1.0);
+            for &sample in samples {
+                writer.write_sample((sample * scale).round().clamp(min, max) as i32)?;
+            }
+        }
+    }
+    writer.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &str) -> Result<Options, String> {
+        parse(&args.split_whitespace().map(String::from).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        let options = parse_args("-s 0.5 --semitones 12 --position 1.5: --output-rate 48000 in.wav out.wav").unwrap();
+        assert_eq!(options.input, PathBuf::from("in.wav"));
+        assert_eq!(options.output, PathBuf::from("out.wav"));
+        assert_eq!(options.speed, 0.5);
+        assert_eq!(options.pitch, 2.0);
+        assert_eq!((options.start, options.end), (Some(1.5), None));
+        assert_eq!(options.output_rate, Some(48000));
+
+        let options = parse_args("--speed -1 -p 0.75 --position :2 in.wav out.wav").unwrap();
+        assert_eq!((options.speed, options.pitch), (-1.0, 0.75));
+        assert_eq!((options.start, options.end), (None, Some(2.0)));
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        for args in [
+            "in.wav",
+            "in.wav out.wav extra.wav",
+            "--speed 0 in.wav out.wav",
+            "--speed fast in.wav out.wav",
+            "--pitch -1 in.wav out.wav",
+            "--pitch 2 --semitones 12 in.wav out.wav",
+            "--position 1.5 in.wav out.wav",
+            "--output-rate -1 in.wav out.wav",
+            "--loud in.wav out.wav",
+            "in.wav out.wav --speed",
+        ] {
+            assert!(parse_args(args).is_err(), "{args}");
+        }
+    }
+
+    #[test]
+    fn integer_samples_round_trip() {
+        let path = std::env::temp_dir().join(format!("bungee-cli-test-{}.wav", std::process::id()));
+        let samples: Vec<f32> = (-4..4).map(|i| i as f32 / 4.0).chain([1.5, -1.5]).collect();
+        for bits in [8, 16, 24, 32] {
+            let spec = WavSpec {
+                channels: 2,
+                sample_rate: 44100,
+                bits_per_sample: bits,
+                sample_format: SampleFormat::Int,
+            };
+            write_samples(&path, spec, &samples).unwrap();
+            let read = read_samples(&mut WavReader::open(&path).unwrap()).unwrap();
+            let step = 1.0 / (1i64 << (bits - 1)) as f32;
+            // In-range samples are exact and out-of-range samples clip
+            assert_eq!(read[..8], samples[..8], "{bits} bits");
+            assert_eq!(read[8..], [1.0 - step, -1.0], "{bits} bits");
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
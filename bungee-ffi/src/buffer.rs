@@ -0,0 +1,707 @@
+//! Views of multichannel audio in the layouts that `Stretcher` reads grains from and writes
+//! them to
+//!
+//! Each view carries its channel count and layout, so a grain passes between the stretcher and
+//! interleaved, planar or arbitrarily strided audio without bare strides whose meaning the
+//! caller has to remember. The traits are sealed: the C layer is given raw pointers derived
+//! from the views, so only the layouts defined here can be trusted to describe memory correctly.
+
+use std::os::raw::c_void;
+
+use crate::{BungeeError, Sample};
+
+mod sealed {
+    use std::os::raw::c_void;
+
+    pub trait Layout<T> {
+        /// Push a pointer to frame 0 of each channel and return the distance between
+        /// successive frames in samples
+        fn pointers(&self, pointers: &mut Vec<*mut c_void>) -> usize;
+    }
+
+    pub trait LayoutMut<T> {
+        /// As `Layout::pointers`, with pointers that may be written through
+        fn pointers_mut(&mut self, pointers: &mut Vec<*mut c_void>) -> usize;
+    }
+
+    /// One channel of planar audio: a slice or vector, whose length cannot change between
+    /// being measured and its pointer being passed to the C layer
+    pub trait Channel<T> {
+        fn samples(&self) -> &[T];
+    }
+
+    /// A channel of planar audio that may be written
+    pub trait ChannelMut<T>: Channel<T> {
+        fn samples_mut(&mut self) -> &mut [T];
+    }
+
+    impl<T> Channel<T> for &[T] {
+        fn samples(&self) -> &[T] {
+            self
+        }
+    }
+
+    impl<T> Channel<T> for &mut [T] {
+        fn samples(&self) -> &[T] {
+            self
+        }
+    }
+
+    impl<T> ChannelMut<T> for &mut [T] {
+        fn samples_mut(&mut self) -> &mut [T] {
+            self
+        }
+    }
+
+    impl<T> Channel<T> for Vec<T> {
+        fn samples(&self) -> &[T] {
+            self
+        }
+    }
+
+    impl<T> ChannelMut<T> for Vec<T> {
+        fn samples_mut(&mut self) -> &mut [T] {
+            self
+        }
+    }
+}
+
+/// Number of frames of `channels` channels in `samples` samples
+fn whole_frames(samples: usize, channels: usize) -> Result<usize, BungeeError> {
+    if channels == 0 {
+        return Err(BungeeError::InvalidChannelCount {
+            channels: 0,
+            message: "Invalid channel count: 0".into(),
+        });
+    }
+    if samples % channels != 0 {
+        return Err(BungeeError::InvalidParam {
+            message: format!("{samples} samples is not a whole number of {channels}-channel frames"),
+        });
+    }
+    Ok(samples / channels)
+}
+
+/// Multichannel audio that can be read sample by sample
+pub trait AudioBuffer<T: Sample = f32>: sealed::Layout<T> {
+    /// Number of channels
+    fn channel_count(&self) -> usize;
+
+    /// Number of frames, each holding one sample of every channel
+    fn frame_count(&self) -> usize;
+
+    /// Sample `frame` of `channel`
+    ///
+    /// Panics if `channel` or `frame` is out of range.
+    fn sample(&self, channel: usize, frame: usize) -> T;
+}
+
+/// Multichannel audio that can also be written
+pub trait AudioBufferMut<T: Sample = f32>: AudioBuffer<T> + sealed::LayoutMut<T> {
+    /// Read-only view returned by `view`
+    type View<'b>: AudioBuffer<T>
+    where
+        Self: 'b;
+
+    /// Set sample `frame` of `channel`
+    ///
+    /// Panics if `channel` or `frame` is out of range.
+    fn set_sample(&mut self, channel: usize, frame: usize, value: T);
+
+    /// Read-only view of the first `frames` frames
+    ///
+    /// Panics if `frames` exceeds `frame_count()`.
+    fn view(&self, frames: usize) -> Self::View<'_>;
+}
+
+/// Interleaved audio: frame `i` of channel `c` is at `[i * channels + c]`
+#[derive(Debug, Clone, Copy)]
+pub struct Interleaved<'a, T = f32> {
+    data: &'a [T],
+    channels: usize,
+}
+
+impl<'a, T> Interleaved<'a, T> {
+    /// View `data` as interleaved audio
+    ///
+    /// Fails with `InvalidChannelCount` if `channels` is zero and with `InvalidParam` if
+    /// `data` does not hold a whole number of frames.
+    pub fn new(data: &'a [T], channels: usize) -> Result<Self, BungeeError> {
+        whole_frames(data.len(), channels)?;
+        Ok(Self { data, channels })
+    }
+
+    /// The interleaved samples
+    pub fn as_slice(&self) -> &'a [T] {
+        self.data
+    }
+}
+
+impl<T: Sample> sealed::Layout<T> for Interleaved<'_, T> {
+    fn pointers(&self, pointers: &mut Vec<*mut c_void>) -> usize {
+        let data = self.data.as_ptr();
+        pointers.extend((0..self.channels).map(|c| data.wrapping_add(c) as *mut c_void));
+        self.channels
+    }
+}
+
+impl<T: Sample> AudioBuffer<T> for Interleaved<'_, T> {
+    fn channel_count(&self) -> usize {
+        self.channels
+    }
+
+    fn frame_count(&self) -> usize {
+        self.data.len() / self.channels
+    }
+
+    fn sample(&self, channel: usize, frame: usize) -> T {
+        assert!(channel < self.channels, "channel {channel} out of range");
+        self.data[frame * self.channels + channel]
+    }
+}
+
+/// Mutable interleaved audio, see `Interleaved`
+#[derive(Debug)]
+pub struct InterleavedMut<'a, T = f32> {
+    data: &'a mut [T],
+    channels: usize,
+}
+
+impl<'a, T> InterleavedMut<'a, T> {
+    /// View `data` as interleaved audio, see `Interleaved::new`
+    pub fn new(data: &'a mut [T], channels: usize) -> Result<Self, BungeeError> {
+        whole_frames(data.len(), channels)?;
+        Ok(Self { data, channels })
+    }
+
+    /// The interleaved samples
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data
+    }
+}
+
+impl<T: Sample> sealed::Layout<T> for InterleavedMut<'_, T> {
+    fn pointers(&self, pointers: &mut Vec<*mut c_void>) -> usize {
+        Interleaved {
+            data: self.data,
+            channels: self.channels,
+        }
+        .pointers(pointers)
+    }
+}
+
+impl<T: Sample> sealed::LayoutMut<T> for InterleavedMut<'_, T> {
+    fn pointers_mut(&mut self, pointers: &mut Vec<*mut c_void>) -> usize {
+        let data = self.data.as_mut_ptr();
+        pointers.extend((0..self.channels).map(|c| data.wrapping_add(c) as *mut c_void));
+        self.channels
+    }
+}
+
+impl<T: Sample> AudioBuffer<T> for InterleavedMut<'_, T> {
+    fn channel_count(&self) -> usize {
+        self.channels
+    }
+
+    fn frame_count(&self) -> usize {
+        self.data.len() / self.channels
+    }
+
+    fn sample(&self, channel: usize, frame: usize) -> T {
+        Interleaved {
+            data: self.data,
+            channels: self.channels,
+        }
+        .sample(channel, frame)
+    }
+}
+
+impl<T: Sample> AudioBufferMut<T> for InterleavedMut<'_, T> {
+    type View<'b>
+        = Interleaved<'b, T>
+    where
+        Self: 'b;
+
+    fn set_sample(&mut self, channel: usize, frame: usize, value: T) {
+        assert!(channel < self.channels, "channel {channel} out of range");
+        self.data[frame * self.channels + channel] = value;
+    }
+
+    fn view(&self, frames: usize) -> Interleaved<'_, T> {
+        Interleaved {
+            data: &self.data[..frames * self.channels],
+            channels: self.channels,
+        }
+    }
+}
+
+/// Planar audio with each channel in its own slice, such as `&[&[f32]]` or `&[Vec<f32>]`
+///
+/// The frame count is the length of the shortest channel. Channels are `&[T]`, `&mut [T]`
+/// or `Vec<T>`.
+#[derive(Debug, Clone, Copy)]
+pub struct Planar<'a, S> {
+    channels: &'a [S],
+    frames: usize,
+}
+
+impl<'a, S> Planar<'a, S> {
+    /// View `channels` as planar audio
+    pub fn new<T>(channels: &'a [S]) -> Self
+    where
+        S: sealed::Channel<T>,
+    {
+        Self { channels, frames: shortest(channels) }
+    }
+
+    /// The channel slices
+    pub fn channels(&self) -> &'a [S] {
+        self.channels
+    }
+}
+
+/// Frame count of planar `channels`: the length of the shortest
+fn shortest<T, S: sealed::Channel<T>>(channels: &[S]) -> usize {
+    channels.iter().map(|channel| channel.samples().len()).min().unwrap_or(0)
+}
+
+impl<T: Sample, S: sealed::Channel<T>> sealed::Layout<T> for Planar<'_, S> {
+    fn pointers(&self, pointers: &mut Vec<*mut c_void>) -> usize {
+        pointers.extend(self.channels.iter().map(|channel| channel.samples().as_ptr() as *mut c_void));
+        1
+    }
+}
+
+impl<T: Sample, S: sealed::Channel<T>> AudioBuffer<T> for Planar<'_, S> {
+    fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    fn sample(&self, channel: usize, frame: usize) -> T {
+        assert!(frame < self.frames, "frame {frame} out of range");
+        self.channels[channel].samples()[frame]
+    }
+}
+
+/// Mutable planar audio, such as `&mut [&mut [f32]]` or `&mut [Vec<f32>]`, see `Planar`
+///
+/// The frame count is measured afresh on each use, as `channels_mut` may resize a channel.
+#[derive(Debug)]
+pub struct PlanarMut<'a, S> {
+    channels: &'a mut [S],
+}
+
+impl<'a, S> PlanarMut<'a, S> {
+    /// View `channels` as planar audio
+    pub fn new<T>(channels: &'a mut [S]) -> Self
+    where
+        S: sealed::ChannelMut<T>,
+    {
+        Self { channels }
+    }
+
+    /// The channel slices
+    pub fn channels_mut(&mut self) -> &mut [S] {
+        self.channels
+    }
+}
+
+impl<T: Sample, S: sealed::Channel<T>> sealed::Layout<T> for PlanarMut<'_, S> {
+    fn pointers(&self, pointers: &mut Vec<*mut c_void>) -> usize {
+        Planar::new::<T>(self.channels).pointers(pointers)
+    }
+}
+
+impl<T: Sample, S: sealed::ChannelMut<T>> sealed::LayoutMut<T> for PlanarMut<'_, S> {
+    fn pointers_mut(&mut self, pointers: &mut Vec<*mut c_void>) -> usize {
+        pointers.extend(self.channels.iter_mut().map(|channel| channel.samples_mut().as_mut_ptr() as *mut c_void));
+        1
+    }
+}
+
+impl<T: Sample, S: sealed::Channel<T>> AudioBuffer<T> for PlanarMut<'_, S> {
+    fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn frame_count(&self) -> usize {
+        shortest(self.channels)
+    }
+
+    fn sample(&self, channel: usize, frame: usize) -> T {
+        Planar::new::<T>(self.channels).sample(channel, frame)
+    }
+}
+
+impl<T: Sample, S: sealed::ChannelMut<T>> AudioBufferMut<T> for PlanarMut<'_, S> {
+    type View<'b>
+        = Planar<'b, S>
+    where
+        Self: 'b;
+
+    fn set_sample(&mut self, channel: usize, frame: usize, value: T) {
+        let frames = shortest(self.channels);
+        assert!(frame < frames, "frame {frame} out of range");
+        self.channels[channel].samples_mut()[frame] = value;
+    }
+
+    fn view(&self, frames: usize) -> Planar<'_, S> {
+        let shortest = shortest(self.channels);
+        assert!(frames <= shortest, "{frames} frames exceeds {shortest}");
+        Planar {
+            channels: self.channels,
+            frames,
+        }
+    }
+}
+
+/// Audio in one slice with any layout: frame `i` of channel `c` is at
+/// `[c * channel_stride + i * frame_stride]`
+///
+/// A planar buffer with channels stored one after another has a `frame_stride` of 1; an
+/// interleaved one has a `channel_stride` of 1.
+#[derive(Debug, Clone, Copy)]
+pub struct Strided<'a, T = f32> {
+    data: &'a [T],
+    layout: StridedLayout,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StridedLayout {
+    channels: usize,
+    frames: usize,
+    channel_stride: usize,
+    frame_stride: usize,
+}
+
+impl StridedLayout {
+    /// Fails unless every sample lies within `length`
+    fn check(&self, length: usize) -> Result<(), BungeeError> {
+        if self.channels > 0 && self.frames > 0 {
+            let last = (self.channels - 1)
+                .checked_mul(self.channel_stride)
+                .zip((self.frames - 1).checked_mul(self.frame_stride))
+                .and_then(|(channel, frame)| channel.checked_add(frame));
+            if last.is_none_or(|last| last >= length) {
+                let reach = last.map_or_else(|| "beyond usize::MAX".to_string(), |last| format!("sample {last}"));
+                return Err(BungeeError::InvalidParam {
+                    message: format!("Strided layout reaches {reach} of {length}"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn index(&self, channel: usize, frame: usize) -> usize {
+        assert!(channel < self.channels, "channel {channel} out of range");
+        assert!(frame < self.frames, "frame {frame} out of range");
+        channel * self.channel_stride + frame * self.frame_stride
+    }
+}
+
+impl<'a, T> Strided<'a, T> {
+    /// View `data` with the given layout
+    ///
+    /// Fails with `InvalidParam` if any sample of the layout lies beyond the end of `data`.
+    pub fn new(data: &'a [T], channels: usize, frames: usize, channel_stride: usize, frame_stride: usize) -> Result<Self, BungeeError> {
+        let layout = StridedLayout {
+            channels,
+            frames,
+            channel_stride,
+            frame_stride,
+        };
+        layout.check(data.len())?;
+        Ok(Self { data, layout })
+    }
+
+    /// View `data` as planar audio with `channels` channels of equal length, one after another
+    ///
+    /// Fails as `Interleaved::new` if `channels` is zero or `data` does not divide evenly
+    /// between them.
+    pub fn planar(data: &'a [T], channels: usize) -> Result<Self, BungeeError> {
+        let frames = whole_frames(data.len(), channels)?;
+        Self::new(data, channels, frames, frames, 1)
+    }
+}
+
+impl<T: Sample> sealed::Layout<T> for Strided<'_, T> {
+    fn pointers(&self, pointers: &mut Vec<*mut c_void>) -> usize {
+        let data = self.data.as_ptr();
+        let layout = &self.layout;
+        pointers.extend((0..layout.channels).map(|c| data.wrapping_add(c * layout.channel_stride) as *mut c_void));
+        layout.frame_stride
+    }
+}
+
+impl<T: Sample> AudioBuffer<T> for Strided<'_, T> {
+    fn channel_count(&self) -> usize {
+        self.layout.channels
+    }
+
+    fn frame_count(&self) -> usize {
+        self.layout.frames
+    }
+
+    fn sample(&self, channel: usize, frame: usize) -> T {
+        self.data[self.layout.index(channel, frame)]
+    }
+}
+
+/// Mutable audio in one slice with any layout, see `Strided`
+#[derive(Debug)]
+pub struct StridedMut<'a, T = f32> {
+    data: &'a mut [T],
+    layout: StridedLayout,
+}
+
+impl<'a, T> StridedMut<'a, T> {
+    /// View `data` with the given layout, see `Strided::new`
+    pub fn new(data: &'a mut [T], channels: usize, frames: usize, channel_stride: usize, frame_stride: usize) -> Result<Self, BungeeError> {
+        let layout = StridedLayout {
+            channels,
+            frames,
+            channel_stride,
+            frame_stride,
+        };
+        layout.check(data.len())?;
+        Ok(Self { data, layout })
+    }
+
+    /// View `data` as planar audio, see `Strided::planar`
+    pub fn planar(data: &'a mut [T], channels: usize) -> Result<Self, BungeeError> {
+        let frames = whole_frames(data.len(), channels)?;
+        Self::new(data, channels, frames, frames, 1)
+    }
+}
+
+impl<T: Sample> sealed::Layout<T> for StridedMut<'_, T> {
+    fn pointers(&self, pointers: &mut Vec<*mut c_void>) -> usize {
+        Strided {
+            data: self.data,
+            layout: self.layout,
+        }
+        .pointers(pointers)
+    }
+}
+
+impl<T: Sample> sealed::LayoutMut<T> for StridedMut<'_, T> {
+    fn pointers_mut(&mut self, pointers: &mut Vec<*mut c_void>) -> usize {
+        let data = self.data.as_mut_ptr();
+        let layout = &self.layout;
+        pointers.extend((0..layout.channels).map(|c| data.wrapping_add(c * layout.channel_stride) as *mut c_void));
+        layout.frame_stride
+    }
+}
+
+impl<T: Sample> AudioBuffer<T> for StridedMut<'_, T> {
+    fn channel_count(&self) -> usize {
+        self.layout.channels
+    }
+
+    fn frame_count(&self) -> usize {
+        self.layout.frames
+    }
+
+    fn sample(&self, channel: usize, frame: usize) -> T {
+        self.data[self.layout.index(channel, frame)]
+    }
+}
+
+impl<T: Sample> AudioBufferMut<T> for StridedMut<'_, T> {
+    type View<'b>
+        = Strided<'b, T>
+    where
+        Self: 'b;
+
+    fn set_sample(&mut self, channel: usize, frame: usize, value: T) {
+        let index = self.layout.index(channel, frame);
+        self.data[index] = value;
+    }
+
+    fn view(&self, frames: usize) -> Strided<'_, T> {
+        assert!(frames <= self.layout.frames, "{frames} frames exceeds {}", self.layout.frames);
+        Strided {
+            data: self.data,
+            layout: StridedLayout { frames, ..self.layout },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::request;
+    use crate::{BungeeError, SampleRates, Stretcher};
+
+    const CHANNELS: usize = 2;
+    const FRAMES: i32 = 8192;
+
+    fn input(channel: usize, frame: i32) -> f32 {
+        if (0..FRAMES).contains(&frame) {
+            ((frame as f32 * 0.05 + channel as f32).sin() * 0.5) / (1 + channel) as f32
+        } else {
+            0.0
+        }
+    }
+
+    /// Stretch two channels of `input`, building each grain with `analyse` and reading each
+    /// chunk with `synthesise`, which returns its frames interleaved
+    fn stretch(
+        analyse: impl FnMut(&mut Stretcher, i32, i32) -> Result<(), BungeeError>,
+        synthesise: impl FnMut(&mut Stretcher) -> Vec<f32>,
+    ) -> Vec<f32> {
+        let mut stretcher = Stretcher::new(SampleRates { input: 44100, output: 44100 }, CHANNELS as i32).unwrap();
+        crate::tests::stretch_grains(&mut stretcher, FRAMES as usize, request(0.0, 0.75), analyse, synthesise)
+    }
+
+    fn synthesise_interleaved(stretcher: &mut Stretcher) -> Vec<f32> {
+        stretcher.synthesise_grain().unwrap().interleaved().to_vec()
+    }
+
+    #[test]
+    fn layouts_analyse_identically() {
+        let reference = stretch(
+            |stretcher, begin, end| {
+                let grain: Vec<f32> = (begin..end).flat_map(|i| (0..CHANNELS).map(move |c| input(c, i))).collect();
+                stretcher.analyse_grain(&Interleaved::new(&grain, CHANNELS).unwrap())
+            },
+            synthesise_interleaved,
+        );
+        assert!(reference.iter().any(|x| x.abs() > 0.1));
+
+        let planar = stretch(
+            |stretcher, begin, end| {
+                let grain: Vec<Vec<f32>> = (0..CHANNELS).map(|c| (begin..end).map(|i| input(c, i)).collect()).collect();
+                stretcher.analyse_grain(&Planar::new(&grain))
+            },
+            synthesise_interleaved,
+        );
+        assert_eq!(planar, reference);
+
+        // Channels 100 samples apart, every third sample, with padding the stretcher must skip
+        let strided = stretch(
+            |stretcher, begin, end| {
+                let frames = (end - begin) as usize;
+                let channel_stride = frames * 3 + 100;
+                let mut grain = vec![f32::NAN; channel_stride * CHANNELS];
+                for c in 0..CHANNELS {
+                    for (f, i) in (begin..end).enumerate() {
+                        grain[c * channel_stride + f * 3] = input(c, i);
+                    }
+                }
+                stretcher.analyse_grain(&Strided::new(&grain, CHANNELS, frames, channel_stride, 3).unwrap())
+            },
+            synthesise_interleaved,
+        );
+        assert_eq!(strided, reference);
+    }
+
+    #[test]
+    fn layouts_synthesise_identically() {
+        let analyse = |stretcher: &mut Stretcher, begin: i32, end: i32| {
+            let grain: Vec<Vec<f32>> = (0..CHANNELS).map(|c| (begin..end).map(|i| input(c, i)).collect()).collect();
+            stretcher.analyse_grain(&Planar::new(&grain))
+        };
+        let reference = stretch(analyse, synthesise_interleaved);
+
+        let planar = stretch(analyse, |stretcher| {
+            let mut channels = vec![vec![0.0; stretcher.max_output_frame_count()]; CHANNELS];
+            let mut output = PlanarMut::new(&mut channels);
+            let chunk = stretcher.synthesise_grain_into(&mut output).unwrap();
+            let mut interleaved = vec![0.0; chunk.frame_count() * CHANNELS];
+            chunk.copy_to(&mut InterleavedMut::new(&mut interleaved, CHANNELS).unwrap());
+            interleaved
+        });
+        assert_eq!(planar, reference);
+
+        let strided = stretch(analyse, |stretcher| {
+            let frames = stretcher.max_output_frame_count();
+            let mut data = vec![0.0; frames * CHANNELS];
+            let mut output = StridedMut::planar(&mut data, CHANNELS).unwrap();
+            let chunk = stretcher.synthesise_grain_into(&mut output).unwrap();
+            (0..chunk.frame_count()).flat_map(|f| (0..CHANNELS).map(move |c| (c, f))).map(|(c, f)| chunk.audio().sample(c, f)).collect()
+        });
+        assert_eq!(strided, reference);
+    }
+
+    #[test]
+    fn channel_count_must_match() {
+        let mut stretcher = Stretcher::new(SampleRates { input: 44100, output: 44100 }, CHANNELS as i32).unwrap();
+        stretcher.preroll(&mut request(0.0, 1.0)).unwrap();
+        let (begin, end) = stretcher.specify_grain().unwrap();
+        let mono = vec![0.0f32; (end - begin) as usize];
+        assert!(matches!(stretcher.analyse_grain(&Interleaved::new(&mono, 1).unwrap()), Err(BungeeError::InvalidParam { .. })));
+        let short = vec![0.0f32; (end - begin - 1) as usize * CHANNELS];
+        assert!(matches!(stretcher.analyse_grain(&Strided::planar(&short, CHANNELS).unwrap()), Err(BungeeError::InvalidParam { .. })));
+
+        let stereo = vec![0.0f32; (end - begin) as usize * CHANNELS];
+        stretcher.analyse_grain(&Interleaved::new(&stereo, CHANNELS).unwrap()).unwrap();
+        let mut mono = vec![0.0f32; stretcher.max_output_frame_count()];
+        let result = stretcher.synthesise_grain_into(&mut InterleavedMut::new(&mut mono, 1).unwrap()).map(|_| ());
+        assert!(matches!(result, Err(BungeeError::InvalidParam { .. })));
+    }
+
+    #[test]
+    fn views_index_their_layouts() {
+        let data: Vec<i16> = (0..12).collect();
+        let interleaved = Interleaved::new(&data, 3).unwrap();
+        assert_eq!((interleaved.channel_count(), interleaved.frame_count()), (3, 4));
+        assert_eq!(interleaved.sample(2, 1), 5);
+        let planar = Strided::planar(&data, 3).unwrap();
+        assert_eq!((planar.channel_count(), planar.frame_count()), (3, 4));
+        assert_eq!(planar.sample(2, 1), 9);
+        let channels = [&data[..5], &data[6..]];
+        let planar = Planar::new(&channels);
+        assert_eq!((planar.channel_count(), planar.frame_count()), (2, 5));
+        assert_eq!(planar.sample(1, 4), 10);
+    }
+
+    #[test]
+    fn strided_layout_must_fit() {
+        assert!(matches!(Strided::new(&[0.0f32; 10], 2, 5, 5, 2), Err(BungeeError::InvalidParam { .. })));
+        assert!(matches!(StridedMut::new(&mut [0.0f32; 10], 2, 5, 1, 3), Err(BungeeError::InvalidParam { .. })));
+        assert!(Strided::new(&[0.0f32; 10], 2, 5, 5, 1).is_ok());
+        // Strides whose product wraps around usize
+        assert!(matches!(Strided::new(&[0.0f32; 4], 4, 1, usize::MAX / 3 + 1, 0), Err(BungeeError::InvalidParam { .. })));
+        assert!(matches!(StridedMut::new(&mut [0.0f32; 4], 1, 3, 0, usize::MAX), Err(BungeeError::InvalidParam { .. })));
+    }
+
+    #[test]
+    fn planar_frame_count_follows_its_channels() {
+        let mut channels = vec![vec![0i16; 8], vec![0i16; 8]];
+        let mut planar = PlanarMut::new(&mut channels);
+        assert_eq!(planar.frame_count(), 8);
+        planar.channels_mut()[1].truncate(3);
+        assert_eq!(planar.frame_count(), 3);
+        assert_eq!(planar.view(3).frame_count(), 3);
+    }
+
+    #[test]
+    fn constructors_reject_zero_channels() {
+        let mut data = [0.0f32; 8];
+        assert!(matches!(Interleaved::new(&data, 0), Err(BungeeError::InvalidChannelCount { channels: 0, .. })));
+        assert!(matches!(InterleavedMut::new(&mut data, 0), Err(BungeeError::InvalidChannelCount { channels: 0, .. })));
+        assert!(matches!(Strided::planar(&data, 0), Err(BungeeError::InvalidChannelCount { channels: 0, .. })));
+        assert!(matches!(StridedMut::planar(&mut data, 0), Err(BungeeError::InvalidChannelCount { channels: 0, .. })));
+    }
+
+    #[test]
+    fn constructors_reject_partial_frames() {
+        let mut data = [0.0f32; 7];
+        let messages = [
+            Interleaved::new(&data, 2).err(),
+            InterleavedMut::new(&mut data, 2).err(),
+            Strided::planar(&data, 2).err(),
+            StridedMut::planar(&mut data, 2).err(),
+        ];
+        for error in messages {
+            let error = error.expect("7 samples do not divide into 2 channels");
+            assert!(matches!(error, BungeeError::InvalidParam { .. }));
+            assert_eq!(error.to_string(), "7 samples is not a whole number of 2-channel frames");
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Creation-time options for a `Stretcher`
+
+use crate::*;
+
+/// Trade-off between latency and frequency resolution
+///
+/// Presets choose the synthesis hop relative to the default for the input sample rate,
+/// which is 512 frames at 44.1 or 48 kHz.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quality {
+    /// Half the default hop: shorter grains and lower latency, less bass resolution
+    LowLatency,
+    /// The default hop
+    #[default]
+    Balanced,
+    /// Twice the default hop: finer frequency resolution for sustained material
+    HighQuality,
+}
+
+/// Builder of `Stretcher` instances with non-default options
+///
+/// ```no_run
+/// use bungee_ffi::{BungeeError, Quality, SampleRates, StretcherConfig};
+///
+/// fn main() -> Result<(), BungeeError> {
+///     let rates = SampleRates { input: 44100, output: 48000 };
+///     let stretcher = StretcherConfig::new()
+///         .quality(Quality::LowLatency)
+///         .max_input_frame_count(4096)
+///         .build(rates, 2)?;
+///     assert_eq!(stretcher.channel_count(), 2);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StretcherConfig {
+    quality: Quality,
+    hop: Option<usize>,
+    max_pitch_octaves: Option<i32>,
+    max_input_frame_count: Option<usize>,
+    resample_mode: ResampleMode,
+    interpolation: Interpolation,
+    transient_sensitivity: f32,
+    channel_coupling: ChannelCoupling,
+    fft: Fft,
+    window: Window,
+}
+
+/// Smallest and largest synthesis hop accepted by the C layer
+pub const MIN_HOP: usize = 1 << BUNGEE_MIN_LOG2_SYNTHESIS_HOP;
+pub const MAX_HOP: usize = 1 << BUNGEE_MAX_LOG2_SYNTHESIS_HOP;
+
+impl StretcherConfig {
+    /// Default options, equivalent to `Stretcher::new`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose the synthesis hop by preset; overridden by `hop`
+    pub fn quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Set the synthesis hop in output frames, a power of two from `MIN_HOP` to `MAX_HOP`
+    pub fn hop(mut self, hop: usize) -> Self {
+        self.hop = Some(hop);
+        self
+    }
+
+    /// Allow pitch from `2^-octaves` to `2^octaves`, 1 to 6 octaves (default 2)
+    ///
+    /// Grain buffers double in size for every octave.
+    pub fn max_pitch_octaves(mut self, octaves: i32) -> Self {
+        self.max_pitch_octaves = Some(octaves);
+        self
+    }
+
+    /// Fail `build` if grains would need more input frames than the caller can supply
+    pub fn max_input_frame_count(mut self, frames: usize) -> Self {
+        self.max_input_frame_count = Some(frames);
+        self
+    }
+
+    /// Initial resample mode, see `Stretcher::set_resample_mode`
+    pub fn resample_mode(mut self, mode: ResampleMode) -> Self {
+        self.resample_mode = mode;
+        self
+    }
+
+    /// Initial interpolation kernel, see `Stretcher::set_interpolation`
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Initial transient detection sensitivity, see `Stretcher::set_transient_sensitivity`
+    pub fn transient_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.transient_sensitivity = sensitivity;
+        self
+    }
+
+    /// Initial channel coupling, see `Stretcher::set_channel_coupling`
+    pub fn channel_coupling(mut self, coupling: ChannelCoupling) -> Self {
+        self.channel_coupling = coupling;
+        self
+    }
+
+    /// FFT used for analysis and synthesis; overridden by `build_with_fft`
+    pub fn fft(mut self, fft: Fft) -> Self {
+        self.fft = fft;
+        self
+    }
+
+    /// Window family of the analysis and synthesis windows (default Hann)
+    ///
+    /// An invalid Kaiser `beta` or set of coefficients makes `build` fail with `InvalidParam`.
+    pub fn window(mut self, window: Window) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Synthesis hop in frames that `build` would use at `input_rate`
+    pub fn synthesis_hop(&self, input_rate: i32) -> Result<usize, BungeeError> {
+        Ok(1 << self.log2_synthesis_hop(input_rate)?)
+    }
+
+    /// Create a stretcher with these options
+    pub fn build(&self, rates: SampleRates, channels: i32) -> Result<Stretcher, BungeeError> {
+        self.create(rates, channels, || self.fft.backend())
+    }
+
+    /// Create a stretcher with these options that transforms with `fft`
+    pub fn build_with_fft<B: FftBackend>(&self, rates: SampleRates, channels: i32, fft: B) -> Result<Stretcher, BungeeError> {
+        self.create(rates, channels, || Some(fft::backend(fft)))
+    }
+
+    // The FFT backend is only made once the options are valid, so that it cannot leak
+    fn create<F>(&self, rates: SampleRates, channels: i32, fft: F) -> Result<Stretcher, BungeeError>
+    where
+        F: FnOnce() -> Option<bungee_fft_backend_t>,
+    {
+        let max_pitch_octaves = self.max_pitch_octaves.unwrap_or(BUNGEE_DEFAULT_MAX_PITCH_OCTAVES as i32);
+        if !(1..=BUNGEE_MAX_PITCH_OCTAVES as i32).contains(&max_pitch_octaves) {
+            return Err(BungeeError::InvalidPitchOctaves {
+                octaves: max_pitch_octaves,
+                message: format!(
+                    "Invalid pitch range: {max_pitch_octaves} octaves, expected 1 to {}",
+                    BUNGEE_MAX_PITCH_OCTAVES
+                ),
+            });
+        }
+        let window = self.window.raw();
+        let config = bungee_config_t {
+            log2_synthesis_hop: self.log2_synthesis_hop(rates.input)?,
+            max_pitch_octaves,
+            fft: std::ptr::null(),
+            window: &window,
+        };
+        let mut stretcher = Stretcher::create(rates, channels, config, fft())?;
+
+        if let Some(limit) = self.max_input_frame_count {
+            let required = stretcher.max_input_frame_count();
+            if required > limit {
+                return Err(BungeeError::InputFrameCountExceeded {
+                    required,
+                    limit,
+                    message: format!("Grains need up to {required} input frames, more than the limit of {limit}"),
+                });
+            }
+        }
+
+        // Only non-default choices are passed on, so that the defaults also work with the C stub
+        if self.resample_mode != ResampleMode::default() {
+            stretcher.set_resample_mode(self.resample_mode)?;
+        }
+        if self.interpolation != Interpolation::default() {
+            stretcher.set_interpolation(self.interpolation)?;
+        }
+        if self.transient_sensitivity != 0.0 {
+            stretcher.set_transient_sensitivity(self.transient_sensitivity)?;
+        }
+        if self.channel_coupling != ChannelCoupling::default() {
+            stretcher.set_channel_coupling(self.channel_coupling)?;
+        }
+        Ok(stretcher)
+    }
+
+    fn log2_synthesis_hop(&self, input_rate: i32) -> Result<i32, BungeeError> {
+        let hop = match self.hop {
+            Some(hop) => hop,
+            None => {
+                // As Timing: 1 << (floor(log2(input_rate)) - 6), then offset by the preset
+                let log2 = (31 - input_rate.max(1).leading_zeros()) as i32 - 6;
+                let log2 = match self.quality {
+                    Quality::LowLatency => log2 - 1,
+                    Quality::Balanced => log2,
+                    Quality::HighQuality => log2 + 1,
+                };
+                1 << log2.clamp(BUNGEE_MIN_LOG2_SYNTHESIS_HOP as i32, BUNGEE_MAX_LOG2_SYNTHESIS_HOP as i32)
+            }
+        };
+
+        if !hop.is_power_of_two() || !(MIN_HOP..=MAX_HOP).contains(&hop) {
+            return Err(BungeeError::InvalidHop {
+                hop,
+                message: format!("Invalid synthesis hop {hop}: expected a power of two from {MIN_HOP} to {MAX_HOP} frames"),
+            });
+        }
+        Ok(hop.trailing_zeros() as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: SampleRates = SampleRates { input: 44100, output: 44100 };
+
+    #[test]
+    fn hop_must_be_a_power_of_two_in_range() {
+        let mut hop = MIN_HOP;
+        while hop <= MAX_HOP {
+            assert_eq!(StretcherConfig::new().hop(hop).synthesis_hop(RATES.input).unwrap(), hop);
+            hop *= 2;
+        }
+        for hop in [0, MIN_HOP / 2, 100, 768, MAX_HOP * 2] {
+            let result = StretcherConfig::new().hop(hop).build(RATES, 1);
+            assert!(matches!(result, Err(BungeeError::InvalidHop { hop: h, .. }) if h == hop), "hop {hop}");
+        }
+    }
+
+    #[test]
+    fn quality_presets_scale_the_default_hop() {
+        let hop = |quality| StretcherConfig::new().quality(quality).synthesis_hop(RATES.input).unwrap();
+        assert_eq!(hop(Quality::LowLatency), 256);
+        assert_eq!(hop(Quality::Balanced), 512);
+        assert_eq!(hop(Quality::HighQuality), 1024);
+        // An explicit hop overrides the preset, and presets stay within range at extreme rates
+        assert_eq!(StretcherConfig::new().quality(Quality::HighQuality).hop(128).synthesis_hop(RATES.input).unwrap(), 128);
+        assert_eq!(StretcherConfig::new().quality(Quality::LowLatency).synthesis_hop(8000).unwrap(), MIN_HOP);
+        assert_eq!(StretcherConfig::new().quality(Quality::HighQuality).synthesis_hop(768000).unwrap(), MAX_HOP);
+    }
+
+    #[test]
+    fn built_stretcher_uses_the_hop() {
+        let small = StretcherConfig::new().hop(256).build(RATES, 1).unwrap();
+        let large = StretcherConfig::new().hop(2048).build(RATES, 1).unwrap();
+        assert!(small.max_input_frame_count() < large.max_input_frame_count());
+        assert!(small.latency().output < large.latency().output);
+    }
+
+    #[test]
+    fn input_frame_limit_is_enforced() {
+        let required = Stretcher::new(RATES, 1).unwrap().max_input_frame_count();
+        assert!(StretcherConfig::new().max_input_frame_count(required).build(RATES, 1).is_ok());
+        let result = StretcherConfig::new().max_input_frame_count(required - 1).build(RATES, 1);
+        assert!(matches!(result, Err(BungeeError::InputFrameCountExceeded { required: r, limit, .. }) if r == required && limit == required - 1));
+    }
+
+    #[test]
+    fn pitch_octaves_must_be_in_range() {
+        for octaves in [0, -1, BUNGEE_MAX_PITCH_OCTAVES as i32 + 1] {
+            let result = StretcherConfig::new().max_pitch_octaves(octaves).build(RATES, 1);
+            assert!(matches!(result, Err(BungeeError::InvalidPitchOctaves { octaves: o, .. }) if o == octaves), "{octaves} octaves");
+        }
+        let mut previous = 0;
+        for octaves in 1..=BUNGEE_MAX_PITCH_OCTAVES as i32 {
+            let stretcher = StretcherConfig::new().max_pitch_octaves(octaves).build(RATES, 1).unwrap();
+            let max = 2f64.powi(octaves);
+            assert_eq!(stretcher.pitch_range(), 1.0 / max..=max);
+            // Grain buffers grow with the range
+            assert!(stretcher.max_input_frame_count() >= previous);
+            previous = stretcher.max_input_frame_count();
+        }
+    }
+
+    #[test]
+    fn pitch_outside_the_range_is_rejected() {
+        let mut stretcher = StretcherConfig::new().max_pitch_octaves(1).build(RATES, 1).unwrap();
+        let request = |pitch| Request { pitch, ..crate::tests::request(0.0, 1.0) };
+        for pitch in [0.5, 0.75, 2.0] {
+            assert!(stretcher.preroll(&mut request(pitch)).is_ok(), "pitch {pitch}");
+        }
+        for pitch in [0.49, 2.01, 4.0] {
+            let result = stretcher.preroll(&mut request(pitch));
+            assert!(matches!(result, Err(BungeeError::PitchOutOfRange { max_octaves: Some(1), .. })), "pitch {pitch}");
+        }
+
+        // A wider range accepts the same pitch
+        let mut stretcher = StretcherConfig::new().max_pitch_octaves(3).build(RATES, 1).unwrap();
+        assert!(stretcher.preroll(&mut request(4.0)).is_ok());
+    }
+}
@@ -0,0 +1,101 @@
+//! Choice of how the phases of multiple channels are related during stretching
+
+use crate::*;
+
+/// How channel phases are coupled
+///
+/// Sum-locked coupling keeps inter-channel phase exact and so folds down to mono best; the
+/// other modes give each group of channels its own phase evolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelCoupling {
+    /// One phase rotation per bin from the sum of all channels
+    #[default]
+    SumLocked,
+    /// Stereo only: mid and side are locked separately, suiting wide pads
+    MidSide,
+    /// Each channel is stretched on its own, suiting dual-mono dialog
+    Independent,
+}
+
+impl From<ChannelCoupling> for bungee_channel_coupling_t {
+    fn from(coupling: ChannelCoupling) -> Self {
+        match coupling {
+            ChannelCoupling::SumLocked => bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED,
+            ChannelCoupling::MidSide => bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE,
+            ChannelCoupling::Independent => bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_INDEPENDENT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::tests::{request, stretch, stretch_interleaved};
+    use crate::{SampleRates, Stretcher, StretcherConfig};
+
+    const RATES: SampleRates = SampleRates { input: 44100, output: 44100 };
+
+    fn tone(frequency: f64, i: usize) -> f32 {
+        (0.4 * (2.0 * PI * frequency * i as f64 / RATES.input as f64).sin()) as f32
+    }
+
+    fn channel(interleaved: &[f32], c: usize) -> Vec<f32> {
+        interleaved.iter().skip(c).step_by(2).copied().collect()
+    }
+
+    #[test]
+    fn mid_side_requires_stereo() {
+        let mut mono = Stretcher::new(RATES, 1).unwrap();
+        assert!(matches!(mono.set_channel_coupling(ChannelCoupling::MidSide), Err(BungeeError::InvalidParam { .. })));
+        assert!(StretcherConfig::new().channel_coupling(ChannelCoupling::MidSide).build(RATES, 1).is_err());
+        let mut stereo = Stretcher::new(RATES, 2).unwrap();
+        assert!(stereo.set_channel_coupling(ChannelCoupling::MidSide).is_ok());
+        for coupling in [ChannelCoupling::SumLocked, ChannelCoupling::Independent] {
+            assert!(mono.set_channel_coupling(coupling).is_ok());
+        }
+    }
+
+    #[test]
+    fn mid_side_keeps_mid_and_side_apart() {
+        // Different tones in mid and side
+        let input: Vec<f32> = (0..RATES.input as usize / 2)
+            .flat_map(|i| {
+                let (mid, side) = (tone(330.0, i), tone(523.0, i));
+                [mid + side, mid - side]
+            })
+            .collect();
+        let mut stretcher = StretcherConfig::new().channel_coupling(ChannelCoupling::MidSide).build(RATES, 2).unwrap();
+        let output = stretch_interleaved(&mut stretcher, &input, request(0.0, 0.75));
+
+        // Each of mid and side comes out as its own mono stretch, to within rounding of the
+        // mid/side transform
+        let mono = |frequency| {
+            let input: Vec<f32> = (0..RATES.input as usize / 2).map(|i| tone(frequency, i)).collect();
+            stretch(&mut Stretcher::new(RATES, 1).unwrap(), &input, request(0.0, 0.75))
+        };
+        let (mid, side) = (mono(330.0), mono(523.0));
+        let (left, right) = (channel(&output, 0), channel(&output, 1));
+        assert_eq!(left.len(), mid.len());
+        for i in 0..left.len() {
+            assert!((left[i] - (mid[i] + side[i])).abs() < 1e-3, "left frame {i}");
+            assert!((right[i] - (mid[i] - side[i])).abs() < 1e-3, "right frame {i}");
+        }
+    }
+
+    #[test]
+    fn independent_channels_match_mono_stretches() {
+        let input: Vec<f32> = (0..RATES.input as usize / 2).flat_map(|i| [tone(220.0, i), tone(587.0, i)]).collect();
+        let mut stretcher = StretcherConfig::new().channel_coupling(ChannelCoupling::Independent).build(RATES, 2).unwrap();
+        let output = stretch_interleaved(&mut stretcher, &input, request(0.0, 1.5));
+
+        for c in 0..2 {
+            let mono = stretch(&mut Stretcher::new(RATES, 1).unwrap(), &channel(&input, c), request(0.0, 1.5));
+            let stereo = channel(&output, c);
+            assert_eq!(stereo.len(), mono.len());
+            let error = stereo.iter().zip(&mono).map(|(s, m)| (s - m).abs()).fold(0.0, f32::max);
+            assert!(error < 1e-4, "channel {c} differs from its mono stretch by {error}");
+        }
+    }
+}
@@ -1,32 +1,149 @@
+use std::ffi::CStr;
+
 use thiserror::Error;
 
+use crate::{bungee_last_error_message, State};
+
+/// Errors of the stretcher and its wrappers
+///
+/// `message` describes the failure: errors reported by the C API carry
+/// `last_error_message()` and those detected in Rust describe the offending values.
 #[derive(Error, Debug)]
 pub enum BungeeError {
-    #[error("Null pointer encountered")]
-    NullPointer,
-    
-    #[error("Invalid parameter")]
-    InvalidParam,
-    
-    #[error("Memory allocation failed")]
-    Memory,
-
-    #[error("Invalid state")]
-    InvalidState,
-
-    #[error("Buffer too small")]
-    BufferTooSmall,
+    #[error("{message}")]
+    NullPointer { message: String },
+
+    #[error("{message}")]
+    InvalidParam { message: String },
+
+    #[error("{message}")]
+    Memory { message: String },
+
+    /// A call was made out of order; `state` is the stretcher's state when known
+    #[error("Invalid state: {message}")]
+    InvalidState { state: Option<State>, message: String },
+
+    #[error("{message}")]
+    BufferTooSmall { message: String },
+
+    #[error("{message}")]
+    InvalidChannelCount { channels: i32, message: String },
+
+    #[error("{message}")]
+    RateOutOfRange { input: i32, output: i32, message: String },
+
+    #[error("{message}")]
+    InvalidHop { hop: usize, message: String },
+
+    #[error("{message}")]
+    InputFrameCountExceeded { required: usize, limit: usize, message: String },
+
+    /// `pitch` and `max_octaves` are known when the pitch was checked in Rust
+    #[error("{message}")]
+    PitchOutOfRange { pitch: Option<f64>, max_octaves: Option<i32>, message: String },
+
+    #[error("{message}")]
+    InvalidPitchOctaves { octaves: i32, message: String },
+
+    #[error("Grain out of order: {message}")]
+    GrainOutOfOrder { message: String },
+
+    #[error("Unknown error code {code}: {message}")]
+    Unknown { code: u32, message: String },
 }
 
 impl From<u32> for BungeeError {
     fn from(error: u32) -> Self {
+        let message = last_error_message();
         match error {
-            1 => BungeeError::NullPointer,    // BUNGEE_NULL_POINTER
-            2 => BungeeError::InvalidParam,    // BUNGEE_INVALID_PARAM
-            3 => BungeeError::Memory,          // BUNGEE_MEMORY
-            4 => BungeeError::InvalidState,    // BUNGEE_INVALID_STATE
-            5 => BungeeError::BufferTooSmall,  // BUNGEE_BUFFER_TOO_SMALL
-            _ => BungeeError::InvalidState,    // Default case
+            1 => BungeeError::NullPointer { message },    // BUNGEE_NULL_POINTER
+            2 => BungeeError::InvalidParam { message },    // BUNGEE_INVALID_PARAM
+            3 => BungeeError::Memory { message },          // BUNGEE_MEMORY
+            4 => BungeeError::InvalidState { state: None, message },  // BUNGEE_INVALID_STATE
+            5 => BungeeError::BufferTooSmall { message },  // BUNGEE_BUFFER_TOO_SMALL
+            8 => BungeeError::GrainOutOfOrder { message },  // BUNGEE_GRAIN_OUT_OF_ORDER
+            9 => BungeeError::PitchOutOfRange {    // BUNGEE_PITCH_OUT_OF_RANGE
+                pitch: None,
+                max_octaves: None,
+                message,
+            },
+            // BUNGEE_INVALID_CHANNEL_COUNT and BUNGEE_RATE_OUT_OF_RANGE only come from
+            // bungee_create, which Stretcher::new reports with the offending values
+            code => BungeeError::Unknown { code, message },
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Description of the most recent failing C call on this thread, empty if none has failed
+pub fn last_error_message() -> String {
+    unsafe {
+        CStr::from_ptr(bungee_last_error_message())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interpolation, SampleRates, Stretcher, StretcherConfig, BUNGEE_MAX_PITCH_OCTAVES, MAX_HOP, MIN_HOP};
+
+    const RATES: SampleRates = SampleRates { input: 44100, output: 44100 };
+
+    #[test]
+    fn codes_map_to_variants() {
+        assert!(matches!(BungeeError::from(1), BungeeError::NullPointer { .. }));
+        assert!(matches!(BungeeError::from(2), BungeeError::InvalidParam { .. }));
+        assert!(matches!(BungeeError::from(3), BungeeError::Memory { .. }));
+        assert!(matches!(BungeeError::from(4), BungeeError::InvalidState { state: None, .. }));
+        assert!(matches!(BungeeError::from(5), BungeeError::BufferTooSmall { .. }));
+        assert!(matches!(BungeeError::from(8), BungeeError::GrainOutOfOrder { .. }));
+        assert!(matches!(
+            BungeeError::from(9),
+            BungeeError::PitchOutOfRange {
+                pitch: None,
+                max_octaves: None,
+                ..
+            }
+        ));
+        assert!(matches!(BungeeError::from(99), BungeeError::Unknown { code: 99, .. }));
+    }
+
+    #[test]
+    fn errors_from_c_carry_the_last_error_message() {
+        let mut stretcher = Stretcher::new(RATES, 1).unwrap();
+        let error = stretcher.set_interpolation(Interpolation::WindowedSinc { taps: 5 }).unwrap_err();
+        let BungeeError::InvalidParam { message } = &error else {
+            panic!("{error:?}");
+        };
+        assert!(!message.is_empty());
+        assert_eq!(*message, last_error_message());
+        assert_eq!(error.to_string(), *message);
+
+        let error = Stretcher::new(RATES, 0).unwrap_err();
+        assert!(matches!(&error, BungeeError::InvalidChannelCount { channels: 0, message } if !message.is_empty()));
+    }
+
+    #[test]
+    fn messages_state_the_limits() {
+        let error = StretcherConfig::new().hop(100).build(RATES, 1).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("100") && message.contains(&format!("{MIN_HOP} to {MAX_HOP}")), "{message}");
+
+        let error = StretcherConfig::new().max_pitch_octaves(0).build(RATES, 1).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains(&format!("1 to {BUNGEE_MAX_PITCH_OCTAVES}")), "{message}");
+
+        let mut stretcher = Stretcher::new(RATES, 1).unwrap();
+        let mut request = crate::Request {
+            position: 0.0,
+            speed: 1.0,
+            pitch: 64.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        let error = stretcher.preroll(&mut request).unwrap_err();
+        assert!(matches!(error, BungeeError::PitchOutOfRange { pitch: Some(64.0), max_octaves: Some(2), .. }));
+        assert!(error.to_string().contains("64"), "{error}");
+    }
+}
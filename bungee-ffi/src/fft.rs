@@ -0,0 +1,315 @@
+//! Real FFTs that can replace the engine's built-in kissfft
+
+use std::os::raw::{c_int, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::slice;
+
+use crate::*;
+
+/// A real FFT the engine can call in place of kissfft
+///
+/// A transform of length `n = 1 << log2_length` maps `n` real samples to `n / 2 + 1` complex
+/// bins, stored as interleaved real and imaginary parts. Both directions are unnormalised and
+/// the forward transform uses a negative exponent, so an inverse of a forward transform
+/// scales by `n`, as with kissfft.
+///
+/// The engine calls these methods through C, so a panic cannot propagate to the caller. It
+/// is caught instead and the transform's output is left as silence.
+pub trait FftBackend: Send {
+    /// Called before a length is first used in each direction
+    fn prepare(&mut self, log2_length: i32, inverse: bool);
+
+    /// Transform `n` real samples to `n / 2 + 1` interleaved complex bins
+    fn forward(&mut self, log2_length: i32, time: &[f32], frequency: &mut [f32]);
+
+    /// Transform `n / 2 + 1` interleaved complex bins to `n` real samples
+    fn inverse(&mut self, log2_length: i32, time: &mut [f32], frequency: &[f32]);
+}
+
+/// Choice of FFT used by a stretcher
+///
+/// The default is RustFFT when the `rustfft` feature is enabled (and the engine is not the
+/// C stub, which has no FFT), else the engine's built-in kissfft.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fft {
+    /// kissfft, built into the engine
+    ///
+    /// The native engine of the `pure-rust` feature has no kissfft and transforms with
+    /// RustFFT instead, so there `Kiss` behaves as `RustFft`.
+    Kiss,
+    /// RustFFT through the realfft crate
+    #[cfg(feature = "rustfft")]
+    RustFft,
+}
+
+impl Default for Fft {
+    fn default() -> Self {
+        #[cfg(all(feature = "rustfft", not(feature = "c-stub")))]
+        return Fft::RustFft;
+        #[cfg(not(all(feature = "rustfft", not(feature = "c-stub"))))]
+        return Fft::Kiss;
+    }
+}
+
+impl Fft {
+    /// Callback table for the C layer, or `None` for the built-in kissfft
+    pub(crate) fn backend(self) -> Option<bungee_fft_backend_t> {
+        match self {
+            Fft::Kiss => None,
+            #[cfg(feature = "rustfft")]
+            Fft::RustFft => Some(backend(RustFft::new())),
+        }
+    }
+}
+
+/// Box `fft` into a callback table, whose context is released by `destroy::<B>`
+pub(crate) fn backend<B: FftBackend>(fft: B) -> bungee_fft_backend_t {
+    bungee_fft_backend_t {
+        context: Box::into_raw(Box::new(fft)) as *mut c_void,
+        prepare: Some(prepare::<B>),
+        forward: Some(forward::<B>),
+        inverse: Some(inverse::<B>),
+        destroy: Some(destroy::<B>),
+    }
+}
+
+fn bin_floats(log2_length: c_int) -> usize {
+    (1 << log2_length) + 2
+}
+
+unsafe extern "C" fn prepare<B: FftBackend>(context: *mut c_void, log2_length: c_int, inverse: bool) {
+    let fft = unsafe { &mut *(context as *mut B) };
+    let _ = catch_unwind(AssertUnwindSafe(|| fft.prepare(log2_length, inverse)));
+}
+
+unsafe extern "C" fn forward<B: FftBackend>(context: *mut c_void, log2_length: c_int, time: *const f32, frequency: *mut f32) {
+    let fft = unsafe { &mut *(context as *mut B) };
+    let (time, frequency) = unsafe {
+        (
+            slice::from_raw_parts(time, 1 << log2_length),
+            slice::from_raw_parts_mut(frequency, bin_floats(log2_length)),
+        )
+    };
+    if catch_unwind(AssertUnwindSafe(|| fft.forward(log2_length, time, &mut *frequency))).is_err() {
+        frequency.fill(0.0);
+    }
+}
+
+unsafe extern "C" fn inverse<B: FftBackend>(context: *mut c_void, log2_length: c_int, time: *mut f32, frequency: *const f32) {
+    let fft = unsafe { &mut *(context as *mut B) };
+    let (time, frequency) = unsafe {
+        (
+            slice::from_raw_parts_mut(time, 1 << log2_length),
+            slice::from_raw_parts(frequency, bin_floats(log2_length)),
+        )
+    };
+    if catch_unwind(AssertUnwindSafe(|| fft.inverse(log2_length, &mut *time, frequency))).is_err() {
+        time.fill(0.0);
+    }
+}
+
+unsafe extern "C" fn destroy<B: FftBackend>(context: *mut c_void) {
+    let fft = unsafe { Box::from_raw(context as *mut B) };
+    let _ = catch_unwind(AssertUnwindSafe(|| drop(fft)));
+}
+
+#[cfg(feature = "rustfft")]
+pub use self::rust_fft::RustFft;
+
+#[cfg(feature = "rustfft")]
+mod rust_fft {
+    use std::sync::Arc;
+
+    use realfft::num_complex::Complex32;
+    use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+
+    use super::FftBackend;
+
+    // Longest transform the engine uses, as its kissfft cache
+    const MAX_LOG2_LENGTH: usize = 16;
+
+    /// RustFFT real transforms through the realfft crate
+    pub struct RustFft {
+        planner: RealFftPlanner<f32>,
+        forward: Vec<Option<Arc<dyn RealToComplex<f32>>>>,
+        inverse: Vec<Option<Arc<dyn ComplexToReal<f32>>>>,
+        time: Vec<f32>,
+        bins: Vec<Complex32>,
+        scratch: Vec<Complex32>,
+    }
+
+    impl RustFft {
+        pub fn new() -> Self {
+            Self {
+                planner: RealFftPlanner::new(),
+                forward: vec![None; MAX_LOG2_LENGTH + 1],
+                inverse: vec![None; MAX_LOG2_LENGTH + 1],
+                time: Vec::new(),
+                bins: Vec::new(),
+                scratch: Vec::new(),
+            }
+        }
+    }
+
+    impl Default for RustFft {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl FftBackend for RustFft {
+        fn prepare(&mut self, log2_length: i32, inverse: bool) {
+            assert!((0..=MAX_LOG2_LENGTH as i32).contains(&log2_length), "transform length 2^{log2_length} out of range");
+            let n = 1 << log2_length;
+            let index = log2_length as usize;
+            let scratch = if inverse {
+                let plan = self.planner.plan_fft_inverse(n);
+                let scratch = plan.get_scratch_len();
+                self.inverse[index] = Some(plan);
+                scratch
+            } else {
+                let plan = self.planner.plan_fft_forward(n);
+                let scratch = plan.get_scratch_len();
+                self.forward[index] = Some(plan);
+                scratch
+            };
+
+            // Size the buffers here so that transforms do not allocate
+            self.time.reserve(n.saturating_sub(self.time.len()));
+            self.bins.reserve((n / 2 + 1).saturating_sub(self.bins.len()));
+            self.scratch.resize(self.scratch.len().max(scratch), Complex32::default());
+        }
+
+        fn forward(&mut self, log2_length: i32, time: &[f32], frequency: &mut [f32]) {
+            let plan = self.forward[log2_length as usize].as_ref().expect("forward transform not prepared");
+
+            // realfft uses its input as workspace, so transform a copy
+            self.time.clear();
+            self.time.extend_from_slice(time);
+            self.bins.resize(time.len() / 2 + 1, Complex32::default());
+            plan.process_with_scratch(&mut self.time, &mut self.bins, &mut self.scratch)
+                .expect("buffer lengths match the plan");
+
+            for (bin, pair) in self.bins.iter().zip(frequency.chunks_exact_mut(2)) {
+                pair[0] = bin.re;
+                pair[1] = bin.im;
+            }
+        }
+
+        fn inverse(&mut self, log2_length: i32, time: &mut [f32], frequency: &[f32]) {
+            let plan = self.inverse[log2_length as usize].as_ref().expect("inverse transform not prepared");
+
+            self.bins.clear();
+            self.bins.extend(frequency.chunks_exact(2).map(|pair| Complex32::new(pair[0], pair[1])));
+
+            // Real signals have real DC and Nyquist bins; realfft rejects anything else
+            let nyquist = self.bins.len() - 1;
+            self.bins[0].im = 0.0;
+            self.bins[nyquist].im = 0.0;
+
+            plan.process_with_scratch(&mut self.bins, time, &mut self.scratch)
+                .expect("buffer lengths match the plan");
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rustfft"))]
+mod tests {
+    use std::f64::consts::PI;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::tests::{request, stretch};
+    use crate::{SampleRates, Stretcher, StretcherConfig};
+
+    const RATES: SampleRates = SampleRates { input: 44100, output: 44100 };
+
+    fn tone() -> Vec<f32> {
+        (0..RATES.input as usize / 2).map(|i| (0.5 * (2.0 * PI * 440.0 * i as f64 / RATES.input as f64).sin()) as f32).collect()
+    }
+
+    /// RustFFT that counts calls, or panics on every forward transform
+    struct Counting {
+        fft: RustFft,
+        calls: Arc<[AtomicUsize; 3]>,
+        panic: bool,
+    }
+
+    impl FftBackend for Counting {
+        fn prepare(&mut self, log2_length: i32, inverse: bool) {
+            self.calls[0].fetch_add(1, Ordering::Relaxed);
+            self.fft.prepare(log2_length, inverse);
+        }
+
+        fn forward(&mut self, log2_length: i32, time: &[f32], frequency: &mut [f32]) {
+            self.calls[1].fetch_add(1, Ordering::Relaxed);
+            assert!(!self.panic, "forward transform failed");
+            self.fft.forward(log2_length, time, frequency);
+        }
+
+        fn inverse(&mut self, log2_length: i32, time: &mut [f32], frequency: &[f32]) {
+            self.calls[2].fetch_add(1, Ordering::Relaxed);
+            self.fft.inverse(log2_length, time, frequency);
+        }
+    }
+
+    fn counting(panic: bool) -> (Counting, Arc<[AtomicUsize; 3]>) {
+        let calls = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0)]);
+        let fft = Counting {
+            fft: RustFft::new(),
+            calls: calls.clone(),
+            panic,
+        };
+        (fft, calls)
+    }
+
+    #[test]
+    fn custom_backend_performs_every_transform() {
+        let (fft, calls) = counting(false);
+        let mut stretcher = StretcherConfig::new().build_with_fft(RATES, 1, fft).unwrap();
+        let output = stretch(&mut stretcher, &tone(), request(0.0, 0.8));
+        let [prepare, forward, inverse] = calls.as_ref().each_ref().map(|count| count.load(Ordering::Relaxed));
+        assert!(prepare > 0 && forward > 0 && inverse > 0, "{prepare} prepare, {forward} forward, {inverse} inverse");
+
+        // The same backend gives the same output as the built-in choice
+        let reference = stretch(&mut Stretcher::new(RATES, 1).unwrap(), &tone(), request(0.0, 0.8));
+        assert_eq!(output, reference);
+    }
+
+    #[test]
+    fn rust_fft_matches_kiss() {
+        let output = |fft| {
+            let mut stretcher = StretcherConfig::new().fft(fft).build(RATES, 2).unwrap();
+            let input: Vec<f32> = tone().iter().flat_map(|&x| [x, -0.5 * x]).collect();
+            crate::tests::stretch_interleaved(&mut stretcher, &input, request(0.0, 0.7))
+        };
+        let (kiss, rust) = (output(Fft::Kiss), output(Fft::RustFft));
+        assert_eq!(kiss.len(), rust.len());
+        let error = kiss.iter().zip(&rust).map(|(k, r)| (k - r).abs()).fold(0.0, f32::max);
+        assert!(error < 1e-4, "largest difference {error}");
+    }
+
+    #[test]
+    fn backend_is_released_with_the_stretcher() {
+        let (fft, calls) = counting(false);
+        let stretcher = StretcherConfig::new().build_with_fft(RATES, 1, fft).unwrap();
+        assert_eq!(Arc::strong_count(&calls), 2);
+        drop(stretcher);
+        assert_eq!(Arc::strong_count(&calls), 1);
+
+        // A failed build hands the backend back to Rust, which releases it
+        let (fft, calls) = counting(false);
+        assert!(StretcherConfig::new().build_with_fft(RATES, 0, fft).is_err());
+        assert_eq!(Arc::strong_count(&calls), 1);
+    }
+
+    #[test]
+    fn panicking_backend_gives_silence() {
+        let (fft, calls) = counting(true);
+        let mut stretcher = StretcherConfig::new().build_with_fft(RATES, 1, fft).unwrap();
+        let output = stretch(&mut stretcher, &tone(), request(0.0, 1.0));
+        assert!(calls[1].load(Ordering::Relaxed) > 0);
+        assert!(output.iter().all(|&x| x == 0.0));
+    }
+}
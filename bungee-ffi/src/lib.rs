@@ -2,14 +2,50 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+pub mod automation;
+mod buffer;
+mod config;
+mod coupling;
 mod error;
+mod fft;
+#[cfg(any(feature = "pure-rust", all(test, feature = "rustfft")))]
+mod native;
+pub mod offline;
+mod output;
+mod push;
+mod resample;
+mod sample;
+mod state;
+mod streaming;
+mod window;
 
+use std::ops::RangeInclusive;
 use std::ptr::NonNull;
-pub use error::BungeeError;
+use std::os::raw::c_void;
+use automation::{Envelope, Parameter};
+pub use buffer::{AudioBuffer, AudioBufferMut, Interleaved, InterleavedMut, Planar, PlanarMut, Strided, StridedMut};
+pub use config::{Quality, StretcherConfig, MAX_HOP, MIN_HOP};
+pub use coupling::ChannelCoupling;
+pub use error::{last_error_message, BungeeError};
+pub use fft::{Fft, FftBackend};
+#[cfg(feature = "rustfft")]
+pub use fft::RustFft;
+pub use output::OutputChunk;
+pub use push::PushStretcher;
+pub use resample::{Interpolation, ResampleMode};
+pub use sample::{Sample, I24};
+pub use state::State;
+pub use streaming::{InputSource, StreamingStretcher};
+pub use window::{Window, MAX_KAISER_BETA, MAX_WINDOW_COEFFICIENTS};
 
 // Include the bindgen generated bindings
+#[cfg(not(feature = "pure-rust"))]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+// Or the same items from the native engine
+#[cfg(feature = "pure-rust")]
+pub use native::{api::*, sys::*};
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct SampleRates {
@@ -30,9 +66,12 @@ impl From<SampleRates> for bungee_sample_rates_t {
 #[derive(Debug, Clone, Copy)]
 pub struct Request {
     pub position: f64,
+    /// Input frames advanced per output frame: negative plays backwards, zero freezes at `position`
     pub speed: f64,
     pub pitch: f64,
     pub reset: bool,
+    /// Keep the spectral envelope in place when `pitch` is not 1, for natural-sounding voices
+    pub preserve_formants: bool,
 }
 
 impl From<Request> for bungee_request_t {
@@ -42,6 +81,40 @@ impl From<Request> for bungee_request_t {
             speed: req.speed,
             pitch: req.pitch,
             reset: req.reset,
+            preserve_formants: req.preserve_formants,
+        }
+    }
+}
+
+impl From<bungee_request_t> for Request {
+    fn from(req: bungee_request_t) -> Self {
+        Self {
+            position: req.position,
+            speed: req.speed,
+            pitch: req.pitch,
+            reset: req.reset,
+            preserve_formants: req.preserve_formants,
+        }
+    }
+}
+
+/// Delays through the stretcher, in frames, for plugin delay compensation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Latency {
+    /// Input frames a grain reads beyond the position at which its output starts
+    pub input: usize,
+    /// Output frames from an input frame being available to its being output
+    pub output: usize,
+    /// Output frames from a request changing to the change being heard
+    pub control: usize,
+}
+
+impl From<bungee_latency_t> for Latency {
+    fn from(latency: bungee_latency_t) -> Self {
+        Self {
+            input: usize::try_from(latency.input).unwrap_or(0),
+            output: usize::try_from(latency.output).unwrap_or(0),
+            control: usize::try_from(latency.control).unwrap_or(0),
         }
     }
 }
@@ -49,6 +122,14 @@ impl From<Request> for bungee_request_t {
 #[derive(Debug)]
 pub struct Stretcher {
     inner: NonNull<bungee_stretcher_t>,
+    channels: usize,
+    output: Vec<f32>,
+    // Channel pointers passed to the C layer, kept to avoid allocating per grain
+    pointers: Vec<*mut c_void>,
+    state: State,
+    // Input frame count of the grain last specified
+    grain_frames: usize,
+    max_pitch_octaves: i32,
 }
 
 /// Initialize the Bungee library
@@ -72,46 +153,133 @@ pub fn cleanup() {
 }
 
 impl Stretcher {
-    /// Create a new stretcher instance
+    /// Create a new stretcher instance with default options, see `StretcherConfig`
     pub fn new(rates: SampleRates, channels: i32) -> Result<Self, BungeeError> {
-        let inner = unsafe {
-            let ptr = bungee_create(rates.into(), channels);
-            NonNull::new(ptr).ok_or(BungeeError::Memory)?
+        let config = bungee_config_t {
+            log2_synthesis_hop: 0,
+            max_pitch_octaves: 0,
+            fft: std::ptr::null(),
+            window: std::ptr::null(),
+        };
+        Self::create(rates, channels, config, Fft::default().backend())
+    }
+
+    /// Create with `fft` in place of `config.fft`, which is released if creation fails
+    pub(crate) fn create(
+        rates: SampleRates,
+        channels: i32,
+        mut config: bungee_config_t,
+        fft: Option<bungee_fft_backend_t>,
+    ) -> Result<Self, BungeeError> {
+        if let Some(fft) = &fft {
+            config.fft = fft;
+        }
+        let ptr = unsafe {
+            bungee_create_with_config(rates.into(), channels, &config)
+        };
+        // On failure the FFT context still belongs to us
+        if let (true, Some(fft)) = (ptr.is_null(), &fft) {
+            if let Some(destroy) = fft.destroy {
+                unsafe {
+                    destroy(fft.context);
+                }
+            }
+        }
+        // bungee_create reports failure with NULL; the cause is in the thread's last error
+        let inner = NonNull::new(ptr).ok_or_else(|| match unsafe { bungee_last_error() } {
+            bungee_error_BUNGEE_INVALID_CHANNEL_COUNT => BungeeError::InvalidChannelCount {
+                channels,
+                message: last_error_message(),
+            },
+            bungee_error_BUNGEE_RATE_OUT_OF_RANGE => BungeeError::RateOutOfRange {
+                input: rates.input,
+                output: rates.output,
+                message: last_error_message(),
+            },
+            code => code.into(),
+        })?;
+        let mut stretcher = Self {
+            inner,
+            channels: channels as usize,
+            output: Vec::new(),
+            pointers: Vec::with_capacity(channels as usize),
+            state: State::Created,
+            grain_frames: 0,
+            max_pitch_octaves: match config.max_pitch_octaves {
+                0 => BUNGEE_DEFAULT_MAX_PITCH_OCTAVES as i32,
+                octaves => octaves,
+            },
         };
-        Ok(Self { inner })
+        stretcher.output = vec![0.0; stretcher.max_output_frame_count() * stretcher.channels];
+        Ok(stretcher)
+    }
+
+    /// Number of audio channels
+    pub fn channel_count(&self) -> usize {
+        self.channels
+    }
+
+    /// Pitch values accepted by `preroll`, `next`, `seek` and pitch automation
+    pub fn pitch_range(&self) -> RangeInclusive<f64> {
+        let max = 2f64.powi(self.max_pitch_octaves);
+        1.0 / max..=max
+    }
+
+    fn check_pitch(&self, pitch: f64) -> Result<(), BungeeError> {
+        // Non-positive and NaN pitch are left to the C layer, which reports InvalidParam
+        if pitch > 0.0 && !self.pitch_range().contains(&pitch) {
+            return Err(BungeeError::PitchOutOfRange {
+                pitch: Some(pitch),
+                max_octaves: Some(self.max_pitch_octaves),
+                message: format!("Pitch {pitch} outside the stretcher's range of +/-{} octaves", self.max_pitch_octaves),
+            });
+        }
+        Ok(())
+    }
+
+    /// Current position in the call sequence
+    pub fn state(&self) -> State {
+        self.state
     }
 
     /// Prepare for processing with initial parameters
-    pub fn preroll(&mut self, request: &Request) -> Result<(), BungeeError> {
-        let c_request = bungee_request_t::from(*request);
+    ///
+    /// Moves `request.position` back so that the pipeline is primed by the time output
+    /// reaches the original position.
+    pub fn preroll(&mut self, request: &mut Request) -> Result<(), BungeeError> {
+        self.check_pitch(request.pitch)?;
+        let mut c_request = bungee_request_t::from(*request);
         let result = unsafe {
-            bungee_preroll(self.inner.as_ptr(), &c_request)
+            bungee_preroll(self.inner.as_ptr(), &mut c_request)
         };
         
         if result == 0 {  // BUNGEE_OK
+            *request = Request::from(c_request);
+            self.state = State::Prerolled;
             Ok(())
         } else {
             Err(result.into())
         }
     }
 
-    /// Specify a grain of audio and get required input range
-    pub fn specify_grain(&mut self, input: &[f32], frame_count: usize) -> Result<(i32, i32), BungeeError> {
+    /// Specify the current grain and get the input frame range `(begin, end)` it requires
+    ///
+    /// `begin` may be negative and `end` may exceed the input length: frames outside
+    /// the caller's audio should be provided as silence.
+    pub fn specify_grain(&mut self) -> Result<(i32, i32), BungeeError> {
+        self.state.check("specify_grain", State::Prerolled)?;
         let mut chunk = bungee_input_chunk_t {
             begin: 0,
             end: 0,
         };
         
         let result = unsafe {
-            bungee_specify_grain(
-                self.inner.as_ptr(),
-                input.as_ptr(),
-                frame_count,
-                &mut chunk
-            )
+            bungee_specify_grain(self.inner.as_ptr(), &mut chunk)
         };
         
         if result == 0 {  // BUNGEE_OK
+            self.state = State::Specified;
+            self.grain_frames = (chunk.end - chunk.begin).max(0) as usize;
             Ok((chunk.begin, chunk.end))
         } else {
             Err(result.into())
@@ -119,49 +287,212 @@ impl Stretcher {
     }
 
     /// Analyze the current grain
-    pub fn analyse_grain(&mut self, data: &[f32], channel_stride: usize) -> Result<(), BungeeError> {
+    ///
+    /// `input` starts with the frames requested by `specify_grain` and may be in any layout,
+    /// for example `Interleaved`, `Planar` or `Strided`. Samples of any `Sample` type are
+    /// converted as they are read.
+    pub fn analyse_grain<T: Sample, B: AudioBuffer<T> + ?Sized>(&mut self, input: &B) -> Result<(), BungeeError> {
+        self.state.check("analyse_grain", State::Specified)?;
+        if input.channel_count() != self.channels || input.frame_count() < self.grain_frames {
+            return Err(BungeeError::InvalidParam {
+                message: format!(
+                    "Invalid input in analyse_grain: {} channels of {} frames, expected {} channels of at least {} frames",
+                    input.channel_count(),
+                    input.frame_count(),
+                    self.channels,
+                    self.grain_frames
+                ),
+            });
+        }
+
+        self.pointers.clear();
+        let frame_stride = input.pointers(&mut self.pointers);
         let result = unsafe {
-            bungee_analyse_grain(
+            bungee_analyse_grain_channels(
                 self.inner.as_ptr(),
-                data.as_ptr(),
-                channel_stride,
+                self.pointers.as_ptr() as *const *const c_void,
+                T::FORMAT,
+                frame_stride,
             )
         };
         
         if result == 0 {  // BUNGEE_OK
+            self.state = State::Analysed;
             Ok(())
         } else {
             Err(result.into())
         }
     }
 
-    /// Synthesize the processed grain
-    pub fn synthesise_grain(&mut self, output: &mut bungee_output_chunk_t) -> Result<(), BungeeError> {
+    /// Synthesize the processed grain into a buffer owned by the stretcher
+    ///
+    /// The returned chunk borrows the stretcher and is valid until the next call on it.
+    pub fn synthesise_grain(&mut self) -> Result<OutputChunk<'_>, BungeeError> {
+        self.state.check("synthesise_grain", State::Analysed)?;
+        let mut output = InterleavedMut::new(&mut self.output, self.channels)?;
+        let chunk = synthesise(self.inner, &mut self.pointers, &mut output)?;
+        self.state = State::Synthesised;
+        let frames = chunk.frame_count as usize;
+        Ok(OutputChunk::new(Interleaved::new(&self.output[..frames * self.channels], self.channels)?, &chunk))
+    }
+
+    /// Synthesize the processed grain into a caller-owned buffer of any layout
+    ///
+    /// `output` must hold at least `max_output_frame_count()` frames, otherwise
+    /// `BufferTooSmall` is returned and the grain may be synthesised again into a larger
+    /// buffer. Samples of any `Sample` type are converted as they are written. The returned
+    /// chunk views the frames written.
+    pub fn synthesise_grain_into<'a, T: Sample, B: AudioBufferMut<T>>(
+        &mut self,
+        output: &'a mut B,
+    ) -> Result<OutputChunk<'a, T, B::View<'a>>, BungeeError> {
+        self.state.check("synthesise_grain_into", State::Analysed)?;
+        if output.channel_count() != self.channels {
+            return Err(BungeeError::InvalidParam {
+                message: format!("Invalid output in synthesise_grain_into: {} channels, expected {}", output.channel_count(), self.channels),
+            });
+        }
+        let chunk = synthesise(self.inner, &mut self.pointers, output)?;
+        self.state = State::Synthesised;
+        let output: &'a B = output;
+        Ok(OutputChunk::new(output.view(chunk.frame_count as usize), &chunk))
+    }
+
+    /// Advance to the next grain
+    pub fn next(&mut self, request: &mut Request) -> Result<(), BungeeError> {
+        self.state.check("next", State::Synthesised)?;
+        self.check_pitch(request.pitch)?;
+        let mut c_request = bungee_request_t::from(*request);
         let result = unsafe {
-            bungee_synthesise_grain(self.inner.as_ptr(), output)
+            bungee_next(self.inner.as_ptr(), &mut c_request)
         };
         
         if result == 0 {  // BUNGEE_OK
+            *request = Request::from(c_request);
+            self.state = State::Prerolled;
             Ok(())
         } else {
             Err(result.into())
         }
     }
 
-    /// Advance to the next grain
-    pub fn next(&mut self, request: &mut Request) -> Result<(), BungeeError> {
+    /// Move playback to `request.position`, crossfading from the current position over
+    /// `crossfade_frames` output frames
+    ///
+    /// Call in place of `next` (or `preroll`): `request` is prerolled as by `preroll`, while
+    /// the chain of grains being left carries on at the speed and pitch of later requests and
+    /// is faded out under the new one. Until the fade ends, grains alternate between the two
+    /// chains, so `specify_grain` may ask for input near the old position, and `next` leaves
+    /// `request` unchanged after a grain of the old chain. A crossfade of 0 frames cuts at
+    /// once, as `preroll`.
+    pub fn seek(&mut self, request: &mut Request, crossfade_frames: usize) -> Result<(), BungeeError> {
+        self.state.check_between_grains("seek")?;
+        let crossfade_frames = i32::try_from(crossfade_frames).map_err(|_| BungeeError::InvalidParam {
+            message: format!("Invalid crossfade: {crossfade_frames} frames"),
+        })?;
+        self.check_pitch(request.pitch)?;
         let mut c_request = bungee_request_t::from(*request);
         let result = unsafe {
-            bungee_next(self.inner.as_ptr(), &mut c_request)
+            bungee_seek(self.inner.as_ptr(), &mut c_request, crossfade_frames)
         };
-        
+
+        if result == 0 {  // BUNGEE_OK
+            *request = Request::from(c_request);
+            self.state = State::Prerolled;
+            Ok(())
+        } else {
+            Err(result.into())
+        }
+    }
+
+    /// Automate `parameter` with `envelope`, replacing any previous envelope
+    ///
+    /// `preroll` and `next` then overwrite the request's speed or pitch with the envelope's
+    /// value for each grain. An envelope without breakpoints removes the automation.
+    ///
+    /// Pitch breakpoints must lie within `pitch_range`. Speed breakpoints may take any value,
+    /// as requests may: a negative speed plays backwards and zero speed freezes playback.
+    pub fn set_automation(&mut self, parameter: Parameter, envelope: &Envelope) -> Result<(), BungeeError> {
+        if parameter == Parameter::Pitch {
+            for breakpoint in &envelope.breakpoints {
+                self.check_pitch(breakpoint.value)?;
+            }
+        }
+        let breakpoints: Vec<bungee_breakpoint_t> = envelope.breakpoints.iter().map(|&b| b.into()).collect();
+        let result = unsafe {
+            bungee_set_automation(
+                self.inner.as_ptr(),
+                parameter.into(),
+                envelope.time_base.into(),
+                breakpoints.as_ptr(),
+                breakpoints.len(),
+            )
+        };
+
+        if result == 0 {  // BUNGEE_OK
+            Ok(())
+        } else {
+            Err(result.into())
+        }
+    }
+
+    /// Select where the engine resamples
+    ///
+    /// The mode applies from the next `preroll`, `specify_grain` or `next`, so it may be
+    /// changed between grains to give each request its own mode.
+    pub fn set_resample_mode(&mut self, mode: ResampleMode) -> Result<(), BungeeError> {
+        let result = unsafe {
+            bungee_set_resample_mode(self.inner.as_ptr(), mode.into())
+        };
+
+        if result == 0 {  // BUNGEE_OK
+            Ok(())
+        } else {
+            Err(result.into())
+        }
+    }
+
+    /// Select the resampling interpolation kernel, applied like `set_resample_mode`
+    ///
+    /// Fails with `InvalidParam` if a windowed sinc has an unsupported number of taps.
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) -> Result<(), BungeeError> {
+        let (interpolation, sinc_taps) = interpolation.to_c();
+        let result = unsafe {
+            bungee_set_interpolation(self.inner.as_ptr(), interpolation, sinc_taps)
+        };
+
+        if result == 0 {  // BUNGEE_OK
+            Ok(())
+        } else {
+            Err(result.into())
+        }
+    }
+
+    /// Set how readily grains are detected as transients, from 0 (off, the default) to 1
+    ///
+    /// Partials that rise sharply in a transient grain have their phase reset, which keeps
+    /// attacks such as kick drums sharp when slowing down.
+    pub fn set_transient_sensitivity(&mut self, sensitivity: f32) -> Result<(), BungeeError> {
+        let result = unsafe {
+            bungee_set_transient_sensitivity(self.inner.as_ptr(), sensitivity)
+        };
+
+        if result == 0 {  // BUNGEE_OK
+            Ok(())
+        } else {
+            Err(result.into())
+        }
+    }
+
+    /// Select how channel phases are coupled, from the next preroll or reset request
+    ///
+    /// `ChannelCoupling::MidSide` requires a stereo stretcher.
+    pub fn set_channel_coupling(&mut self, coupling: ChannelCoupling) -> Result<(), BungeeError> {
+        let result = unsafe {
+            bungee_set_channel_coupling(self.inner.as_ptr(), coupling.into())
+        };
+
         if result == 0 {  // BUNGEE_OK
-            *request = Request {
-                position: c_request.position,
-                speed: c_request.speed,
-                pitch: c_request.pitch,
-                reset: c_request.reset,
-            };
             Ok(())
         } else {
             Err(result.into())
@@ -169,11 +500,72 @@ impl Stretcher {
     }
 
     /// Check if all grains have been processed
+    ///
+    /// A seek's crossfade counts as output still to come until it completes.
     pub fn is_flushed(&self) -> bool {
         unsafe {
             bungee_is_flushed(self.inner.as_ptr())
         }
     }
+
+    /// Maximum number of input frames a single grain may request
+    pub fn max_input_frame_count(&self) -> usize {
+        unsafe {
+            bungee_max_input_frame_count(self.inner.as_ptr())
+        }
+    }
+
+    /// Maximum number of output frames a single grain may produce
+    pub fn max_output_frame_count(&self) -> usize {
+        unsafe {
+            bungee_max_output_frame_count(self.inner.as_ptr())
+        }
+    }
+
+    /// Latency at the current sample rates, hop and the pitch of the last `preroll` or `next`
+    ///
+    /// A host that compensates plugin delay reports `output`; `control` is how far ahead of
+    /// the output a speed or pitch change must be requested to be heard on time.
+    pub fn latency(&self) -> Latency {
+        unsafe {
+            bungee_latency(self.inner.as_ptr())
+        }.into()
+    }
+}
+
+/// Run `bungee_synthesise_grain_channels` into `output`, returning the filled-in chunk descriptor
+fn synthesise<T: Sample, B: AudioBufferMut<T> + ?Sized>(
+    inner: NonNull<bungee_stretcher_t>,
+    pointers: &mut Vec<*mut c_void>,
+    output: &mut B,
+) -> Result<bungee_output_chunk_t, BungeeError> {
+    let empty = bungee_request_t::from(Request {
+        position: f64::NAN,
+        speed: 0.0,
+        pitch: 1.0,
+        reset: false,
+        preserve_formants: false,
+    });
+    let mut chunk = bungee_output_chunk_t {
+        data: std::ptr::null_mut(),
+        frame_count: output.frame_count().min(i32::MAX as usize) as i32,
+        channel_stride: 0,
+        request: [empty; 2],
+        first_position: f64::NAN,
+        last_position: f64::NAN,
+    };
+
+    pointers.clear();
+    let frame_stride = output.pointers_mut(pointers);
+    let result = unsafe {
+        bungee_synthesise_grain_channels(inner.as_ptr(), &mut chunk, pointers.as_ptr(), T::FORMAT, frame_stride)
+    };
+
+    if result == 0 {  // BUNGEE_OK
+        Ok(chunk)
+    } else {
+        Err(result.into())
+    }
 }
 
 impl Drop for Stretcher {
@@ -186,4 +578,358 @@ impl Drop for Stretcher {
 
 // Implement Send and Sync for Stretcher as it's thread-safe
 unsafe impl Send for Stretcher {}
-unsafe impl Sync for Stretcher {} 
\ No newline at end of file
+unsafe impl Sync for Stretcher {} 
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    const RATE: i32 = 44100;
+    const RATES: SampleRates = SampleRates { input: RATE, output: RATE };
+
+    /// Mono 440 Hz tone of amplitude 0.5
+    fn tone(frames: usize) -> Vec<f32> {
+        (0..frames).map(|i| (0.5 * (2.0 * PI * 440.0 * i as f64 / RATE as f64).sin()) as f32).collect()
+    }
+
+    pub(crate) fn request(position: f64, speed: f64) -> Request {
+        Request {
+            position,
+            speed,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        }
+    }
+
+    /// Run the grain loop over mono `input` from `request` until flushed, returning the output
+    pub(crate) fn stretch<T: Sample>(stretcher: &mut Stretcher, input: &[T], request: Request) -> Vec<T> {
+        stretch_interleaved(stretcher, input, request)
+    }
+
+    /// As `stretch`, for interleaved input with the stretcher's channel count
+    pub(crate) fn stretch_interleaved<T: Sample>(stretcher: &mut Stretcher, input: &[T], request: Request) -> Vec<T> {
+        let channels = stretcher.channel_count();
+        let frames = input.len() / channels;
+        let mut buffer = Vec::new();
+        stretch_grains(
+            stretcher,
+            frames,
+            request,
+            |stretcher, begin, end| {
+                let grain: Vec<T> = (begin..end)
+                    .flat_map(|i| (0..channels).map(move |c| (i, c)))
+                    .map(|(i, c)| usize::try_from(i).ok().filter(|&i| i < frames).map_or(T::default(), |i| input[i * channels + c]))
+                    .collect();
+                stretcher.analyse_grain(&Interleaved::new(&grain, channels).unwrap())
+            },
+            |stretcher| {
+                buffer.resize(stretcher.max_output_frame_count() * channels, T::default());
+                let mut output = InterleavedMut::new(&mut buffer, channels).unwrap();
+                stretcher.synthesise_grain_into(&mut output).unwrap().interleaved().to_vec()
+            },
+        )
+    }
+
+    /// Run the grain loop over `frames` input frames from `request` until flushed: `analyse` feeds
+    /// the stretcher each grain's frames and `synthesise` returns each chunk, interleaved
+    pub(crate) fn stretch_grains<T>(
+        stretcher: &mut Stretcher,
+        frames: usize,
+        mut request: Request,
+        mut analyse: impl FnMut(&mut Stretcher, i32, i32) -> Result<(), BungeeError>,
+        mut synthesise: impl FnMut(&mut Stretcher) -> Vec<T>,
+    ) -> Vec<T> {
+        stretcher.preroll(&mut request).unwrap();
+        let max_output = stretcher.max_output_frame_count() * stretcher.channel_count();
+        let mut output = Vec::new();
+        while !request.position.is_nan() || !stretcher.is_flushed() {
+            let (begin, end) = stretcher.specify_grain().unwrap();
+            assert!((end - begin) as usize <= stretcher.max_input_frame_count());
+            analyse(stretcher, begin, end).unwrap();
+            let chunk = synthesise(stretcher);
+            assert!(chunk.len() <= max_output);
+            output.extend(chunk);
+            request.reset = false;
+            stretcher.next(&mut request).unwrap();
+            if request.position >= frames as f64 {
+                request.position = f64::NAN;
+            }
+        }
+        output
+    }
+
+    fn rms(samples: &[f32]) -> f64 {
+        (samples.iter().map(|&x| (x as f64).powi(2)).sum::<f64>() / samples.len().max(1) as f64).sqrt()
+    }
+
+    #[test]
+    fn grain_loop_stretches_a_tone() {
+        let input = tone(RATE as usize);
+        let mut stretcher = Stretcher::new(RATES, 1).unwrap();
+        let output = stretch(&mut stretcher, &input, request(0.0, 0.5));
+
+        // Twice the input, plus the preroll and the tail of the last grains
+        assert!(output.len() >= 2 * input.len() && output.len() < 2 * input.len() + 8192, "{} frames", output.len());
+        let middle = &output[output.len() / 4..output.len() * 3 / 4];
+        assert!((rms(middle) / rms(&input) - 1.0).abs() < 0.05, "rms {} against {}", rms(middle), rms(&input));
+    }
+
+    #[test]
+    fn latency_covers_the_pitch_range_and_resample_modes() {
+        for mode in [ResampleMode::AutoOut, ResampleMode::AutoIn, ResampleMode::ForceIn] {
+            for pitch in [0.25, 1.0, 4.0] {
+                let mut stretcher = StretcherConfig::new().resample_mode(mode).build(RATES, 1).unwrap();
+                let mut request = Request { pitch, ..request(0.0, 1.0) };
+                stretcher.preroll(&mut request).unwrap();
+                let latency = stretcher.latency();
+                assert!(latency.input > 0 && latency.control > 0, "{mode:?} at pitch {pitch}: {latency:?}");
+                assert!(latency.control < latency.output, "{mode:?} at pitch {pitch}: {latency:?}");
+                assert!(latency.output.abs_diff(latency.input) <= 2, "{mode:?} at pitch {pitch}: {latency:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn negative_latency_clamps_to_zero() {
+        let latency = Latency::from(bungee_latency_t { input: -1, output: 3072, control: i32::MIN });
+        assert_eq!(latency, Latency { input: 0, output: 3072, control: 0 });
+    }
+
+    #[test]
+    fn invalid_creation_is_reported() {
+        assert!(matches!(Stretcher::new(RATES, 0), Err(BungeeError::InvalidChannelCount { channels: 0, .. })));
+        let rates = SampleRates { input: 0, output: RATE };
+        assert!(matches!(Stretcher::new(rates, 1), Err(BungeeError::RateOutOfRange { input: 0, .. })));
+    }
+
+    /// Energy-weighted mean frequency of `samples` over harmonics of `fundamental` up to 4 kHz
+    fn centroid(samples: &[f32], fundamental: f64) -> f64 {
+        let n = samples.len() as f64;
+        let (mut weighted, mut total) = (0.0, 0.0);
+        let mut frequency = fundamental;
+        while frequency < 4000.0 {
+            let w = 2.0 * PI * frequency / RATE as f64;
+            let (mut re, mut im) = (0.0, 0.0);
+            for (i, &x) in samples.iter().enumerate() {
+                let x = x as f64 * (0.5 - 0.5 * (2.0 * PI * i as f64 / n).cos());
+                re += x * (w * i as f64).cos();
+                im += x * (w * i as f64).sin();
+            }
+            weighted += frequency * (re * re + im * im);
+            total += re * re + im * im;
+            frequency += fundamental;
+        }
+        weighted / total
+    }
+
+    #[test]
+    fn formants_stay_put_when_preserved() {
+        // Harmonics of 110 Hz shaped by a resonance at 1 kHz
+        let input: Vec<f32> = (0..RATE as usize)
+            .map(|i| {
+                let t = i as f64 / RATE as f64;
+                (1..36).map(|h| {
+                    let f = 110.0 * h as f64;
+                    let gain = 1.0 / (1.0 + ((f - 1000.0) / 400.0).powi(2));
+                    0.1 * gain * (2.0 * PI * f * t).sin()
+                }).sum::<f64>() as f32
+            })
+            .collect();
+        let middle = |output: &[f32]| output[output.len() / 4..output.len() * 3 / 4].to_vec();
+        let original = centroid(&middle(&input), 110.0);
+
+        let pitch = 1.5;
+        let shifted = |preserve_formants| {
+            let mut stretcher = Stretcher::new(RATES, 1).unwrap();
+            let request = Request {
+                pitch,
+                preserve_formants,
+                ..request(0.0, 1.0)
+            };
+            centroid(&middle(&stretch(&mut stretcher, &input, request)), 110.0 * pitch)
+        };
+
+        let plain = shifted(false);
+        let preserved = shifted(true);
+        assert!((plain / original - pitch).abs() < 0.1, "centroid {plain} Hz from {original} Hz without preservation");
+        // The cepstral envelope is smooth, so the resonance is kept only approximately
+        assert!((preserved / original - 1.0).abs() < 0.15, "centroid {preserved} Hz from {original} Hz with preservation");
+    }
+
+    #[test]
+    fn transient_sensitivity_must_be_in_range() {
+        let mut stretcher = Stretcher::new(RATES, 1).unwrap();
+        for sensitivity in [0.0, 0.5, 1.0] {
+            assert!(stretcher.set_transient_sensitivity(sensitivity).is_ok(), "{sensitivity}");
+        }
+        for sensitivity in [-0.1, 1.1, f32::NAN] {
+            let result = stretcher.set_transient_sensitivity(sensitivity);
+            assert!(matches!(result, Err(BungeeError::InvalidParam { .. })), "{sensitivity}");
+        }
+    }
+
+    #[test]
+    fn transient_detection_sharpens_attacks() {
+        // A decaying 2 kHz burst every quarter second over a quiet tone
+        let input: Vec<f32> = (0..RATE as usize)
+            .map(|i| {
+                let t = (i % (RATE as usize / 4)) as f64 / RATE as f64;
+                let burst = (-t * 200.0).exp() * (2.0 * PI * 2000.0 * t).sin();
+                (0.5 * burst + 0.05 * (2.0 * PI * 220.0 * i as f64 / RATE as f64).sin()) as f32
+            })
+            .collect();
+        let peak = |sensitivity| {
+            let mut stretcher = Stretcher::new(RATES, 1).unwrap();
+            stretcher.set_transient_sensitivity(sensitivity).unwrap();
+            let output = stretch(&mut stretcher, &input, request(0.0, 0.5));
+            output.iter().fold(0.0f32, |peak, x| peak.max(x.abs()))
+        };
+        let (smeared, sharp) = (peak(0.0), peak(1.0));
+        assert!(sharp > 1.1 * smeared, "peak {sharp} with detection, {smeared} without");
+    }
+
+    /// 440 Hz for the first half of `frames`, then 1 kHz, so that each half is heard apart
+    fn two_tones(frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| {
+                let frequency = if i < frames / 2 { 440.0 } else { 1000.0 };
+                (0.5 * (2.0 * PI * frequency * i as f64 / RATE as f64).sin()) as f32
+            })
+            .collect()
+    }
+
+    /// Amplitude of the `frequency` Hz component of `block`
+    fn amplitude(block: &[f32], frequency: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, &x) in block.iter().enumerate() {
+            let phase = 2.0 * PI * frequency * i as f64 / RATE as f64;
+            re += x as f64 * phase.cos();
+            im += x as f64 * phase.sin();
+        }
+        2.0 * (re * re + im * im).sqrt() / block.len() as f64
+    }
+
+    /// Output, with the input position of each frame, of `stretch_seeking`
+    struct Seeking {
+        output: Vec<f32>,
+        positions: Vec<f64>,
+        /// Output frame at which the first seek was made
+        seek_frame: usize,
+    }
+
+    /// As `stretch` over mono `input` at unit speed, seeking in place of `next` to `to` with
+    /// `crossfade` frames once the request reaches `from`, for each `(from, to, crossfade)` of
+    /// `seeks` in turn; if `retry`, each grain is first offered a buffer one frame too small
+    fn stretch_seeking(stretcher: &mut Stretcher, input: &[f32], seeks: &[(f64, f64, usize)], retry: bool) -> Seeking {
+        let mut request = request(0.0, 1.0);
+        stretcher.preroll(&mut request).unwrap();
+        let mut small = vec![0.0f32; stretcher.max_output_frame_count() - 1];
+        let mut seeking = Seeking { output: Vec::new(), positions: Vec::new(), seek_frame: 0 };
+        let mut pending = seeks.iter();
+        let mut next_seek = pending.next();
+        let mut grains = 0;
+        while !request.position.is_nan() || !stretcher.is_flushed() {
+            grains += 1;
+            assert!(grains < 1000, "grain loop did not finish");
+            let (begin, end) = stretcher.specify_grain().unwrap();
+            let grain: Vec<f32> = (begin..end).map(|i| usize::try_from(i).ok().and_then(|i| input.get(i)).map_or(0.0, |&x| x)).collect();
+            stretcher.analyse_grain(&Interleaved::new(&grain, 1).unwrap()).unwrap();
+            if retry {
+                let result = stretcher.synthesise_grain_into(&mut InterleavedMut::new(&mut small, 1).unwrap()).map(|_| ());
+                assert!(matches!(result, Err(BungeeError::BufferTooSmall { .. })));
+            }
+            let chunk = stretcher.synthesise_grain().unwrap();
+            seeking.output.extend_from_slice(chunk.interleaved());
+            let position = chunk.first_position().unwrap_or(f64::NAN);
+            seeking.positions.extend(std::iter::repeat_n(position, chunk.frame_count()));
+            request.reset = false;
+
+            let mut sought = false;
+            while let Some(&(from, to, crossfade)) = next_seek {
+                if request.position.is_nan() || request.position < from {
+                    break;
+                }
+                if seeking.seek_frame == 0 {
+                    seeking.seek_frame = seeking.output.len();
+                }
+                request.position = to;
+                stretcher.seek(&mut request, crossfade).unwrap();
+                assert!(crossfade == 0 || !stretcher.is_flushed(), "flushed during a crossfade");
+                next_seek = pending.next();
+                sought = true;
+            }
+            if !sought {
+                stretcher.next(&mut request).unwrap();
+            }
+            if request.position >= input.len() as f64 {
+                request.position = f64::NAN;
+            }
+        }
+        assert!(next_seek.is_none(), "not every seek was made");
+        seeking
+    }
+
+    /// Check that `seeking`, from 440 Hz to 1 kHz, holds its level across the seek and then
+    /// plays from beyond `to`
+    fn assert_seek_is_heard(seeking: &Seeking, to: f64) {
+        let seek_frame = seeking.seek_frame;
+        for block in seeking.output[seek_frame - 8192..seek_frame + 24576].chunks(1024) {
+            let (old, new) = (amplitude(block, 440.0), amplitude(block, 1000.0));
+            let level = (old * old + new * new).sqrt();
+            assert!((0.4..0.6).contains(&level), "level {level} across the seek");
+        }
+        assert!(seeking.positions[seek_frame + 24576..].iter().all(|&p| p.is_nan() || p > to), "output did not move to {to}");
+    }
+
+    #[test]
+    fn seek_survives_a_buffer_too_small() {
+        let input = two_tones(4 * RATE as usize);
+        let seeks = [(RATE as f64, 3.0 * RATE as f64, 8192)];
+        let plain = stretch_seeking(&mut Stretcher::new(RATES, 1).unwrap(), &input, &seeks, false);
+        let retried = stretch_seeking(&mut Stretcher::new(RATES, 1).unwrap(), &input, &seeks, true);
+        assert_seek_is_heard(&retried, 3.0 * RATE as f64);
+        // A grain offered too small a buffer is synthesised afresh, not lost
+        assert_eq!(plain.output, retried.output);
+    }
+
+    #[test]
+    fn seek_twice_in_a_row() {
+        let input = two_tones(4 * RATE as usize);
+        // Seeking again before any grain, and again while the first seek's new chain primes
+        for seeks in [
+            [(RATE as f64, 2.5 * RATE as f64, 4096), (RATE as f64, 3.0 * RATE as f64, 8192)],
+            [(RATE as f64, 2.5 * RATE as f64, 8192), (2.0 * RATE as f64, 3.0 * RATE as f64, 8192)],
+        ] {
+            let seeking = stretch_seeking(&mut Stretcher::new(RATES, 1).unwrap(), &input, &seeks, false);
+            assert_seek_is_heard(&seeking, 3.0 * RATE as f64);
+        }
+    }
+
+    #[test]
+    fn seek_near_the_end_of_the_source() {
+        let frames = 2 * RATE as usize;
+        let input = two_tones(frames);
+        let to = (frames - 1024) as f64;
+        let seeking = stretch_seeking(&mut Stretcher::new(RATES, 1).unwrap(), &input, &[(RATE as f64 / 2.0, to, 8192)], false);
+        let after = &seeking.output[seeking.seek_frame..];
+        assert!(after.len() < RATE as usize / 4, "{} frames after the seek", after.len());
+        assert!(after.iter().all(|x| x.is_finite()));
+        assert!(seeking.positions[seeking.seek_frame..].iter().all(|&p| p.is_nan() || p < RATE as f64 || p > to - 8192.0));
+    }
+
+    #[test]
+    fn seek_without_crossfade_cuts_at_once() {
+        let input = two_tones(4 * RATE as usize);
+        let to = 3.0 * RATE as f64;
+        let seeking = stretch_seeking(&mut Stretcher::new(RATES, 1).unwrap(), &input, &[(RATE as f64, to, 0)], false);
+        // Only the overlap of grains already synthesised follows from the old position
+        let positions = &seeking.positions[seeking.seek_frame..];
+        let old = positions.iter().take_while(|&&p| p < to - 8192.0).count();
+        assert!(old <= 2048, "{old} frames from the old position after the cut");
+        assert!(positions[old..].iter().all(|&p| p.is_nan() || p > to - 8192.0), "old position heard after the cut");
+        let block = &seeking.output[seeking.seek_frame + 8192..][..4096];
+        assert!(amplitude(block, 440.0) < 0.05 && amplitude(block, 1000.0) > 0.4);
+    }
+}
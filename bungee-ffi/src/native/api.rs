@@ -0,0 +1,1027 @@
+//! bungee_c.h implemented on the native engine, as bungee_basic.c, bungee_error.c and
+//! bungee_window.c
+//!
+//! Each function has the signature bindgen gives its C counterpart, so that the safe wrappers
+//! call either without change. Validation and error messages follow bungee_basic.c.
+
+// The safety contract of each function is that of its declaration in bungee_c.h
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::{Cell, RefCell};
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+use std::slice;
+
+use super::basic::{Basic, Chunk};
+use super::output::Frames;
+use super::sample;
+use super::window::Shape;
+use crate::*;
+
+thread_local! {
+    static LAST_ERROR: Cell<bungee_error_t> = const { Cell::new(bungee_error_BUNGEE_OK) };
+    static LAST_ERROR_MESSAGE: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Record an error for `bungee_last_error` and `bungee_last_error_message`, returning `code`
+fn set_error(code: bungee_error_t, message: String) -> bungee_error_t {
+    LAST_ERROR.with(|last| last.set(code));
+    LAST_ERROR_MESSAGE.with(|last| *last.borrow_mut() = CString::new(message).unwrap_or_default());
+    code
+}
+
+/// Check the window option and copy it for the engine, as bungee_check_window, recording an
+/// error if it is invalid
+///
+/// # Safety
+/// `window` must be null or valid, with `coefficient_count` readable coefficients if it is valid.
+unsafe fn window_shape(window: *const bungee_window_t) -> Option<Shape> {
+    let Some(window) = (unsafe { window.as_ref() }) else {
+        return Some(Shape::Hann);
+    };
+
+    match window.shape {
+        bungee_window_shape_BUNGEE_WINDOW_HANN => Some(Shape::Hann),
+        bungee_window_shape_BUNGEE_WINDOW_BLACKMAN_HARRIS => Some(Shape::BlackmanHarris),
+        bungee_window_shape_BUNGEE_WINDOW_KAISER => {
+            let beta = window.kaiser_beta;
+            if !(beta >= 0.0 && beta <= BUNGEE_MAX_KAISER_BETA as f32) {
+                set_error(
+                    bungee_error_BUNGEE_INVALID_PARAM,
+                    format!("Kaiser window beta {beta} outside 0..{BUNGEE_MAX_KAISER_BETA}"),
+                );
+                return None;
+            }
+            Some(Shape::Kaiser(beta))
+        }
+        bungee_window_shape_BUNGEE_WINDOW_COSINE_SUM => {
+            let count = window.coefficient_count;
+            if window.coefficients.is_null() || !(1..=BUNGEE_MAX_WINDOW_COEFFICIENTS as i32).contains(&count) {
+                set_error(
+                    bungee_error_BUNGEE_INVALID_PARAM,
+                    format!("Cosine-sum window needs 1..{BUNGEE_MAX_WINDOW_COEFFICIENTS} coefficients, got {count}"),
+                );
+                return None;
+            }
+            let coefficients = unsafe { slice::from_raw_parts(window.coefficients, count as usize) };
+            if let Some(i) = coefficients.iter().position(|c| !c.is_finite()) {
+                set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Cosine-sum window coefficient {i} is not finite"));
+                return None;
+            }
+            // a0 is the window's mean, which sets its gain
+            if !(coefficients[0] > 0.0) {
+                set_error(
+                    bungee_error_BUNGEE_INVALID_PARAM,
+                    format!("Cosine-sum window coefficient a0 {} is not positive", coefficients[0]),
+                );
+                return None;
+            }
+            Some(Shape::CosineSum(coefficients.to_vec()))
+        }
+        shape => {
+            set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Invalid window shape: {shape}"));
+            None
+        }
+    }
+}
+
+/// Progress through the calls that process one grain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// After create, preroll or next: specify is next
+    Ready,
+    /// analyse is next
+    Specified,
+    /// synthesise is next
+    Analysed,
+    /// next is next
+    Synthesised,
+}
+
+impl Stage {
+    fn name(self) -> &'static str {
+        match self {
+            Stage::Ready => "ready to specify",
+            Stage::Specified => "specified",
+            Stage::Analysed => "analysed",
+            Stage::Synthesised => "synthesised",
+        }
+    }
+}
+
+/// Progress of a crossfading seek, as fade_stage_t
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FadeStage {
+    /// One chain plays
+    None,
+    /// The new chain has yet to output: outgoing output passes through
+    Priming,
+    /// Outgoing output is mixed under the new chain's
+    Mixing,
+}
+
+const NO_REQUEST: bungee_request_t = bungee_request_t {
+    position: f64::NAN,
+    speed: 1.0,
+    pitch: 1.0,
+    reset: false,
+    preserve_formants: false,
+};
+
+/// Crossfade of a seek, as the fade_ fields of bungee_basic.c
+struct Fade {
+    stage: FadeStage,
+    /// Outgoing output awaiting mixing, planar, `capacity` frames per channel
+    buffer: Vec<f32>,
+    capacity: usize,
+    buffered: usize,
+    /// Requests bounding the outgoing output in `buffer`
+    request: [bungee_request_t; 2],
+    /// Length of the crossfade in output frames
+    frames: usize,
+    /// Output frames of the crossfade mixed so far
+    position: usize,
+}
+
+impl Fade {
+    /// Grow `buffer` to hold `frames` of outgoing output and one grain of `max_output` more,
+    /// keeping what is buffered, as reserve_fade()
+    fn reserve(&mut self, frames: usize, max_output: usize, channels: usize) -> bungee_error_t {
+        let capacity = frames + max_output;
+        if capacity <= self.capacity {
+            return bungee_error_BUNGEE_OK;
+        }
+        let mut buffer = Vec::new();
+        if buffer.try_reserve_exact(capacity * channels).is_err() {
+            let bytes = capacity * channels * std::mem::size_of::<f32>();
+            return set_error(bungee_error_BUNGEE_MEMORY, format!("Failed to allocate fade buffer: {bytes} bytes"));
+        }
+        buffer.resize(capacity * channels, 0.0);
+        for ch in 0..channels {
+            buffer[ch * capacity..][..self.buffered].copy_from_slice(&self.buffer[ch * self.capacity..][..self.buffered]);
+        }
+        self.buffer = buffer;
+        self.capacity = capacity;
+        bungee_error_BUNGEE_OK
+    }
+
+    /// Frames of outgoing output still to be buffered that will be heard, as fade_heard()
+    ///
+    /// Outgoing output beyond the end of the crossfade, where its gain is zero, is not buffered.
+    fn heard(&self) -> usize {
+        match self.stage {
+            FadeStage::Mixing => (self.frames - self.position).saturating_sub(self.buffered),
+            _ => usize::MAX,
+        }
+    }
+
+    /// Route a grain's output through the crossfade, as fade() in bungee_basic.c, returning
+    /// the output to deliver and whether its frames are then to be consumed from `buffer`
+    fn route<'a>(&'a mut self, outgoing_turn: bool, flushed: bool, output: Chunk<'a>, available: usize) -> (Chunk<'a>, bool) {
+        let channels = self.buffer.len() / self.capacity;
+        let frames = output.frames;
+        if outgoing_turn {
+            let count = frames.frame_count.min(self.heard());
+            for ch in 0..channels {
+                let source = &frames.data[ch * frames.stride..][..count];
+                self.buffer[ch * self.capacity + self.buffered..][..count].copy_from_slice(source);
+            }
+            if self.buffered == 0 {
+                self.request[0] = output.request[0];
+            }
+            self.request[1] = output.request[1];
+            self.buffered += count;
+            return (Chunk { frames: Frames { frame_count: 0, ..frames }, request: output.request }, false);
+        }
+
+        if flushed {
+            // The new chain has been flushed by the caller: nothing remains to fade into
+            self.stage = FadeStage::None;
+            self.buffered = 0;
+            return (Chunk { frames, request: output.request }, false);
+        }
+
+        if self.stage == FadeStage::Priming && (frames.frame_count == 0 || output.request[0].position.is_nan()) {
+            if self.buffered == 0 {
+                return (Chunk { frames: Frames { frame_count: 0, ..frames }, request: output.request }, false);
+            }
+            let frames = Frames { data: &self.buffer, frame_count: self.buffered.min(available), stride: self.capacity };
+            return (Chunk { frames, request: self.request }, true);
+        }
+
+        self.stage = FadeStage::Mixing;
+        for ch in 0..channels {
+            let buffer = &mut self.buffer[ch * self.capacity..][..frames.frame_count];
+            let incoming = &frames.data[ch * frames.stride..][..frames.frame_count];
+            for (i, (y, &x)) in buffer.iter_mut().zip(incoming).enumerate() {
+                let t = ((self.position + i) as f64 + 0.5) / self.frames as f64;
+                let angle = t.min(1.0) * std::f64::consts::FRAC_PI_2;
+                let outgoing = if i < self.buffered { *y } else { 0.0 };
+                *y = (outgoing as f64 * angle.cos() + x as f64 * angle.sin()) as f32;
+            }
+        }
+        self.position += frames.frame_count;
+        let frames = Frames { data: &self.buffer, frame_count: frames.frame_count, stride: self.capacity };
+        (Chunk { frames, request: output.request }, true)
+    }
+
+    /// Remove `frames` frames from the front of `buffer`
+    fn consume(&mut self, frames: usize) {
+        let remaining = self.buffered.saturating_sub(frames);
+        if remaining > 0 {
+            for channel in self.buffer.chunks_exact_mut(self.capacity) {
+                channel.copy_within(frames..frames + remaining, 0);
+            }
+        }
+        self.buffered = remaining;
+    }
+}
+
+/// Run grains without input through `basic` until its pipeline is empty, so that a chain then
+/// primed on it starts from silence, as flush_engine
+fn flush(basic: &mut Basic) {
+    while !basic.is_flushed() {
+        basic.specify_grain(&NO_REQUEST);
+        basic.analyse_grain(&[], 0);
+        basic.synthesise_grain();
+    }
+}
+
+/// What a `*mut bungee_stretcher_t` points to
+struct Instance {
+    basic: Basic,
+    request: bungee_request_t,
+    /// Planar float copy of typed input, `max_input_frame_count` per channel
+    grain: Vec<f32>,
+    channels: i32,
+    max_pitch_octaves: i32,
+    stage: Stage,
+    /// Creation options, kept for the engine a seek creates
+    rates: bungee_sample_rates_t,
+    log2_synthesis_hop: i32,
+    /// Caller's FFT less `destroy`, which the first engine owns
+    fft: Option<bungee_fft_backend_t>,
+    window: Shape,
+    /// Engine running the chain a seek left, `None` before the first crossfading seek
+    outgoing: Option<Basic>,
+    outgoing_request: bungee_request_t,
+    /// Whether the current grain is the outgoing chain's
+    outgoing_turn: bool,
+    fade: Fade,
+}
+
+impl Instance {
+    /// The engine that runs the current grain
+    fn grain_engine(&mut self) -> &mut Basic {
+        self.grain_engine_and_buffer().0
+    }
+
+    /// The engine that runs the current grain, with `grain` to convert its input into
+    fn grain_engine_and_buffer(&mut self) -> (&mut Basic, &mut [f32]) {
+        match &mut self.outgoing {
+            Some(outgoing) if self.outgoing_turn => (outgoing, &mut self.grain),
+            _ => (&mut self.basic, &mut self.grain),
+        }
+    }
+
+    /// Both engines, for settings that apply to each
+    fn engines(&mut self) -> impl Iterator<Item = &mut Basic> {
+        std::iter::once(&mut self.basic).chain(self.outgoing.as_mut())
+    }
+
+    /// Create the engine for the outgoing chain of a seek, with the settings of the first
+    fn create_outgoing(&mut self) {
+        let channels = self.channels as usize;
+        let mut outgoing = Basic::new(self.rates, channels, self.log2_synthesis_hop, self.max_pitch_octaves, self.fft, &self.window);
+        outgoing.timing.automation = self.basic.timing.automation.clone();
+        outgoing.timing.resample_mode = self.basic.timing.resample_mode;
+        outgoing.timing.resample_kernel = self.basic.timing.resample_kernel;
+        outgoing.transient_sensitivity = self.basic.transient_sensitivity;
+        outgoing.requested_channel_coupling = self.basic.requested_channel_coupling;
+        self.outgoing = Some(outgoing);
+    }
+
+    fn check_stage(&self, function: &str, expected: Stage) -> bungee_error_t {
+        if self.stage != expected {
+            return set_error(
+                bungee_error_BUNGEE_GRAIN_OUT_OF_ORDER,
+                format!("{function} called while grain is {}, expected {}", self.stage.name(), expected.name()),
+            );
+        }
+        bungee_error_BUNGEE_OK
+    }
+
+    fn check_grain_format(&self, function: &str, expected: Stage, format: bungee_sample_format_t) -> bungee_error_t {
+        let error = self.check_stage(function, expected);
+        if error != bungee_error_BUNGEE_OK {
+            return error;
+        }
+        if !sample::is_valid(format) {
+            return set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Invalid sample format: {format}"));
+        }
+        bungee_error_BUNGEE_OK
+    }
+
+    fn check_pitch(&self, function: &str, pitch: f64) -> bungee_error_t {
+        if !(pitch > 0.0) {
+            return set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Invalid pitch in {function}: {pitch:.6}"));
+        }
+        let max = 2f64.powi(self.max_pitch_octaves);
+        if pitch < 1.0 / max || pitch > max {
+            return set_error(
+                bungee_error_BUNGEE_PITCH_OUT_OF_RANGE,
+                format!("Pitch {pitch:.6} in {function} is outside +/-{} octaves", self.max_pitch_octaves),
+            );
+        }
+        bungee_error_BUNGEE_OK
+    }
+}
+
+unsafe fn instance<'a>(stretcher: *const bungee_stretcher_t) -> &'a Instance {
+    unsafe { &*(stretcher as *const Instance) }
+}
+
+unsafe fn instance_mut<'a>(stretcher: *mut bungee_stretcher_t) -> &'a mut Instance {
+    unsafe { &mut *(stretcher as *mut Instance) }
+}
+
+/// Input positions of a chunk's first and last frames, evenly spaced from `request[0]` at
+/// the first frame to `request[1]` one frame past the last
+fn set_frame_positions(chunk: &mut bungee_output_chunk_t) {
+    let begin = chunk.request[0].position;
+    let end = chunk.request[1].position;
+    if chunk.frame_count <= 0 || begin.is_nan() || end.is_nan() {
+        chunk.first_position = f64::NAN;
+        chunk.last_position = f64::NAN;
+        return;
+    }
+    chunk.first_position = begin;
+    chunk.last_position = begin + (end - begin) * (chunk.frame_count - 1) as f64 / chunk.frame_count as f64;
+}
+
+pub unsafe fn bungee_init() -> bungee_error_t {
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_cleanup() {}
+
+pub unsafe fn bungee_create(rates: bungee_sample_rates_t, channels: c_int) -> *mut bungee_stretcher_t {
+    unsafe { bungee_create_with_config(rates, channels, std::ptr::null()) }
+}
+
+pub unsafe fn bungee_create_with_config(
+    rates: bungee_sample_rates_t,
+    channels: c_int,
+    config: *const bungee_config_t,
+) -> *mut bungee_stretcher_t {
+    let config = unsafe { config.as_ref() };
+    let log2_synthesis_hop = config.map_or(0, |config| config.log2_synthesis_hop);
+    let mut max_pitch_octaves = config.map_or(0, |config| config.max_pitch_octaves);
+    let fft = config.and_then(|config| unsafe { config.fft.as_ref() }).copied();
+
+    if channels <= 0 {
+        set_error(bungee_error_BUNGEE_INVALID_CHANNEL_COUNT, format!("Invalid channel count: {channels}"));
+        return std::ptr::null_mut();
+    }
+
+    let rate_range = BUNGEE_MIN_SAMPLE_RATE as i32..=BUNGEE_MAX_SAMPLE_RATE as i32;
+    if !rate_range.contains(&rates.input_rate) || !rate_range.contains(&rates.output_rate) {
+        set_error(
+            bungee_error_BUNGEE_RATE_OUT_OF_RANGE,
+            format!(
+                "Sample rates input={}, output={} outside {}..{} Hz",
+                rates.input_rate, rates.output_rate, BUNGEE_MIN_SAMPLE_RATE, BUNGEE_MAX_SAMPLE_RATE
+            ),
+        );
+        return std::ptr::null_mut();
+    }
+
+    let hop_range = BUNGEE_MIN_LOG2_SYNTHESIS_HOP as i32..=BUNGEE_MAX_LOG2_SYNTHESIS_HOP as i32;
+    if log2_synthesis_hop != 0 && !hop_range.contains(&log2_synthesis_hop) {
+        set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            format!(
+                "log2_synthesis_hop {log2_synthesis_hop} outside {BUNGEE_MIN_LOG2_SYNTHESIS_HOP}..{BUNGEE_MAX_LOG2_SYNTHESIS_HOP}"
+            ),
+        );
+        return std::ptr::null_mut();
+    }
+
+    if max_pitch_octaves == 0 {
+        max_pitch_octaves = BUNGEE_DEFAULT_MAX_PITCH_OCTAVES as i32;
+    } else if !(1..=BUNGEE_MAX_PITCH_OCTAVES as i32).contains(&max_pitch_octaves) {
+        set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            format!("max_pitch_octaves {max_pitch_octaves} outside 1..{BUNGEE_MAX_PITCH_OCTAVES}"),
+        );
+        return std::ptr::null_mut();
+    }
+
+    if fft.is_some_and(|fft| fft.forward.is_none() || fft.inverse.is_none()) {
+        set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            "FFT backend must provide forward and inverse transforms".to_string(),
+        );
+        return std::ptr::null_mut();
+    }
+
+    let Some(window) = (unsafe { window_shape(config.map_or(std::ptr::null(), |config| config.window)) }) else {
+        return std::ptr::null_mut();
+    };
+
+    let basic = Basic::new(rates, channels as usize, log2_synthesis_hop, max_pitch_octaves, fft, &window);
+    let grain = vec![0.0; basic.timing.max_input_frame_count() as usize * channels as usize];
+    let fft = fft.map(|fft| bungee_fft_backend_t { destroy: None, ..fft });
+    let instance = Instance {
+        basic,
+        request: bungee_request_t {
+            position: 0.0,
+            speed: 1.0,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        },
+        grain,
+        channels,
+        max_pitch_octaves,
+        stage: Stage::Ready,
+        rates,
+        log2_synthesis_hop,
+        fft,
+        window,
+        outgoing: None,
+        outgoing_request: NO_REQUEST,
+        outgoing_turn: false,
+        fade: Fade {
+            stage: FadeStage::None,
+            buffer: Vec::new(),
+            capacity: 0,
+            buffered: 0,
+            request: [NO_REQUEST; 2],
+            frames: 0,
+            position: 0,
+        },
+    };
+    Box::into_raw(Box::new(instance)) as *mut bungee_stretcher_t
+}
+
+pub unsafe fn bungee_destroy(stretcher: *mut bungee_stretcher_t) {
+    if !stretcher.is_null() {
+        drop(unsafe { Box::from_raw(stretcher as *mut Instance) });
+    }
+}
+
+pub unsafe fn bungee_preroll(stretcher: *mut bungee_stretcher_t, request: *mut bungee_request_t) -> bungee_error_t {
+    if stretcher.is_null() || request.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in preroll: stretcher={stretcher:p}, request={request:p}"),
+        );
+    }
+    let (stretcher, request) = unsafe { (instance_mut(stretcher), &mut *request) };
+
+    let error = stretcher.check_pitch("bungee_preroll", request.pitch);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    stretcher.request = *request;
+    stretcher.basic.timing.preroll(&mut stretcher.request);
+    stretcher.stage = Stage::Ready;
+    stretcher.fade.stage = FadeStage::None;
+    stretcher.fade.buffered = 0;
+    stretcher.outgoing_turn = false;
+    *request = stretcher.request;
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_specify_grain(stretcher: *mut bungee_stretcher_t, chunk: *mut bungee_input_chunk_t) -> bungee_error_t {
+    if stretcher.is_null() || chunk.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in specify_grain: stretcher={stretcher:p}, chunk={chunk:p}"),
+        );
+    }
+    let (stretcher, chunk) = unsafe { (instance_mut(stretcher), &mut *chunk) };
+
+    let error = stretcher.check_stage("bungee_specify_grain", Stage::Ready);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    *chunk = match &mut stretcher.outgoing {
+        Some(outgoing) if stretcher.outgoing_turn => outgoing.specify_grain(&stretcher.outgoing_request),
+        _ => stretcher.basic.specify_grain(&stretcher.request),
+    };
+    stretcher.stage = Stage::Specified;
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_analyse_grain(stretcher: *mut bungee_stretcher_t, input_data: *const f32, channel_stride: usize) -> bungee_error_t {
+    unsafe { bungee_analyse_grain_format(stretcher, input_data as *const c_void, bungee_sample_format_BUNGEE_SAMPLE_F32, channel_stride) }
+}
+
+pub unsafe fn bungee_analyse_grain_format(
+    stretcher: *mut bungee_stretcher_t,
+    input_data: *const c_void,
+    format: bungee_sample_format_t,
+    channel_stride: usize,
+) -> bungee_error_t {
+    if stretcher.is_null() || input_data.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in analyse_grain: stretcher={stretcher:p}, input_data={input_data:p}"),
+        );
+    }
+    let stretcher = unsafe { instance_mut(stretcher) };
+
+    let error = stretcher.check_grain_format("bungee_analyse_grain", Stage::Specified, format);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    let frames = stretcher.grain_engine().input_frame_count();
+    if format == bungee_sample_format_BUNGEE_SAMPLE_F32 {
+        let length = if frames == 0 { 0 } else { (stretcher.channels as usize - 1) * channel_stride + frames };
+        let data = unsafe { slice::from_raw_parts(input_data as *const f32, length) };
+        stretcher.grain_engine().analyse_grain(data, channel_stride);
+    } else {
+        let channels = stretcher.channels as usize;
+        let (engine, grain) = stretcher.grain_engine_and_buffer();
+        let grain = &mut grain[..frames * channels];
+        if frames != 0 {
+            for (ch, destination) in grain.chunks_exact_mut(frames).enumerate() {
+                unsafe { sample::to_float(input_data, format, ch * channel_stride, 1, destination) };
+            }
+        }
+        engine.analyse_grain(grain, frames);
+    }
+    stretcher.stage = Stage::Analysed;
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_analyse_grain_channels(
+    stretcher: *mut bungee_stretcher_t,
+    channel_data: *const *const c_void,
+    format: bungee_sample_format_t,
+    frame_stride: usize,
+) -> bungee_error_t {
+    if stretcher.is_null() || channel_data.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in analyse_grain: stretcher={stretcher:p}, channel_data={channel_data:p}"),
+        );
+    }
+    let stretcher = unsafe { instance_mut(stretcher) };
+
+    let error = stretcher.check_grain_format("bungee_analyse_grain", Stage::Specified, format);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    let frames = stretcher.grain_engine().input_frame_count();
+    let channel_data = unsafe { slice::from_raw_parts(channel_data, stretcher.channels as usize) };
+    if frames != 0 {
+        if let Some(ch) = channel_data.iter().position(|data| data.is_null()) {
+            return set_error(bungee_error_BUNGEE_NULL_POINTER, format!("Null pointer in analyse_grain: channel {ch}"));
+        }
+    }
+
+    let channels = stretcher.channels as usize;
+    let (engine, grain) = stretcher.grain_engine_and_buffer();
+    let grain = &mut grain[..frames * channels];
+    if frames != 0 {
+        for (&source, destination) in channel_data.iter().zip(grain.chunks_exact_mut(frames)) {
+            unsafe { sample::to_float(source, format, 0, frame_stride, destination) };
+        }
+    }
+    engine.analyse_grain(grain, frames);
+    stretcher.stage = Stage::Analysed;
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_synthesise_grain(stretcher: *mut bungee_stretcher_t, chunk: *mut bungee_output_chunk_t) -> bungee_error_t {
+    let data = unsafe { chunk.as_ref() }.map_or(std::ptr::null_mut(), |chunk| chunk.data);
+    unsafe { bungee_synthesise_grain_format(stretcher, chunk, data as *mut c_void, bungee_sample_format_BUNGEE_SAMPLE_F32) }
+}
+
+pub unsafe fn bungee_synthesise_grain_format(
+    stretcher: *mut bungee_stretcher_t,
+    chunk: *mut bungee_output_chunk_t,
+    data: *mut c_void,
+    format: bungee_sample_format_t,
+) -> bungee_error_t {
+    if stretcher.is_null() || chunk.is_null() || data.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in synthesise_grain: stretcher={stretcher:p}, chunk={chunk:p}"),
+        );
+    }
+    let (stretcher, chunk) = unsafe { (instance_mut(stretcher), &mut *chunk) };
+
+    let error = stretcher.check_grain_format("bungee_synthesise_grain", Stage::Analysed, format);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    let channels = stretcher.channels as usize;
+    if chunk.channel_stride < channels {
+        return set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            format!("Invalid channel stride: {} < {} channels", chunk.channel_stride, channels),
+        );
+    }
+
+    let step = chunk.channel_stride;
+    unsafe { synthesise(stretcher, chunk, |ch| (data, ch), format, step) }
+}
+
+pub unsafe fn bungee_synthesise_grain_channels(
+    stretcher: *mut bungee_stretcher_t,
+    chunk: *mut bungee_output_chunk_t,
+    channel_data: *const *mut c_void,
+    format: bungee_sample_format_t,
+    frame_stride: usize,
+) -> bungee_error_t {
+    if stretcher.is_null() || chunk.is_null() || channel_data.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in synthesise_grain: stretcher={stretcher:p}, chunk={chunk:p}"),
+        );
+    }
+    let (stretcher, chunk) = unsafe { (instance_mut(stretcher), &mut *chunk) };
+
+    let error = stretcher.check_grain_format("bungee_synthesise_grain", Stage::Analysed, format);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    let channel_data = unsafe { slice::from_raw_parts(channel_data, stretcher.channels as usize) };
+    if let Some(ch) = channel_data.iter().position(|data| data.is_null()) {
+        return set_error(bungee_error_BUNGEE_NULL_POINTER, format!("Null pointer in synthesise_grain: channel {ch}"));
+    }
+
+    if frame_stride == 0 {
+        return set_error(bungee_error_BUNGEE_INVALID_PARAM, "Invalid frame stride: 0".to_string());
+    }
+
+    unsafe { synthesise(stretcher, chunk, |ch| (channel_data[ch], 0), format, frame_stride) }
+}
+
+/// Synthesise the analysed grain, writing frame `i` of channel `c` at index `offset + i * step`
+/// of the buffer that `destination(c)` returns as `(buffer, offset)`
+unsafe fn synthesise(
+    stretcher: &mut Instance,
+    chunk: &mut bungee_output_chunk_t,
+    destination: impl Fn(usize) -> (*mut c_void, usize),
+    format: bungee_sample_format_t,
+    step: usize,
+) -> bungee_error_t {
+    // Checked before the engine consumes the grain, so that it can be synthesised again into a larger buffer
+    let max_output = stretcher.basic.timing.max_output_frame_count();
+    if chunk.frame_count < max_output {
+        return set_error(
+            bungee_error_BUNGEE_BUFFER_TOO_SMALL,
+            format!("Buffer too small: available={}, required={max_output}", chunk.frame_count),
+        );
+    }
+    if stretcher.fade.stage != FadeStage::None && stretcher.outgoing_turn {
+        let fade = &stretcher.fade;
+        let required = (max_output as usize).min(fade.heard());
+        if required > fade.capacity - fade.buffered {
+            return set_error(
+                bungee_error_BUNGEE_BUFFER_TOO_SMALL,
+                format!("Fade buffer too small: buffered={}, required={required}, capacity={}", fade.buffered, fade.capacity),
+            );
+        }
+    }
+
+    let channels = stretcher.channels as usize;
+    let flushed = stretcher.basic.is_flushed();
+    let outgoing_turn = stretcher.outgoing_turn;
+    let output = match &mut stretcher.outgoing {
+        Some(outgoing) if outgoing_turn => outgoing.synthesise_grain(),
+        _ => stretcher.basic.synthesise_grain(),
+    };
+    stretcher.stage = Stage::Synthesised;
+
+    let available = chunk.frame_count as usize;
+    let (output, faded) = match stretcher.fade.stage {
+        FadeStage::None => (output, false),
+        _ => stretcher.fade.route(outgoing_turn, flushed, output, available),
+    };
+
+    let frames = output.frames;
+    for ch in 0..channels {
+        let source = &frames.data[ch * frames.stride..][..frames.frame_count];
+        let (data, offset) = destination(ch);
+        unsafe { sample::from_float(source, data, format, offset, step) };
+    }
+    chunk.frame_count = frames.frame_count as i32;
+    chunk.request = output.request;
+    set_frame_positions(chunk);
+
+    if faded {
+        let fade = &mut stretcher.fade;
+        fade.consume(chunk.frame_count as usize);
+        if fade.stage == FadeStage::Mixing && fade.position >= fade.frames {
+            fade.stage = FadeStage::None;
+            fade.buffered = 0;
+        }
+    }
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_next(stretcher: *mut bungee_stretcher_t, request: *mut bungee_request_t) -> bungee_error_t {
+    if stretcher.is_null() || request.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in next: stretcher={stretcher:p}, request={request:p}"),
+        );
+    }
+    let (stretcher, request) = unsafe { (instance_mut(stretcher), &mut *request) };
+
+    if stretcher.stage == Stage::Specified || stretcher.stage == Stage::Analysed {
+        return set_error(
+            bungee_error_BUNGEE_GRAIN_OUT_OF_ORDER,
+            format!("bungee_next called while grain is {}, expected {}", stretcher.stage.name(), Stage::Synthesised.name()),
+        );
+    }
+
+    let error = stretcher.check_pitch("bungee_next", request.pitch);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    if let Some(outgoing) = stretcher.outgoing.as_mut().filter(|_| stretcher.outgoing_turn) {
+        // The outgoing chain follows the caller's speed and pitch; the new chain has yet to use its request
+        stretcher.outgoing_request.speed = request.speed;
+        stretcher.outgoing_request.pitch = request.pitch;
+        stretcher.outgoing_request.preserve_formants = request.preserve_formants;
+        outgoing.timing.next(&mut stretcher.outgoing_request);
+        let reset = stretcher.request.reset;
+        stretcher.request = *request;
+        stretcher.request.reset |= reset;
+        stretcher.outgoing_turn = false;
+        stretcher.stage = Stage::Ready;
+        return bungee_error_BUNGEE_OK;
+    }
+
+    stretcher.request = *request;
+    stretcher.basic.timing.next(&mut stretcher.request);
+    stretcher.stage = Stage::Ready;
+    stretcher.outgoing_turn = stretcher.fade.stage != FadeStage::None;
+    *request = stretcher.request;
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_seek(stretcher: *mut bungee_stretcher_t, request: *mut bungee_request_t, crossfade_frames: i32) -> bungee_error_t {
+    if stretcher.is_null() || request.is_null() {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in seek: stretcher={stretcher:p}, request={request:p}"),
+        );
+    }
+    let (instance, request) = unsafe { (instance_mut(stretcher), &mut *request) };
+
+    if instance.stage == Stage::Specified || instance.stage == Stage::Analysed {
+        return set_error(
+            bungee_error_BUNGEE_GRAIN_OUT_OF_ORDER,
+            format!("bungee_seek called while grain is {}, expected {}", instance.stage.name(), Stage::Synthesised.name()),
+        );
+    }
+
+    let error = instance.check_pitch("bungee_seek", request.pitch);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    if crossfade_frames < 0 {
+        return set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Invalid crossfade: {crossfade_frames} frames"));
+    }
+
+    if crossfade_frames == 0 {
+        return unsafe { bungee_preroll(stretcher, request) };
+    }
+
+    let max_output = instance.basic.timing.max_output_frame_count() as usize;
+    let error = instance.fade.reserve(crossfade_frames as usize, max_output, instance.channels as usize);
+    if error != bungee_error_BUNGEE_OK {
+        return error;
+    }
+
+    if instance.outgoing.is_none() {
+        instance.create_outgoing();
+    }
+
+    let synthesised = instance.stage == Stage::Synthesised;
+    if instance.fade.stage == FadeStage::Priming {
+        // The new chain is not yet heard: keep the outgoing chain and prime afresh
+        if synthesised && instance.outgoing_turn {
+            instance.outgoing_request.speed = request.speed;
+            instance.outgoing_request.pitch = request.pitch;
+            instance.outgoing_request.preserve_formants = request.preserve_formants;
+            if let Some(outgoing) = instance.outgoing.as_mut() {
+                outgoing.timing.next(&mut instance.outgoing_request);
+            }
+            instance.outgoing_turn = false;
+        } else if synthesised {
+            instance.outgoing_turn = true;
+        }
+    } else {
+        // The chain playing, or being faded in, becomes the outgoing chain
+        let mut continuing = bungee_request_t {
+            speed: request.speed,
+            pitch: request.pitch,
+            preserve_formants: request.preserve_formants,
+            ..instance.request
+        };
+        if synthesised && !instance.outgoing_turn {
+            instance.basic.timing.next(&mut continuing);
+        }
+        if let Some(outgoing) = instance.outgoing.as_mut() {
+            std::mem::swap(&mut instance.basic, outgoing);
+        }
+        instance.outgoing_request = continuing;
+        instance.outgoing_turn = true;
+        instance.fade.buffered = 0;
+    }
+
+    flush(&mut instance.basic);
+    instance.request = *request;
+    instance.basic.timing.preroll(&mut instance.request);
+    instance.stage = Stage::Ready;
+    instance.fade.stage = FadeStage::Priming;
+    instance.fade.frames = crossfade_frames as usize;
+    instance.fade.position = 0;
+    *request = instance.request;
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_set_automation(
+    stretcher: *mut bungee_stretcher_t,
+    parameter: bungee_parameter_t,
+    time_base: bungee_time_base_t,
+    breakpoints: *const bungee_breakpoint_t,
+    count: usize,
+) -> bungee_error_t {
+    if stretcher.is_null() || (count != 0 && breakpoints.is_null()) {
+        return set_error(
+            bungee_error_BUNGEE_NULL_POINTER,
+            format!("Null pointer in set_automation: stretcher={stretcher:p}, breakpoints={breakpoints:p}"),
+        );
+    }
+    let stretcher = unsafe { instance_mut(stretcher) };
+
+    if parameter > bungee_parameter_BUNGEE_PARAMETER_PITCH || time_base > bungee_time_base_BUNGEE_TIME_BASE_OUTPUT || count > i32::MAX as usize {
+        return set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            format!("Invalid automation: parameter={parameter}, time_base={time_base}, count={count}"),
+        );
+    }
+
+    let breakpoints = if count == 0 { &[][..] } else { unsafe { slice::from_raw_parts(breakpoints, count) } };
+    for (i, b) in breakpoints.iter().enumerate() {
+        let valid = b.time.is_finite()
+            && b.value.is_finite()
+            && b.curve <= bungee_curve_BUNGEE_CURVE_EXPONENTIAL
+            && (i == 0 || b.time >= breakpoints[i - 1].time);
+        if !valid {
+            return set_error(
+                bungee_error_BUNGEE_INVALID_PARAM,
+                format!("Invalid breakpoint {i}: time={:.6}, value={:.6}, curve={}", b.time, b.value, b.curve),
+            );
+        }
+        if parameter == bungee_parameter_BUNGEE_PARAMETER_PITCH {
+            let error = stretcher.check_pitch("bungee_set_automation", b.value);
+            if error != bungee_error_BUNGEE_OK {
+                return error;
+            }
+        }
+    }
+
+    for engine in stretcher.engines() {
+        let envelope = engine.timing.automation.envelope(parameter);
+        envelope.time_base = time_base;
+        envelope.breakpoints = breakpoints.to_vec();
+    }
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_set_resample_mode(stretcher: *mut bungee_stretcher_t, mode: bungee_resample_mode_t) -> bungee_error_t {
+    if stretcher.is_null() {
+        return set_error(bungee_error_BUNGEE_NULL_POINTER, "Null pointer in set_resample_mode".to_string());
+    }
+    let stretcher = unsafe { instance_mut(stretcher) };
+
+    if mode > bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN {
+        return set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Invalid resample mode: {mode}"));
+    }
+
+    stretcher.engines().for_each(|engine| engine.timing.resample_mode = mode);
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_set_interpolation(
+    stretcher: *mut bungee_stretcher_t,
+    interpolation: bungee_interpolation_t,
+    sinc_taps: c_int,
+) -> bungee_error_t {
+    if stretcher.is_null() {
+        return set_error(bungee_error_BUNGEE_NULL_POINTER, "Null pointer in set_interpolation".to_string());
+    }
+    let stretcher = unsafe { instance_mut(stretcher) };
+
+    if interpolation > bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC {
+        return set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Invalid interpolation: {interpolation}"));
+    }
+
+    if interpolation == bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC && ![4, 8, 16, 32].contains(&sinc_taps) {
+        return set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            format!("Invalid sinc taps: {sinc_taps}, expected 4, 8, 16 or 32"),
+        );
+    }
+
+    let kernel = super::resample::Kernel::select(interpolation, sinc_taps);
+    stretcher.engines().for_each(|engine| engine.timing.resample_kernel = kernel);
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_set_transient_sensitivity(stretcher: *mut bungee_stretcher_t, sensitivity: f32) -> bungee_error_t {
+    if stretcher.is_null() {
+        return set_error(bungee_error_BUNGEE_NULL_POINTER, "Null pointer in set_transient_sensitivity".to_string());
+    }
+    let stretcher = unsafe { instance_mut(stretcher) };
+
+    if !(0.0..=1.0).contains(&sensitivity) {
+        return set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            format!("Transient sensitivity {sensitivity:.6} outside 0..1"),
+        );
+    }
+
+    stretcher.engines().for_each(|engine| engine.transient_sensitivity = sensitivity);
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_set_channel_coupling(stretcher: *mut bungee_stretcher_t, coupling: bungee_channel_coupling_t) -> bungee_error_t {
+    if stretcher.is_null() {
+        return set_error(bungee_error_BUNGEE_NULL_POINTER, "Null pointer in set_channel_coupling".to_string());
+    }
+    let stretcher = unsafe { instance_mut(stretcher) };
+
+    if coupling > bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_INDEPENDENT {
+        return set_error(bungee_error_BUNGEE_INVALID_PARAM, format!("Invalid channel coupling: {coupling}"));
+    }
+
+    if coupling == bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE && stretcher.channels != 2 {
+        return set_error(
+            bungee_error_BUNGEE_INVALID_PARAM,
+            format!("Mid/side coupling requires 2 channels, not {}", stretcher.channels),
+        );
+    }
+
+    stretcher.engines().for_each(|engine| engine.requested_channel_coupling = coupling);
+    bungee_error_BUNGEE_OK
+}
+
+pub unsafe fn bungee_last_error() -> bungee_error_t {
+    LAST_ERROR.with(|last| last.get())
+}
+
+/// Valid until the next failing call on the same thread
+pub unsafe fn bungee_last_error_message() -> *const c_char {
+    LAST_ERROR_MESSAGE.with(|last| last.borrow().as_ptr())
+}
+
+/// False while a seek's crossfade is in progress, as in bungee_basic.c
+pub unsafe fn bungee_is_flushed(stretcher: *const bungee_stretcher_t) -> bool {
+    if stretcher.is_null() {
+        return true;
+    }
+    let instance = unsafe { instance(stretcher) };
+    instance.fade.stage == FadeStage::None && instance.fade.buffered == 0 && instance.basic.is_flushed()
+}
+
+pub unsafe fn bungee_max_input_frame_count(stretcher: *const bungee_stretcher_t) -> usize {
+    if stretcher.is_null() {
+        return 0;
+    }
+    unsafe { instance(stretcher) }.basic.timing.max_input_frame_count() as usize
+}
+
+pub unsafe fn bungee_max_output_frame_count(stretcher: *const bungee_stretcher_t) -> usize {
+    if stretcher.is_null() {
+        return 0;
+    }
+    unsafe { instance(stretcher) }.basic.timing.max_output_frame_count() as usize
+}
+
+pub unsafe fn bungee_latency(stretcher: *const bungee_stretcher_t) -> bungee_latency_t {
+    if stretcher.is_null() {
+        return bungee_latency_t { input: 0, output: 0, control: 0 };
+    }
+    let instance = unsafe { instance(stretcher) };
+    instance.basic.timing.latency(instance.request.pitch)
+}
@@ -0,0 +1,87 @@
+//! Speed and pitch envelopes applied to requests, as Automation.cpp
+
+use crate::{
+    bungee_breakpoint_t, bungee_curve_BUNGEE_CURVE_EXPONENTIAL, bungee_curve_BUNGEE_CURVE_HOLD,
+    bungee_parameter_BUNGEE_PARAMETER_PITCH, bungee_parameter_t, bungee_request_t,
+    bungee_time_base_BUNGEE_TIME_BASE_INPUT, bungee_time_base_BUNGEE_TIME_BASE_OUTPUT, bungee_time_base_t,
+};
+
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub time_base: bungee_time_base_t,
+    pub breakpoints: Vec<bungee_breakpoint_t>,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            time_base: bungee_time_base_BUNGEE_TIME_BASE_INPUT,
+            breakpoints: Vec::new(),
+        }
+    }
+}
+
+impl Envelope {
+    /// Value at `time`, holding the first and last breakpoint values outside the envelope
+    pub fn value(&self, time: f64) -> f64 {
+        let next = self.breakpoints.partition_point(|b| !(time < b.time));
+        if next == 0 {
+            return self.breakpoints[0].value;
+        }
+        if next == self.breakpoints.len() {
+            return self.breakpoints[next - 1].value;
+        }
+
+        let a = &self.breakpoints[next - 1];
+        let b = &self.breakpoints[next];
+        let x = (time - a.time) / (b.time - a.time);
+
+        match a.curve {
+            bungee_curve_BUNGEE_CURVE_HOLD => a.value,
+            bungee_curve_BUNGEE_CURVE_EXPONENTIAL if a.value * b.value > 0.0 => a.value * (b.value / a.value).powf(x),
+            _ => a.value + (b.value - a.value) * x,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Automation {
+    pub speed: Envelope,
+    pub pitch: Envelope,
+    /// Output frames synthesised since the last grain with reset set, the clock for
+    /// output-based envelopes
+    pub output_position: f64,
+}
+
+impl Automation {
+    pub fn envelope(&mut self, parameter: bungee_parameter_t) -> &mut Envelope {
+        if parameter == bungee_parameter_BUNGEE_PARAMETER_PITCH {
+            &mut self.pitch
+        } else {
+            &mut self.speed
+        }
+    }
+
+    /// Set the request's speed and pitch from their envelopes, if any, at the request's
+    /// position or at `output_position` depending on each envelope's time base
+    pub fn apply(&self, request: &mut bungee_request_t, output_position: f64) {
+        if request.position.is_nan() {
+            return;
+        }
+
+        let time = |envelope: &Envelope| {
+            if envelope.time_base == bungee_time_base_BUNGEE_TIME_BASE_OUTPUT {
+                output_position
+            } else {
+                request.position
+            }
+        };
+
+        if !self.speed.breakpoints.is_empty() {
+            request.speed = self.speed.value(time(&self.speed));
+        }
+        if !self.pitch.breakpoints.is_empty() {
+            request.pitch = self.pitch.value(time(&self.pitch));
+        }
+    }
+}
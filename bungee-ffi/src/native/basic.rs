@@ -0,0 +1,227 @@
+//! The phase-vocoder stretcher, as Basic.cpp
+
+use std::f32::consts::PI;
+
+use realfft::num_complex::Complex32;
+
+use super::fourier::{bin_count, Transforms};
+use super::formants;
+use super::grain::Grains;
+use super::input::Input;
+use super::output::{Frames, Output, Segment};
+use super::partials;
+use super::phase;
+use super::synthesis;
+use super::timing::Timing;
+use super::window::{self, Shape};
+use super::Strided;
+use crate::{
+    bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE, bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED,
+    bungee_channel_coupling_t, bungee_fft_backend_t, bungee_input_chunk_t, bungee_request_t, bungee_sample_rates_t,
+};
+
+/// Output of one grain, with the requests of the grains that bound it
+pub struct Chunk<'a> {
+    pub frames: Frames<'a>,
+    pub request: [bungee_request_t; 2],
+}
+
+pub struct Basic {
+    pub timing: Timing,
+    transforms: Transforms,
+    input: Input,
+    pub grains: Grains,
+    output: Output,
+    formants: formants::Envelope,
+    pub transient_sensitivity: f32,
+    channel_coupling: bungee_channel_coupling_t,
+    pub requested_channel_coupling: bungee_channel_coupling_t,
+}
+
+impl Basic {
+    pub fn new(
+        sample_rates: bungee_sample_rates_t,
+        channel_count: usize,
+        log2_synthesis_hop_override: i32,
+        max_pitch_octaves: i32,
+        fourier: Option<bungee_fft_backend_t>,
+        window: &Shape,
+    ) -> Self {
+        let timing = Timing::new(sample_rates, log2_synthesis_hop_override, max_pitch_octaves);
+        let hop = timing.log2_synthesis_hop;
+        let mut transforms = Transforms::new(fourier);
+        let mut input = Input::new(hop, channel_count, &mut transforms, window);
+        let grains = Grains::new(hop, channel_count);
+        let mut output = Output::new(&mut transforms, hop, channel_count, timing.max_output_frame_count() as usize, window);
+        window::normalise(hop, &mut input.analysis_window_basic, &mut output.synthesis_window);
+        Self {
+            input,
+            grains,
+            output,
+            formants: formants::Envelope::new(hop + 3, 8.max(sample_rates.input_rate / 1000)),
+            transforms,
+            timing,
+            transient_sensitivity: 0.0,
+            channel_coupling: bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED,
+            requested_channel_coupling: bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED,
+        }
+    }
+
+    pub fn specify_grain(&mut self, request: &bungee_request_t) -> bungee_input_chunk_t {
+        self.grains.rotate();
+
+        if request.reset {
+            self.timing.automation.output_position = 0.0;
+            self.channel_coupling = self.requested_channel_coupling;
+        }
+
+        let timing = &self.timing;
+        let (grain, previous) = self.grains.pair_mut(0);
+        grain.channel_coupling = self.channel_coupling;
+        grain.specify(
+            request,
+            previous,
+            timing.sample_rates,
+            timing.log2_synthesis_hop,
+            timing.resample_mode,
+            timing.resample_kernel,
+        )
+    }
+
+    /// Input frames the current grain requires, as returned by `specify_grain`
+    pub fn input_frame_count(&self) -> usize {
+        let chunk = self.grains[0].input_chunk;
+        (chunk.end - chunk.begin).max(0) as usize
+    }
+
+    /// Analyse `data`, holding `input_frame_count()` frames of each channel at `stride`
+    pub fn analyse_grain(&mut self, data: &[f32], stride: usize) {
+        let log2_synthesis_hop = self.timing.log2_synthesis_hop;
+        let (grain, previous) = self.grains.pair_mut(0);
+        grain.valid_bin_count = 0;
+        for track in &mut grain.tracks {
+            track.transient = false;
+        }
+        if !grain.valid() {
+            return;
+        }
+
+        let chunk = Strided {
+            data,
+            stride,
+            rows: (grain.input_chunk.end - grain.input_chunk.begin) as usize,
+            cols: grain.transformed.cols(),
+        };
+        let input = grain.resample_input(chunk, log2_synthesis_hop + 3);
+        let log2_transform_length = self.input.apply_analysis_window(input);
+
+        for c in 0..grain.transformed.cols() {
+            self.transforms.forward(log2_transform_length, self.input.windowed_input.col(c), grain.transformed.col_mut(c));
+        }
+
+        let n = bin_count(grain.log2_transform_length) - 1;
+        let valid_bin_count = ((n as f32 / grain.resample_operations.output.ratio).ceil() as usize).min(n) + 1;
+        grain.valid_bin_count = valid_bin_count;
+        grain.transformed.fill_rows(valid_bin_count, n + 1 - valid_bin_count, Complex32::default());
+
+        grain.log2_transform_length = log2_transform_length;
+
+        if grain.channel_coupling == bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE {
+            let (left, right) = grain.transformed.first_two_cols_mut();
+            for (l, r) in left[..valid_bin_count].iter_mut().zip(&mut right[..valid_bin_count]) {
+                *l = (*l + *r) * 0.5;
+                *r = *l - *r;
+            }
+        }
+
+        for t in 0..grain.track_count() {
+            let columns = grain.track_columns(t);
+            let track = &mut grain.tracks[t];
+            let previous_track = &previous.tracks[t];
+
+            for i in 0..valid_bin_count {
+                let mut x = grain.transformed.col(columns.start)[i];
+                for c in columns.start + 1..columns.end {
+                    x += grain.transformed.col(c)[i];
+                }
+                track.energy[i] = x.re * x.re + x.im * x.im;
+                track.phase[i] = phase::from_radians(x.im.atan2(x.re));
+            }
+
+            partials::enumerate(&mut track.partials, valid_bin_count, &mut track.energy);
+
+            if grain.continuous {
+                partials::suppress_transient_partials(&mut track.partials, &track.energy, &previous_track.energy);
+
+                if self.transient_sensitivity > 0.0 {
+                    let n = valid_bin_count.min(previous.valid_bin_count);
+                    track.transient =
+                        partials::spectral_flux(n, &track.energy, &previous_track.energy) > 1.0 - self.transient_sensitivity;
+                }
+            }
+
+            if grain.request.preserve_formants && grain.request.pitch != 1.0 {
+                self.formants.preserve(
+                    &mut self.transforms,
+                    grain.log2_transform_length,
+                    valid_bin_count,
+                    &track.energy,
+                    &mut grain.transformed,
+                    columns,
+                    grain.request.pitch,
+                );
+            }
+        }
+    }
+
+    pub fn synthesise_grain(&mut self) -> Chunk<'_> {
+        let log2_synthesis_hop = self.timing.log2_synthesis_hop;
+        let (grain, previous) = self.grains.pair_mut(0);
+        if grain.valid() {
+            synthesis::synthesise(log2_synthesis_hop, grain, previous);
+
+            let valid_bin_count = grain.valid_bin_count;
+            let reverse = grain.reverse();
+            for t in 0..grain.track_count() {
+                let rotation = &grain.tracks[t].rotation[..valid_bin_count];
+                for c in grain.track_columns(t) {
+                    let column = &mut grain.transformed.col_mut(c)[..valid_bin_count];
+                    for (x, &rotation) in column.iter_mut().zip(rotation) {
+                        let theta = rotation as f32 * (PI / 32768.0);
+                        let r = Complex32::new(theta.cos(), theta.sin());
+                        *x = if reverse { x.conj() * r } else { *x * r };
+                    }
+                }
+            }
+
+            if grain.channel_coupling == bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE {
+                let (mid, side) = grain.transformed.first_two_cols_mut();
+                for (m, s) in mid[..valid_bin_count].iter_mut().zip(&mut side[..valid_bin_count]) {
+                    *m += *s;
+                    *s = *m - *s * 2.0;
+                }
+            }
+
+            for c in 0..grain.transformed.cols() {
+                self.transforms.inverse(grain.log2_transform_length, self.output.inverse_transformed.col_mut(c), grain.transformed.col(c));
+            }
+        }
+
+        self.output.apply_synthesis_window(log2_synthesis_hop, &mut self.grains);
+
+        let (next, current) = self.grains.pair_mut(2);
+        Segment::lap_padding(&mut current.segment, &mut next.segment);
+
+        let request = [self.grains[2].request, self.grains[1].request];
+        let begin = self.grains[2].resample_operations.output;
+        let end = self.grains[1].resample_operations.output;
+        let frames = self.grains[3].segment.resample(&mut self.output.resample_offset, begin, end, &mut self.output.buffer_resampled);
+
+        self.timing.automation.output_position += frames.frame_count as f64;
+        Chunk { frames, request }
+    }
+
+    pub fn is_flushed(&self) -> bool {
+        self.grains.flushed()
+    }
+}
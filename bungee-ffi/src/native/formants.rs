@@ -0,0 +1,95 @@
+//! Cepstral estimate of a grain's spectral envelope, used to keep formants in place while
+//! pitch shifting, as Formants.cpp
+
+use std::f32::consts::PI;
+use std::ops::Range;
+
+use realfft::num_complex::Complex32;
+
+use super::fourier::{bin_count, bin_rows, transform_length, Transforms};
+use super::Array;
+
+/// limits the correction to +/-40 dB so that near-silent regions of the envelope are not
+/// boosted into noise
+const MAX_LOG_GAIN: f32 = 4.6;
+
+/// Complete bins `0..=n` of `t` into a sequence of length `2n` that is even-symmetric, so that
+/// its forward transform is real
+fn mirror(t: &mut [f32], n: usize) {
+    for j in 1..n {
+        t[2 * n - j] = t[j];
+    }
+}
+
+pub struct Envelope {
+    cepstrum: Vec<f32>,
+    spectrum: Vec<Complex32>,
+    log_energy: Vec<f32>,
+    lifter_length: i32,
+}
+
+impl Envelope {
+    pub fn new(log2_transform_length: i32, lifter_length: i32) -> Self {
+        Self {
+            cepstrum: vec![0.0; transform_length(log2_transform_length)],
+            spectrum: vec![Complex32::default(); bin_rows(log2_transform_length)],
+            log_energy: vec![0.0; bin_rows(log2_transform_length)],
+            lifter_length,
+        }
+    }
+
+    /// Scale the valid bins of `columns` of `transformed` so that, once the grain is shifted by
+    /// `pitch`, its spectral envelope matches that estimated from `energy`
+    #[allow(clippy::too_many_arguments)]
+    pub fn preserve(
+        &mut self,
+        transforms: &mut Transforms,
+        log2_transform_length: i32,
+        valid_bin_count: usize,
+        energy: &[f32],
+        transformed: &mut Array<Complex32>,
+        columns: Range<usize>,
+        pitch: f64,
+    ) {
+        let n = bin_count(log2_transform_length) - 1;
+
+        // Log energy, holding the last valid bin up to Nyquist
+        let max = energy[..valid_bin_count].iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let floor = (max * 1e-10).max(f32::MIN_POSITIVE);
+        for k in 0..=n {
+            self.cepstrum[k] = energy[k.min(valid_bin_count - 1)].max(floor).ln();
+        }
+        mirror(&mut self.cepstrum, n);
+        transforms.forward(log2_transform_length, &self.cepstrum, &mut self.spectrum);
+
+        // Keep only the low quefrencies, those of the envelope rather than of the harmonics,
+        // with a raised-cosine taper
+        let scale = 1.0 / (2 * n) as f32;
+        for q in 0..=n {
+            let lifter = if (q as i32) < self.lifter_length {
+                0.5 + 0.5 * (PI * q as f32 / self.lifter_length as f32).cos()
+            } else {
+                0.0
+            };
+            self.cepstrum[q] = self.spectrum[q].re * scale * lifter;
+        }
+        mirror(&mut self.cepstrum, n);
+        transforms.forward(log2_transform_length, &self.cepstrum, &mut self.spectrum);
+        for (log_energy, bin) in self.log_energy[..=n].iter_mut().zip(&self.spectrum) {
+            *log_energy = bin.re;
+        }
+
+        // Bin k is heard at k * pitch once shifted, where it should carry the envelope found there
+        let log_energy = &self.log_energy;
+        for k in 0..valid_bin_count {
+            let x = ((k as f64 * pitch) as f32).min(n as f32);
+            let i = (x as i32).min(n as i32 - 1) as usize;
+            let target = log_energy[i] + (x - i as f32) * (log_energy[i + 1] - log_energy[i]);
+            let log_gain = (0.5 * (target - log_energy[k])).clamp(-MAX_LOG_GAIN, MAX_LOG_GAIN);
+            let gain = log_gain.exp();
+            for c in columns.clone() {
+                transformed.col_mut(c)[k] *= gain;
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Real FFTs of the engine, through RustFFT or a caller's backend, as Fourier.cpp
+
+use std::slice;
+
+use realfft::num_complex::Complex32;
+
+use crate::fft::{FftBackend, RustFft};
+use crate::bungee_fft_backend_t;
+
+pub fn transform_length(log2_transform_length: i32) -> usize {
+    1 << log2_transform_length
+}
+
+pub fn bin_count(log2_transform_length: i32) -> usize {
+    transform_length(log2_transform_length - 1) + 1
+}
+
+/// Rows of per-bin arrays: the bins and spare rows past Nyquist, which `partials::enumerate`
+/// uses for its sentinels
+pub fn bin_rows(log2_transform_length: i32) -> usize {
+    bin_count(log2_transform_length) + 8
+}
+
+fn floats(bins: &[Complex32]) -> &[f32] {
+    // Complex32 is repr(C), a real part followed by an imaginary part
+    unsafe { slice::from_raw_parts(bins.as_ptr() as *const f32, bins.len() * 2) }
+}
+
+fn floats_mut(bins: &mut [Complex32]) -> &mut [f32] {
+    unsafe { slice::from_raw_parts_mut(bins.as_mut_ptr() as *mut f32, bins.len() * 2) }
+}
+
+pub struct Transforms {
+    backend: Box<dyn FftBackend>,
+}
+
+impl Transforms {
+    /// Transforms by `callbacks`, whose context is released on drop, else by RustFFT
+    pub fn new(callbacks: Option<bungee_fft_backend_t>) -> Self {
+        Self {
+            backend: match callbacks {
+                Some(callbacks) => Box::new(Callbacks(callbacks)),
+                None => Box::new(RustFft::new()),
+            },
+        }
+    }
+
+    pub fn prepare_forward(&mut self, log2_transform_length: i32) {
+        self.backend.prepare(log2_transform_length, false);
+    }
+
+    pub fn prepare_inverse(&mut self, log2_transform_length: i32) {
+        self.backend.prepare(log2_transform_length, true);
+    }
+
+    /// Transform the first `n` samples of `time` to the first `n / 2 + 1` bins of `frequency`
+    pub fn forward(&mut self, log2_transform_length: i32, time: &[f32], frequency: &mut [Complex32]) {
+        let n = transform_length(log2_transform_length);
+        self.backend.forward(log2_transform_length, &time[..n], floats_mut(&mut frequency[..n / 2 + 1]));
+    }
+
+    /// Transform the first `n / 2 + 1` bins of `frequency` to the first `n` samples of `time`
+    pub fn inverse(&mut self, log2_transform_length: i32, time: &mut [f32], frequency: &[Complex32]) {
+        let n = transform_length(log2_transform_length);
+        self.backend.inverse(log2_transform_length, &mut time[..n], floats(&frequency[..n / 2 + 1]));
+    }
+}
+
+/// Transforms delegated to an FFT supplied through the C API
+struct Callbacks(bungee_fft_backend_t);
+
+// The C API hands the context to the stretcher, which uses it from one thread at a time
+unsafe impl Send for Callbacks {}
+
+impl FftBackend for Callbacks {
+    fn prepare(&mut self, log2_length: i32, inverse: bool) {
+        if let Some(prepare) = self.0.prepare {
+            unsafe {
+                prepare(self.0.context, log2_length, inverse);
+            }
+        }
+    }
+
+    fn forward(&mut self, log2_length: i32, time: &[f32], frequency: &mut [f32]) {
+        let forward = self.0.forward.expect("validated by bungee_create_with_config");
+        unsafe {
+            forward(self.0.context, log2_length, time.as_ptr(), frequency.as_mut_ptr());
+        }
+    }
+
+    fn inverse(&mut self, log2_length: i32, time: &mut [f32], frequency: &[f32]) {
+        let inverse = self.0.inverse.expect("validated by bungee_create_with_config");
+        unsafe {
+            inverse(self.0.context, log2_length, time.as_mut_ptr(), frequency.as_ptr());
+        }
+    }
+}
+
+impl Drop for Callbacks {
+    fn drop(&mut self) {
+        if let Some(destroy) = self.0.destroy {
+            unsafe {
+                destroy(self.0.context);
+            }
+        }
+    }
+}
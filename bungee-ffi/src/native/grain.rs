@@ -0,0 +1,240 @@
+//! State of one grain through analysis and synthesis, as Grain.cpp and Grains.cpp
+
+use std::ops::{Index, IndexMut};
+
+use realfft::num_complex::Complex32;
+
+use super::fourier::{bin_rows, transform_length};
+use super::output::Segment;
+use super::partials::Partial;
+use super::phase::Phase;
+use super::resample::{Kernel, Operations, Padded};
+use super::{Array, Strided};
+use crate::{
+    bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED, bungee_channel_coupling_t, bungee_input_chunk_t,
+    bungee_request_t, bungee_resample_mode_t, bungee_sample_rates_t,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Analysis {
+    pub position_error: f64,
+    pub hop_ideal: f64,
+    pub speed: f64,
+    /// rounded
+    pub hop: i32,
+}
+
+/// Phase analysis and synthesis state of a group of channels that share one rotation per bin
+pub struct Track {
+    pub phase: Vec<Phase>,
+    pub energy: Vec<f32>,
+    pub rotation: Vec<Phase>,
+    pub delta: Vec<Phase>,
+    pub partials: Vec<Partial>,
+    pub transient: bool,
+}
+
+impl Track {
+    fn new(log2_transform_length: i32) -> Self {
+        let rows = bin_rows(log2_transform_length);
+        Self {
+            phase: vec![0; rows],
+            energy: vec![0.0; rows],
+            rotation: vec![0; rows],
+            delta: vec![0; rows],
+            partials: Vec::with_capacity(transform_length(log2_transform_length)),
+            transient: false,
+        }
+    }
+}
+
+pub struct Grain {
+    pub log2_transform_length: i32,
+    pub request: bungee_request_t,
+
+    pub request_hop: f64,
+    pub continuous: bool,
+    pub passthrough: i32,
+    pub valid_bin_count: usize,
+
+    pub resample_operations: Operations,
+
+    pub input_chunk: bungee_input_chunk_t,
+    pub analysis: Analysis,
+
+    pub transformed: Array<Complex32>,
+    pub channel_coupling: bungee_channel_coupling_t,
+    pub tracks: Vec<Track>,
+    pub input_resampled: Padded,
+
+    pub segment: Segment,
+}
+
+impl Grain {
+    pub fn new(log2_synthesis_hop: i32, channel_count: usize) -> Self {
+        let log2_transform_length = log2_synthesis_hop + 3;
+        Self {
+            log2_transform_length,
+            request: bungee_request_t {
+                position: f64::NAN,
+                speed: f64::NAN,
+                pitch: 1.0,
+                reset: false,
+                preserve_formants: false,
+            },
+            request_hop: 0.0,
+            continuous: false,
+            passthrough: 0,
+            valid_bin_count: 0,
+            resample_operations: Operations::default(),
+            input_chunk: bungee_input_chunk_t { begin: 0, end: 0 },
+            analysis: Analysis::default(),
+            transformed: Array::new(bin_rows(log2_transform_length), channel_count),
+            channel_coupling: bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED,
+            tracks: (0..channel_count).map(|_| Track::new(log2_transform_length)).collect(),
+            input_resampled: Padded::new(transform_length(log2_transform_length), channel_count),
+            segment: Segment::new(log2_synthesis_hop, channel_count),
+        }
+    }
+
+    pub fn specify(
+        &mut self,
+        request: &bungee_request_t,
+        previous: &Grain,
+        sample_rates: bungee_sample_rates_t,
+        log2_synthesis_hop: i32,
+        resample_mode: bungee_resample_mode_t,
+        resample_kernel: Kernel,
+    ) -> bungee_input_chunk_t {
+        self.request = *request;
+
+        let unit_hop = (1 << log2_synthesis_hop) as f64
+            * self.resample_operations.setup(sample_rates, self.request.pitch, resample_mode, resample_kernel);
+
+        self.request_hop = self.request.position - previous.request.position;
+        if self.request_hop.is_nan() || self.request.reset {
+            self.request_hop = self.request.speed * unit_hop;
+        }
+
+        let analysis = &mut self.analysis;
+        analysis.hop_ideal = self.request_hop * self.resample_operations.input.ratio as f64;
+
+        self.continuous = !self.request.reset && !previous.request.position.is_nan();
+        if self.continuous {
+            analysis.position_error = previous.analysis.position_error - analysis.hop_ideal;
+            analysis.hop = (-analysis.position_error).round() as i32;
+            analysis.position_error += analysis.hop as f64;
+        } else {
+            analysis.hop = analysis.hop_ideal.round() as i32;
+            analysis.position_error = self.request.position.round() - self.request.position;
+        }
+
+        analysis.speed = analysis.hop_ideal / (1 << log2_synthesis_hop) as f64;
+
+        self.passthrough = if analysis.speed.abs() == 1.0 { analysis.speed as i32 } else { 0 };
+        if self.continuous && self.passthrough != previous.passthrough {
+            self.passthrough = 0;
+        }
+
+        self.log2_transform_length = log2_synthesis_hop + 3;
+        self.input_resampled.frame_count = transform_length(self.log2_transform_length);
+
+        let mut half_input_frame_count = self.input_resampled.frame_count as i32 / 2;
+        let ratio = self.resample_operations.input.ratio;
+        if ratio != 1.0 {
+            half_input_frame_count = (half_input_frame_count as f32 / ratio).round() as i32 + 1;
+        }
+        let position = self.request.position.round() as i32;
+        self.input_chunk = bungee_input_chunk_t {
+            begin: position - half_input_frame_count,
+            end: position + half_input_frame_count,
+        };
+        self.input_chunk
+    }
+
+    pub fn reverse(&self) -> bool {
+        self.analysis.hop < 0
+    }
+
+    pub fn valid(&self) -> bool {
+        !self.request.position.is_nan()
+    }
+
+    /// Number of tracks in use: one for all channels when sum-locked, else one per channel
+    pub fn track_count(&self) -> usize {
+        if self.channel_coupling == bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED {
+            1
+        } else {
+            self.transformed.cols()
+        }
+    }
+
+    /// Columns of `transformed` whose phase is governed by the given track
+    pub fn track_columns(&self, track: usize) -> std::ops::Range<usize> {
+        if self.channel_coupling == bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED {
+            0..self.transformed.cols()
+        } else {
+            track..track + 1
+        }
+    }
+
+    /// Input resampled to the transform length if the grain resamples its input, else `input`
+    pub fn resample_input<'a>(&'a mut self, input: Strided<'a, f32>, log2_window_length: i32) -> Strided<'a, f32> {
+        match self.resample_operations.input.function {
+            Some(function) => {
+                let ratio = self.resample_operations.input.ratio;
+                let mut offset = (self.input_chunk.begin as f64 - self.request.position) as f32;
+                offset *= ratio;
+                offset += (1 << (log2_window_length - 1)) as f32;
+                offset = (offset as f64 - self.analysis.position_error) as f32;
+
+                function(&mut self.input_resampled, &mut offset, input, ratio, ratio, false);
+
+                self.input_resampled.unpadded()
+            }
+            None => input,
+        }
+    }
+}
+
+/// The four grains that overlap an output segment, newest first
+pub struct Grains {
+    vector: Vec<Grain>,
+}
+
+impl Grains {
+    pub fn new(log2_synthesis_hop: i32, channel_count: usize) -> Self {
+        Self {
+            vector: (0..4).map(|_| Grain::new(log2_synthesis_hop, channel_count)).collect(),
+        }
+    }
+
+    /// Recycle the oldest grain as the newest
+    pub fn rotate(&mut self) {
+        self.vector.rotate_left(1);
+    }
+
+    pub fn flushed(&self) -> bool {
+        self.vector.iter().all(|grain| !grain.valid())
+    }
+
+    /// `(grains[i], grains[i + 1])`, a grain and the one before it
+    pub fn pair_mut(&mut self, i: usize) -> (&mut Grain, &mut Grain) {
+        let (older, newer) = self.vector.split_at_mut(3 - i);
+        (&mut newer[0], &mut older[2 - i])
+    }
+}
+
+impl Index<usize> for Grains {
+    type Output = Grain;
+
+    fn index(&self, i: usize) -> &Grain {
+        &self.vector[3 - i]
+    }
+}
+
+impl IndexMut<usize> for Grains {
+    fn index_mut(&mut self, i: usize) -> &mut Grain {
+        &mut self.vector[3 - i]
+    }
+}
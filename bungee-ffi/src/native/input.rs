@@ -0,0 +1,37 @@
+//! Windowing of each grain's input ahead of the forward transform, as Input.cpp
+
+use super::fourier::Transforms;
+use super::window::{self, Shape};
+use super::{Array, Strided};
+
+pub struct Input {
+    pub analysis_window_basic: Vec<f32>,
+    pub windowed_input: Array<f32>,
+}
+
+impl Input {
+    /// The window's gain is set by `window::normalise` once the synthesis window is known
+    pub fn new(log2_synthesis_hop: i32, channel_count: usize, transforms: &mut Transforms, window: &Shape) -> Self {
+        let analysis_window_basic = window::from_shape(transforms, log2_synthesis_hop + 3, window);
+        transforms.prepare_forward(log2_synthesis_hop + 3);
+        Self {
+            analysis_window_basic,
+            windowed_input: Array::new(8 << log2_synthesis_hop, channel_count),
+        }
+    }
+
+    /// Window `input` into `windowed_input` with its halves swapped, so that the grain's centre
+    /// is at time zero, and return the log2 length of the transform
+    pub fn apply_analysis_window(&mut self, input: Strided<'_, f32>) -> i32 {
+        let half = self.analysis_window_basic.len() / 2;
+        let (head, tail) = self.analysis_window_basic.split_at(half);
+        let middle = input.rows / 2;
+        for c in 0..input.cols {
+            let column = input.col(c);
+            let (top, bottom) = self.windowed_input.col_mut(c).split_at_mut(half);
+            window::apply(false, head, &column[middle..middle + half], top);
+            window::apply(false, tail, &column[middle - half..middle], &mut bottom[..half]);
+        }
+        self.windowed_input.rows().trailing_zeros() as i32
+    }
+}
@@ -0,0 +1,120 @@
+//! Pure-Rust port of the Bungee::Basic engine and its C layer
+//!
+//! With the `pure-rust` feature the functions of `api` stand in for those of bungee_c.h, so
+//! `Stretcher` and everything built on it work without a C or C++ toolchain. The modules
+//! mirror the engine sources in src/ one for one; see there for the design notes.
+//!
+//! Otherwise the port is only compiled for tests, which check it against the C++ engine.
+
+// Outside pure-rust builds only the tests reach the native engine
+#![cfg_attr(not(feature = "pure-rust"), allow(dead_code))]
+// Negated comparisons keep the engine's handling of NaN
+#![allow(clippy::neg_cmp_op_on_partial_ord)]
+
+pub mod api;
+mod automation;
+mod basic;
+mod formants;
+mod fourier;
+mod grain;
+mod input;
+mod output;
+mod partials;
+mod phase;
+mod resample;
+mod sample;
+mod stretch;
+mod synthesis;
+mod timing;
+mod window;
+
+#[cfg(feature = "pure-rust")]
+pub mod sys;
+
+#[cfg(test)]
+mod tests;
+
+/// Column-major array with one column per channel, as Eigen's ArrayXX
+#[derive(Debug, Clone)]
+pub struct Array<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T: Copy + Default> Array<T> {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            data: vec![T::default(); rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn col(&self, c: usize) -> &[T] {
+        &self.data[c * self.rows..(c + 1) * self.rows]
+    }
+
+    pub fn col_mut(&mut self, c: usize) -> &mut [T] {
+        &mut self.data[c * self.rows..(c + 1) * self.rows]
+    }
+
+    /// Columns 0 and 1, for stereo processing
+    pub fn first_two_cols_mut(&mut self) -> (&mut [T], &mut [T]) {
+        let (first, rest) = self.data.split_at_mut(self.rows);
+        (first, &mut rest[..self.rows])
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.data.fill(value);
+    }
+
+    /// Rows `begin..begin + count` of every column
+    pub fn fill_rows(&mut self, begin: usize, count: usize, value: T) {
+        for c in 0..self.cols {
+            self.col_mut(c)[begin..begin + count].fill(value);
+        }
+    }
+
+    /// Read-only view of rows `begin..begin + rows`
+    pub fn view(&self, begin: usize, rows: usize) -> Strided<'_, T> {
+        Strided {
+            data: &self.data[begin..],
+            stride: self.rows,
+            rows,
+            cols: self.cols,
+        }
+    }
+}
+
+/// Read-only planar frames: channel `c` starts at `data[c * stride]`, as an Eigen map with
+/// an outer stride
+#[derive(Debug, Clone, Copy)]
+pub struct Strided<'a, T> {
+    pub data: &'a [T],
+    pub stride: usize,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl<T: Copy> Strided<'_, T> {
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.data[col * self.stride + row]
+    }
+
+    pub fn col(&self, c: usize) -> &[T] {
+        &self.data[c * self.stride..c * self.stride + self.rows]
+    }
+}
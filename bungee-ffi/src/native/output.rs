@@ -0,0 +1,169 @@
+//! Overlap-add of synthesised grains and output resampling, as Output.cpp
+
+use super::fourier::Transforms;
+use super::grain::Grains;
+use super::resample::{self, Operation, OutputFunction, Padded, PADDING};
+use super::window::{self, Shape};
+use super::Array;
+
+pub struct Output {
+    pub synthesis_window: Vec<f32>,
+    pub inverse_transformed: Array<f32>,
+    pub buffer_resampled: Array<f32>,
+    pub resample_offset: f32,
+}
+
+/// Planar frames of one output chunk: channel `c` starts at `data[c * stride]`
+pub struct Frames<'a> {
+    pub data: &'a [f32],
+    pub frame_count: usize,
+    pub stride: usize,
+}
+
+impl Output {
+    pub fn new(
+        transforms: &mut Transforms,
+        log2_synthesis_hop: i32,
+        channel_count: usize,
+        max_output_chunk_size: usize,
+        window: &Shape,
+    ) -> Self {
+        let synthesis_window = window::from_shape(transforms, log2_synthesis_hop + 2, window);
+        transforms.prepare_inverse(log2_synthesis_hop + 3);
+        Self {
+            synthesis_window,
+            inverse_transformed: Array::new(8 << log2_synthesis_hop, channel_count),
+            buffer_resampled: Array::new(max_output_chunk_size, channel_count),
+            resample_offset: 0.0,
+        }
+    }
+
+    /// Window the latest inverse transform and lap it into the segments of the four grains
+    pub fn apply_synthesis_window(&self, log2_synthesis_hop: i32, grains: &mut Grains) {
+        let window = &self.synthesis_window;
+        let quadrant_size = window.len() / 4;
+        let hops_per_transform = 1 << (grains[0].log2_transform_length - log2_synthesis_hop);
+        let valid = grains[0].valid();
+
+        grains[0].segment.buffer_lapped.frame_count = 0;
+        grains[0].segment.buffer_lapped.all_zeros = true;
+
+        for i in 0..4 {
+            let quadrant = &mut grains[3 - i].segment.buffer_lapped;
+
+            if valid {
+                let window_segment = &window[quadrant_size * (i ^ 2)..][..quadrant_size];
+                let j = (i + hops_per_transform - 2) % hops_per_transform;
+
+                let add = quadrant.frame_count != 0;
+                for c in 0..self.inverse_transformed.cols() {
+                    let input_segment = &self.inverse_transformed.col(c)[quadrant_size * j..][..quadrant_size];
+                    window::apply(add, window_segment, input_segment, &mut quadrant.unpadded_mut(c)[..quadrant_size]);
+                }
+                quadrant.all_zeros = false;
+            } else if quadrant.frame_count == 0 {
+                for c in 0..quadrant.array.cols() {
+                    quadrant.unpadded_mut(c)[..quadrant_size].fill(0.0);
+                }
+            }
+
+            quadrant.frame_count = quadrant_size;
+        }
+
+        grains[2].segment.needs_resample =
+            grains[1].resample_operations.output.function.is_some() || grains[0].resample_operations.output.function.is_some();
+    }
+}
+
+/// One synthesis hop of output, lapped from the grains that overlap it
+#[derive(Clone)]
+pub struct Segment {
+    pub buffer_lapped: Padded,
+    pub needs_resample: bool,
+}
+
+impl Segment {
+    pub fn new(log2_frame_count: i32, channel_count: usize) -> Self {
+        Self {
+            buffer_lapped: Padded::new(1 << log2_frame_count, channel_count),
+            needs_resample: false,
+        }
+    }
+
+    /// Share frames across the boundary of consecutive segments so that resampling kernels
+    /// can read past either end
+    pub fn lap_padding(current: &mut Segment, next: &mut Segment) {
+        const N: usize = PADDING;
+        let current_frames = current.buffer_lapped.frame_count;
+
+        if current.needs_resample {
+            for c in 0..current.buffer_lapped.array.cols() {
+                let destination = &mut current.buffer_lapped.array.col_mut(c)[N + current_frames..][..N];
+                if next.buffer_lapped.all_zeros {
+                    destination.fill(0.0);
+                } else {
+                    destination.copy_from_slice(&next.buffer_lapped.array.col(c)[N..2 * N]);
+                }
+            }
+        }
+
+        if current.needs_resample || next.needs_resample {
+            for c in 0..next.buffer_lapped.array.cols() {
+                let destination = &mut next.buffer_lapped.array.col_mut(c)[..N];
+                if current.buffer_lapped.all_zeros {
+                    destination.fill(0.0);
+                } else {
+                    destination.copy_from_slice(&current.buffer_lapped.array.col(c)[current_frames..][..N]);
+                }
+            }
+        }
+    }
+
+    /// Resample the segment into `buffer_resampled` if either bounding grain resamples its
+    /// output, else return the segment's own frames
+    pub fn resample<'a>(
+        &'a mut self,
+        resample_offset: &mut f32,
+        mut begin: Operation<OutputFunction>,
+        mut end: Operation<OutputFunction>,
+        buffer_resampled: &'a mut Array<f32>,
+    ) -> Frames<'a> {
+        if begin.function.is_none() {
+            begin.ratio = 1.0;
+        }
+
+        if end.function.is_none() {
+            end.ratio = 1.0;
+            end.function = begin.function;
+        }
+
+        let lapped = &mut self.buffer_lapped;
+        if let Some(mut function) = end.function {
+            if lapped.all_zeros {
+                function = resample::SILENT_OUTPUT;
+            }
+
+            let align_end = end.ratio == 1.0;
+            let frame_count = function(lapped, resample_offset, buffer_resampled, begin.ratio, end.ratio, align_end).max(0) as usize;
+
+            if lapped.all_zeros {
+                buffer_resampled.fill_rows(0, frame_count, 0.0);
+            }
+            Frames {
+                data: buffer_resampled.data(),
+                frame_count,
+                stride: buffer_resampled.rows(),
+            }
+        } else {
+            let frame_count = lapped.frame_count;
+            if lapped.all_zeros {
+                lapped.array.fill_rows(PADDING, frame_count, 0.0);
+            }
+            Frames {
+                data: &lapped.array.data()[PADDING..],
+                frame_count,
+                stride: lapped.array.rows(),
+            }
+        }
+    }
+}
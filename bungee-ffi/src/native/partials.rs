@@ -0,0 +1,82 @@
+//! Division of the spectrum into partials around energy peaks, as Partials.cpp
+
+/// Bins from the previous partial's end up to `end` follow the phase of bin `peak`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Partial {
+    pub peak: i16,
+    pub end: i16,
+}
+
+/// In a transient grain, partials whose energy has risen by more than this factor have their
+/// phase reset
+pub const TRANSIENT_RISE: f32 = 2.0;
+
+/// Enumerate the partials of the first `n` bins; `energy` needs two spare bins past `n`
+pub fn enumerate(partials: &mut Vec<Partial>, n: usize, energy: &mut [f32]) {
+    // Sentinels that end the last partial exactly at n
+    let undo = [energy[n], energy[n + 1]];
+    energy[n] = -1.0;
+    energy[n + 1] = 0.0;
+
+    partials.clear();
+    let mut m = 1;
+    loop {
+        while energy[m] < energy[m + 1] {
+            m += 1;
+        }
+        let peak = m as i16;
+        m += 1;
+
+        while !(energy[m] < energy[m + 1]) {
+            m += 1;
+        }
+        partials.push(Partial { peak, end: m as i16 });
+        m += 1;
+
+        if m > n {
+            break;
+        }
+    }
+
+    energy[n] = undo[0];
+    energy[n + 1] = undo[1];
+}
+
+fn suppress_partial(partials: &mut [Partial], i: usize, energy: &[f32]) {
+    if energy[partials[i - 1].end as usize] > energy[partials[i].end as usize] {
+        partials[i - 1].end = partials[i].end;
+    } else {
+        partials[i].end = partials[i - 1].end;
+    }
+}
+
+pub fn suppress_transient_partials(partials: &mut [Partial], energy: &[f32], previous_energy: &[f32]) {
+    let peak = |partial: Partial| partial.peak as usize;
+
+    let mut strongest = 0;
+    for i in 1..partials.len() {
+        if energy[peak(partials[i])] > energy[peak(partials[strongest])] {
+            strongest = i;
+        }
+    }
+
+    for i in 1..partials.len().saturating_sub(1) {
+        if i != strongest {
+            // fudge: lower constant helps transients, higher helps tones
+            const K: f32 = 1.5;
+            if energy[peak(partials[i])] > K * previous_energy[peak(partials[i])] {
+                suppress_partial(partials, i, energy);
+            }
+        }
+    }
+}
+
+/// Rectified spectral flux over the first `n` bins as a fraction of the grain's energy
+pub fn spectral_flux(n: usize, energy: &[f32], previous_energy: &[f32]) -> f32 {
+    let total: f32 = energy[..n].iter().sum();
+    if !(total > 0.0) {
+        return 0.0;
+    }
+    let rise: f32 = energy[..n].iter().zip(&previous_energy[..n]).map(|(e, p)| (e - p).max(0.0)).sum();
+    rise / total
+}
@@ -0,0 +1,11 @@
+//! Fixed-point phase, as Phase.h: a full revolution spans the range of an `i16`
+
+use std::f64::consts::PI;
+
+pub type Phase = i16;
+
+pub fn from_radians(radians: f32) -> Phase {
+    const K: f32 = (65536.0 / (2.0 * PI)) as f32;
+    // Through i32 so that +pi wraps to -pi as in the C++ conversion
+    (K * radians) as i32 as Phase
+}
@@ -0,0 +1,366 @@
+//! Resampling between the engine's fixed-rate buffers and variable-rate audio, as Resample.h
+//!
+//! Input resampling reads variable-rate input into a fixed buffer; output resampling reads a
+//! fixed buffer out at a variable rate. Each interpolation kernel supplies both directions.
+
+use std::f32::consts::PI;
+
+use super::{Array, Strided};
+use crate::{
+    bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE, bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST,
+    bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, bungee_interpolation_t,
+    bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN_OUT,
+    bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT, bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN,
+    bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_OUT, bungee_resample_mode_t, bungee_sample_rates_t,
+};
+
+const MAX_SINC_TAPS: usize = 32;
+
+/// Frames either side of a `Padded` buffer's content, enough for the longest kernel
+pub const PADDING: usize = 24;
+
+/// Fixed-rate buffer with room for kernels to read or write past either end of its frames
+#[derive(Debug, Clone)]
+pub struct Padded {
+    pub array: Array<f32>,
+    pub frame_count: usize,
+    pub all_zeros: bool,
+}
+
+impl Padded {
+    pub fn new(max_frame_count: usize, channel_count: usize) -> Self {
+        Self {
+            array: Array::new(PADDING + max_frame_count + PADDING, channel_count),
+            frame_count: 0,
+            all_zeros: true,
+        }
+    }
+
+    /// The first `frame_count` frames after the leading padding
+    pub fn unpadded(&self) -> Strided<'_, f32> {
+        self.array.view(PADDING, self.frame_count)
+    }
+
+    /// Channel `c` from the first frame after the leading padding
+    pub fn unpadded_mut(&mut self, c: usize) -> &mut [f32] {
+        &mut self.array.col_mut(c)[PADDING..]
+    }
+}
+
+/// Fixed-buffer frames and their coefficients for one variable-rate frame
+struct Taps {
+    first: usize,
+    coefficients: [f32; MAX_SINC_TAPS],
+    count: usize,
+}
+
+impl Taps {
+    fn new(first: i32, coefficients: &[f32]) -> Self {
+        let mut taps = Self {
+            first: first as usize,
+            coefficients: [0.0; MAX_SINC_TAPS],
+            count: coefficients.len(),
+        };
+        taps.coefficients[..coefficients.len()].copy_from_slice(coefficients);
+        taps
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.coefficients[..self.count].iter().enumerate().map(|(j, &k)| (self.first + j, k))
+    }
+}
+
+trait Interpolation {
+    /// Taps around fixed-buffer position `offset`; `cutoff` is relative to the fixed rate
+    fn taps(offset: f32, cutoff: f32) -> Taps;
+}
+
+/// Leaves the variable-rate frames untouched, for output that is known to be silent
+struct None;
+
+impl Interpolation for None {
+    fn taps(_: f32, _: f32) -> Taps {
+        Taps::new(0, &[])
+    }
+}
+
+struct Nearest;
+
+impl Interpolation for Nearest {
+    fn taps(offset: f32, _: f32) -> Taps {
+        Taps::new((offset + 0.5) as i32, &[1.0])
+    }
+}
+
+struct Bilinear;
+
+impl Interpolation for Bilinear {
+    fn taps(offset: f32, _: f32) -> Taps {
+        let x = offset as i32;
+        let k = offset - x as f32;
+        Taps::new(x, &[1.0 - k, k])
+    }
+}
+
+/// Catmull-Rom cubic Hermite spline through the four nearest samples
+struct CubicHermite;
+
+impl Interpolation for CubicHermite {
+    fn taps(offset: f32, _: f32) -> Taps {
+        let x = offset as i32;
+        let k = offset - x as f32;
+        let k2 = k * k;
+        let k3 = k2 * k;
+        Taps::new(
+            x - 1,
+            &[
+                -0.5 * k3 + k2 - 0.5 * k,
+                1.5 * k3 - 2.5 * k2 + 1.0,
+                -1.5 * k3 + 2.0 * k2 + 0.5 * k,
+                0.5 * k3 - 0.5 * k2,
+            ],
+        )
+    }
+}
+
+/// Blackman-windowed sinc spanning `TAPS` samples of the fixed buffer
+struct WindowedSinc<const TAPS: usize>;
+
+impl<const TAPS: usize> Interpolation for WindowedSinc<TAPS> {
+    fn taps(offset: f32, cutoff: f32) -> Taps {
+        let half = TAPS as i32 / 2;
+        let x = offset as i32;
+        let k = offset - x as f32;
+
+        let mut coefficients = [0.0; TAPS];
+        let mut sum = 0.0;
+        for (j, coefficient) in coefficients.iter_mut().enumerate() {
+            let d = (j as i32 - (half - 1)) as f32 - k;
+            let sinc = if d.abs() < 1e-6 { 1.0 } else { (PI * cutoff * d).sin() / (PI * cutoff * d) };
+            let t = PI * d / half as f32;
+            let window = if d.abs() < half as f32 { 0.42 + 0.5 * t.cos() + 0.08 * (2.0 * t).cos() } else { 0.0 };
+            *coefficient = sinc * window;
+            sum += *coefficient;
+        }
+
+        // normalise for unity gain at DC, which short kernels with a low cutoff would otherwise lose
+        for coefficient in &mut coefficients {
+            *coefficient /= sum;
+        }
+
+        Taps::new(x - (half - 1), &coefficients)
+    }
+}
+
+/// Number of variable-rate frames that fill the fixed buffer from `offset`, whether that
+/// exceeds `variable_rows`, and the end ratio
+fn frame_count(fixed_frames: usize, offset: f32, variable_rows: usize, ratio_begin: f32, ratio_end: f32, align_end: bool) -> (i32, bool, f32) {
+    let mut count = (2.0 * (fixed_frames as f32 + ratio_end - offset) / (ratio_begin + ratio_end) - 1.0).round() as i32;
+
+    let truncate = count > variable_rows as i32;
+    if truncate {
+        count = variable_rows as i32;
+    }
+
+    let mut ratio_end = ratio_end;
+    if align_end {
+        let dividend = 2.0 * (fixed_frames as f32 - offset) - (count + 1) as f32 * ratio_begin;
+        let divisor = (count - 1) as f32;
+        ratio_end = dividend / divisor;
+    }
+    (count, truncate, ratio_end)
+}
+
+/// Call `step(i, x, ratio)` for each variable-rate frame `i` at fixed-buffer position `x`,
+/// then move `offset` on by the fixed buffer's frames
+fn steps(count: i32, offset: &mut f32, fixed_frames: usize, ratio_begin: f32, ratio_end: f32, mut step: impl FnMut(usize, f32, f32)) {
+    let ratio_gradient = (ratio_end - ratio_begin) / count as f32;
+    let base = PADDING as f32 + *offset;
+    let mut ratio = ratio_begin;
+    for i in 0..count.max(0) {
+        if ratio_begin != ratio_end {
+            let x = base + i as f32 * (ratio_begin + ratio) * 0.5;
+            ratio = ratio_begin + ratio_gradient * i as f32;
+            step(i as usize, x, ratio);
+        } else {
+            step(i as usize, base + i as f32 * ratio, ratio);
+        }
+    }
+
+    *offset += count as f32 * (ratio_begin + ratio) * 0.5;
+    *offset -= fixed_frames as f32;
+}
+
+/// Reset an offset that did not land at the end of the fixed buffer
+fn land(offset: &mut f32, truncate: bool, align_end: bool, ratio_begin: f32, ratio_end: f32) {
+    let tolerance = if align_end { 1e-2 } else { (ratio_begin + ratio_end) * 0.3 };
+    if !(truncate || offset.abs() < tolerance) {
+        *offset = 0.0;
+    }
+}
+
+/// Resample variable-rate `variable` into all of `fixed`, returning the frames read
+pub type InputFunction = fn(&mut Padded, &mut f32, Strided<'_, f32>, f32, f32, bool) -> i32;
+
+/// Resample all of `fixed` into variable-rate `variable`, returning the frames written
+pub type OutputFunction = fn(&Padded, &mut f32, &mut Array<f32>, f32, f32, bool) -> i32;
+
+fn resample_input<I: Interpolation>(
+    fixed: &mut Padded,
+    offset: &mut f32,
+    variable: Strided<'_, f32>,
+    ratio_begin: f32,
+    ratio_end: f32,
+    align_end: bool,
+) -> i32 {
+    let (count, truncate, ratio_end) = frame_count(fixed.frame_count, *offset, variable.rows, ratio_begin, ratio_end, align_end);
+
+    fixed.array.fill(0.0);
+    let array = &mut fixed.array;
+    steps(count, offset, fixed.frame_count, ratio_begin, ratio_end, |i, x, ratio| {
+        // writing the fixed buffer at steps of ratio, so cut off at the variable rate
+        let taps = I::taps(x, ratio.min(1.0));
+        for c in 0..variable.cols {
+            let sample = variable.get(i, c);
+            let column = array.col_mut(c);
+            for (row, coefficient) in taps.iter() {
+                column[row] += sample * (coefficient * ratio);
+            }
+        }
+    });
+
+    land(offset, truncate, align_end, ratio_begin, ratio_end);
+    count
+}
+
+fn resample_output<I: Interpolation>(
+    fixed: &Padded,
+    offset: &mut f32,
+    variable: &mut Array<f32>,
+    ratio_begin: f32,
+    ratio_end: f32,
+    align_end: bool,
+) -> i32 {
+    let (count, truncate, ratio_end) = frame_count(fixed.frame_count, *offset, variable.rows(), ratio_begin, ratio_end, align_end);
+
+    steps(count, offset, fixed.frame_count, ratio_begin, ratio_end, |i, x, ratio| {
+        // reading the fixed buffer at steps of ratio, so cut off at the variable rate
+        let taps = I::taps(x, (1.0 / ratio).min(1.0));
+        for c in 0..fixed.array.cols() {
+            let column = fixed.array.col(c);
+            let mut taps = taps.iter();
+            if let Some((row, coefficient)) = taps.next() {
+                let mut sample = column[row] * coefficient;
+                for (row, coefficient) in taps {
+                    sample += column[row] * coefficient;
+                }
+                variable.col_mut(c)[i] = sample;
+            }
+        }
+    });
+
+    land(offset, truncate, align_end, ratio_begin, ratio_end);
+    count
+}
+
+/// Output resampling that writes nothing, for a fixed buffer that is all zeros
+pub const SILENT_OUTPUT: OutputFunction = resample_output::<None>;
+
+/// Resample functions of one interpolation kernel, for the input and output sides
+#[derive(Clone, Copy)]
+pub struct Kernel {
+    pub input: InputFunction,
+    pub output: OutputFunction,
+}
+
+impl Kernel {
+    fn make<I: Interpolation>() -> Self {
+        Self {
+            input: resample_input::<I>,
+            output: resample_output::<I>,
+        }
+    }
+
+    /// Bilinear unless `interpolation` is another supported kernel; `sinc_taps` may be 4, 8,
+    /// 16 or 32
+    pub fn select(interpolation: bungee_interpolation_t, sinc_taps: i32) -> Self {
+        match (interpolation, sinc_taps) {
+            (bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST, _) => Self::make::<Nearest>(),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE, _) => Self::make::<CubicHermite>(),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 4) => Self::make::<WindowedSinc<4>>(),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 8) => Self::make::<WindowedSinc<8>>(),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 16) => Self::make::<WindowedSinc<16>>(),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 32) => Self::make::<WindowedSinc<32>>(),
+            _ => Self::make::<Bilinear>(),
+        }
+    }
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Self::make::<Bilinear>()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Operation<F> {
+    pub function: Option<F>,
+    pub ratio: f32,
+}
+
+impl<F> Default for Operation<F> {
+    fn default() -> Self {
+        Self {
+            function: Option::None,
+            ratio: 0.0,
+        }
+    }
+}
+
+/// Where a grain resamples, and by what ratio
+#[derive(Clone, Copy, Default)]
+pub struct Operations {
+    pub input: Operation<InputFunction>,
+    pub output: Operation<OutputFunction>,
+}
+
+impl Operations {
+    /// Choose input or output resampling for `pitch`, returning the ratio of output frames
+    /// synthesised per input frame at unit speed
+    pub fn setup(&mut self, sample_rates: bungee_sample_rates_t, pitch: f64, mode: bungee_resample_mode_t, kernel: Kernel) -> f64 {
+        let resample_ratio = pitch * sample_rates.input_rate as f64 / sample_rates.output_rate as f64;
+        self.input = Operation {
+            function: Some(kernel.input),
+            ratio: (1.0 / resample_ratio) as f32,
+        };
+        self.output = Operation {
+            function: Some(kernel.output),
+            ratio: resample_ratio as f32,
+        };
+
+        let (input, output) = match mode {
+            bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_OUT => (false, true),
+            bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN => (true, false),
+            _ if resample_ratio == 1.0 => (false, false),
+            bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN => (true, false),
+            bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT => (false, true),
+            bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN_OUT if resample_ratio > 1.0 => (true, false),
+            bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN_OUT if resample_ratio < 1.0 => (false, true),
+            _ => (false, true),
+        };
+        if !input {
+            self.input = Operation {
+                function: Option::None,
+                ratio: 1.0,
+            };
+        }
+        if !output {
+            self.output = Operation {
+                function: Option::None,
+                ratio: 1.0,
+            };
+        }
+
+        (sample_rates.input_rate as f64 / sample_rates.output_rate as f64) / self.output.ratio as f64
+    }
+}
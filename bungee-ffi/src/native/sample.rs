@@ -0,0 +1,70 @@
+//! Conversion between float and the formats of `bungee_sample_format_t`, as bungee_sample.c
+//!
+//! Integer formats map full scale to 1.0. Floats outside -1..1 are clamped on conversion back.
+
+use std::os::raw::c_void;
+
+use crate::*;
+
+const I16_SCALE: f64 = 32768.0;
+const I24_SCALE: f64 = 8388608.0;
+const I32_SCALE: f64 = 2147483648.0;
+
+pub fn is_valid(format: bungee_sample_format_t) -> bool {
+    format <= bungee_sample_format_BUNGEE_SAMPLE_I32
+}
+
+/// Scale to an integer of the given full scale, rounding half to even and clamping
+fn to_integer(value: f32, scale: f64) -> f64 {
+    let scaled = (value as f64 * scale).round_ties_even();
+    if scaled.is_nan() {
+        return 0.0;
+    }
+    scaled.clamp(-scale, scale - 1.0)
+}
+
+/// Convert `destination.len()` samples, `step` apart from index `offset` of `source`
+///
+/// # Safety
+/// `source` must hold samples of `format` at every index read.
+pub unsafe fn to_float(source: *const c_void, format: bungee_sample_format_t, offset: usize, step: usize, destination: &mut [f32]) {
+    unsafe fn convert<T: Copy>(source: *const c_void, offset: usize, step: usize, destination: &mut [f32], f: impl Fn(T) -> f32) {
+        let source = source as *const T;
+        for (i, sample) in destination.iter_mut().enumerate() {
+            *sample = f(unsafe { *source.add(offset + i * step) });
+        }
+    }
+    unsafe {
+        match format {
+            bungee_sample_format_BUNGEE_SAMPLE_F32 => convert(source, offset, step, destination, |x: f32| x),
+            bungee_sample_format_BUNGEE_SAMPLE_F64 => convert(source, offset, step, destination, |x: f64| x as f32),
+            bungee_sample_format_BUNGEE_SAMPLE_I16 => convert(source, offset, step, destination, |x: i16| (x as f64 / I16_SCALE) as f32),
+            bungee_sample_format_BUNGEE_SAMPLE_I24 => convert(source, offset, step, destination, |x: i32| (x as f64 / I24_SCALE) as f32),
+            bungee_sample_format_BUNGEE_SAMPLE_I32 => convert(source, offset, step, destination, |x: i32| (x as f64 / I32_SCALE) as f32),
+            _ => {}
+        }
+    }
+}
+
+/// Convert `source` to samples `step` apart from index `offset` of `destination`
+///
+/// # Safety
+/// `destination` must have room for samples of `format` at every index written.
+pub unsafe fn from_float(source: &[f32], destination: *mut c_void, format: bungee_sample_format_t, offset: usize, step: usize) {
+    unsafe fn convert<T>(source: &[f32], destination: *mut c_void, offset: usize, step: usize, f: impl Fn(f32) -> T) {
+        let destination = destination as *mut T;
+        for (i, &sample) in source.iter().enumerate() {
+            unsafe { *destination.add(offset + i * step) = f(sample) };
+        }
+    }
+    unsafe {
+        match format {
+            bungee_sample_format_BUNGEE_SAMPLE_F32 => convert(source, destination, offset, step, |x| x),
+            bungee_sample_format_BUNGEE_SAMPLE_F64 => convert(source, destination, offset, step, |x| x as f64),
+            bungee_sample_format_BUNGEE_SAMPLE_I16 => convert(source, destination, offset, step, |x| to_integer(x, I16_SCALE) as i16),
+            bungee_sample_format_BUNGEE_SAMPLE_I24 => convert(source, destination, offset, step, |x| to_integer(x, I24_SCALE) as i32),
+            bungee_sample_format_BUNGEE_SAMPLE_I32 => convert(source, destination, offset, step, |x| to_integer(x, I32_SCALE) as i32),
+            _ => {}
+        }
+    }
+}
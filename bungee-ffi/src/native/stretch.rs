@@ -0,0 +1,68 @@
+//! Phase rotations that stretch partials in frequency and time, as Stretch.h
+
+use super::phase::Phase;
+
+/// Rotation of each bin relative to bin 0 that keeps partials coherent at a given speed
+pub struct Frequency {
+    multiplier: i32,
+}
+
+impl Frequency {
+    const SHIFT: i32 = 8;
+
+    pub fn new(speed: f32) -> Self {
+        const PREVENT_DIVIDE_BY_ZERO: f32 = 1e-20;
+        let speed = speed.abs() + PREVENT_DIVIDE_BY_ZERO;
+        let multiplier = ((1 << Self::SHIFT) as f32 / -speed).round().max(i16::MIN as f32) as i32;
+        Self { multiplier }
+    }
+
+    pub fn apply(&self, n: usize, rotation: &mut [Phase], phase: &[Phase]) {
+        rotation[0] = 0;
+        for m in 1..n {
+            let delta = phase[m - 1].wrapping_sub(phase[m]);
+            let x = (delta as i32).wrapping_mul(self.multiplier) >> Self::SHIFT;
+            rotation[m] = rotation[m - 1].wrapping_add(x as Phase).wrapping_add(delta);
+        }
+    }
+}
+
+/// Phase advance of a partial between grains, for analysis hops in either direction
+pub struct Time {
+    reverse: bool,
+    reverse_previous: bool,
+    a: i32,
+    multiplier: i32,
+}
+
+impl Time {
+    const LOG2_SYNTHESIS_HOP_REVOLUTION: i32 = -3;
+
+    pub fn new(log2_synthesis_hop: i32, analysis_hop: i32, analysis_hop_previous: i32) -> Self {
+        let log2_transform_length = log2_synthesis_hop - Self::LOG2_SYNTHESIS_HOP_REVOLUTION;
+        let dividend = (1 << log2_synthesis_hop) << 16;
+        let divisor = analysis_hop << 1;
+        Self {
+            reverse: analysis_hop < 0,
+            reverse_previous: analysis_hop_previous < 0,
+            a: analysis_hop << (32 - log2_transform_length),
+            multiplier: if divisor != 0 { (dividend + divisor.abs() / 2) / divisor } else { 0 },
+        }
+    }
+
+    pub fn offset(&self, phase: Phase, previous: Phase) -> Phase {
+        let phase = if self.reverse { phase.wrapping_neg() } else { phase };
+        let previous = if self.reverse_previous { previous.wrapping_neg() } else { previous };
+        phase.wrapping_sub(previous)
+    }
+
+    fn delta32(&self, phase: i32, previous: i32, m: i32) -> i32 {
+        const LOG_S: i32 = 32 + Time::LOG2_SYNTHESIS_HOP_REVOLUTION;
+        let da = phase.wrapping_sub(previous).wrapping_sub(m.wrapping_mul(self.a));
+        (m << LOG_S).wrapping_add((da >> 15).wrapping_mul(self.multiplier))
+    }
+
+    pub fn delta(&self, phase: Phase, previous: Phase, m: i32) -> Phase {
+        (self.delta32((phase as i32) << 16, (previous as i32) << 16, m) >> 16) as Phase
+    }
+}
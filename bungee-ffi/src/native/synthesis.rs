@@ -0,0 +1,58 @@
+//! Phase rotation of each bin for the current grain, as Synthesis.cpp
+
+use super::fourier::bin_count;
+use super::grain::{Grain, Track};
+use super::partials::TRANSIENT_RISE;
+use super::stretch;
+
+fn synthesise_track(log2_synthesis_hop: i32, grain: &Grain, previous: &Grain, track: &mut Track, previous_track: &Track) {
+    let valid_bin_count = grain.valid_bin_count;
+    stretch::Frequency::new(grain.analysis.speed as f32).apply(valid_bin_count, &mut track.rotation, &track.phase);
+
+    if grain.continuous {
+        let stretch_time = stretch::Time::new(log2_synthesis_hop, grain.analysis.hop, previous.analysis.hop);
+
+        for (i, partial) in track.partials.iter().enumerate() {
+            let peak = partial.peak as usize;
+            let offset = stretch_time.offset(track.phase[peak], previous_track.phase[peak]);
+            let stretched = stretch_time.delta(track.phase[peak], previous_track.phase[peak], peak as i32);
+            track.delta[i] = previous_track.rotation[peak].wrapping_sub(offset).wrapping_add(stretched).wrapping_sub(track.rotation[peak]);
+        }
+
+        if track.transient {
+            for (i, partial) in track.partials.iter().enumerate() {
+                let peak = partial.peak as usize;
+                if peak < previous.valid_bin_count && track.energy[peak] > TRANSIENT_RISE * previous_track.energy[peak] {
+                    track.delta[i] = track.rotation[peak].wrapping_neg();
+                }
+            }
+        }
+    } else {
+        for (i, partial) in track.partials.iter().enumerate() {
+            track.delta[i] = track.rotation[partial.peak as usize].wrapping_neg();
+        }
+    }
+
+    let mut n = 0;
+    for (i, partial) in track.partials.iter().enumerate() {
+        loop {
+            track.rotation[n] = track.rotation[n].wrapping_add(track.delta[i]);
+            n += 1;
+            if n >= partial.end as usize {
+                break;
+            }
+        }
+    }
+
+    let nyquist = bin_count(grain.log2_transform_length) - 1;
+    track.rotation[nyquist] = track.rotation[nyquist - 1];
+}
+
+/// Set the rotation of each track of `grain`, continuing the phase of `previous`
+pub fn synthesise(log2_synthesis_hop: i32, grain: &mut Grain, previous: &Grain) {
+    let mut tracks = std::mem::take(&mut grain.tracks);
+    for (track, previous_track) in tracks.iter_mut().zip(&previous.tracks).take(grain.track_count()) {
+        synthesise_track(log2_synthesis_hop, grain, previous, track, previous_track);
+    }
+    grain.tracks = tracks;
+}
@@ -0,0 +1,144 @@
+//! Types and constants of bungee_c.h, written out as bindgen would generate them
+//!
+//! Pure-rust builds run no bindgen, so this file must be kept in step with the header.
+
+pub const BUNGEE_MIN_SAMPLE_RATE: u32 = 8000;
+pub const BUNGEE_MAX_SAMPLE_RATE: u32 = 768000;
+pub const BUNGEE_MIN_LOG2_SYNTHESIS_HOP: u32 = 6;
+pub const BUNGEE_MAX_LOG2_SYNTHESIS_HOP: u32 = 13;
+pub const BUNGEE_DEFAULT_MAX_PITCH_OCTAVES: u32 = 2;
+pub const BUNGEE_MAX_PITCH_OCTAVES: u32 = 6;
+pub const BUNGEE_MAX_KAISER_BETA: f64 = 40.0;
+pub const BUNGEE_MAX_WINDOW_COEFFICIENTS: u32 = 16;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_fft_backend_t {
+    pub context: *mut std::os::raw::c_void,
+    pub prepare: Option<unsafe extern "C" fn(context: *mut std::os::raw::c_void, log2_length: std::os::raw::c_int, inverse: bool)>,
+    pub forward: Option<unsafe extern "C" fn(context: *mut std::os::raw::c_void, log2_length: std::os::raw::c_int, time: *const f32, frequency: *mut f32)>,
+    pub inverse: Option<unsafe extern "C" fn(context: *mut std::os::raw::c_void, log2_length: std::os::raw::c_int, time: *mut f32, frequency: *const f32)>,
+    pub destroy: Option<unsafe extern "C" fn(context: *mut std::os::raw::c_void)>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_window_t {
+    pub shape: bungee_window_shape_t,
+    pub kaiser_beta: f32,
+    pub coefficients: *const f32,
+    pub coefficient_count: i32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_latency_t {
+    pub input: i32,
+    pub output: i32,
+    pub control: i32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_config_t {
+    pub log2_synthesis_hop: i32,
+    pub max_pitch_octaves: i32,
+    pub fft: *const bungee_fft_backend_t,
+    pub window: *const bungee_window_t,
+}
+pub const bungee_error_BUNGEE_OK: bungee_error = 0;
+pub const bungee_error_BUNGEE_NULL_POINTER: bungee_error = 1;
+pub const bungee_error_BUNGEE_INVALID_PARAM: bungee_error = 2;
+pub const bungee_error_BUNGEE_MEMORY: bungee_error = 3;
+pub const bungee_error_BUNGEE_INVALID_STATE: bungee_error = 4;
+pub const bungee_error_BUNGEE_BUFFER_TOO_SMALL: bungee_error = 5;
+pub const bungee_error_BUNGEE_INVALID_CHANNEL_COUNT: bungee_error = 6;
+pub const bungee_error_BUNGEE_RATE_OUT_OF_RANGE: bungee_error = 7;
+pub const bungee_error_BUNGEE_GRAIN_OUT_OF_ORDER: bungee_error = 8;
+pub const bungee_error_BUNGEE_PITCH_OUT_OF_RANGE: bungee_error = 9;
+pub type bungee_error = std::os::raw::c_uint;
+pub use self::bungee_error as bungee_error_t;
+pub const bungee_sample_format_BUNGEE_SAMPLE_F32: bungee_sample_format = 0;
+pub const bungee_sample_format_BUNGEE_SAMPLE_F64: bungee_sample_format = 1;
+pub const bungee_sample_format_BUNGEE_SAMPLE_I16: bungee_sample_format = 2;
+pub const bungee_sample_format_BUNGEE_SAMPLE_I24: bungee_sample_format = 3;
+pub const bungee_sample_format_BUNGEE_SAMPLE_I32: bungee_sample_format = 4;
+pub type bungee_sample_format = std::os::raw::c_uint;
+pub use self::bungee_sample_format as bungee_sample_format_t;
+pub const bungee_curve_BUNGEE_CURVE_HOLD: bungee_curve = 0;
+pub const bungee_curve_BUNGEE_CURVE_LINEAR: bungee_curve = 1;
+pub const bungee_curve_BUNGEE_CURVE_EXPONENTIAL: bungee_curve = 2;
+pub type bungee_curve = std::os::raw::c_uint;
+pub use self::bungee_curve as bungee_curve_t;
+pub const bungee_parameter_BUNGEE_PARAMETER_SPEED: bungee_parameter = 0;
+pub const bungee_parameter_BUNGEE_PARAMETER_PITCH: bungee_parameter = 1;
+pub type bungee_parameter = std::os::raw::c_uint;
+pub use self::bungee_parameter as bungee_parameter_t;
+pub const bungee_time_base_BUNGEE_TIME_BASE_INPUT: bungee_time_base = 0;
+pub const bungee_time_base_BUNGEE_TIME_BASE_OUTPUT: bungee_time_base = 1;
+pub type bungee_time_base = std::os::raw::c_uint;
+pub use self::bungee_time_base as bungee_time_base_t;
+pub const bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT: bungee_resample_mode = 0;
+pub const bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN: bungee_resample_mode = 1;
+pub const bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN_OUT: bungee_resample_mode = 2;
+pub const bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_OUT: bungee_resample_mode = 3;
+pub const bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN: bungee_resample_mode = 4;
+pub type bungee_resample_mode = std::os::raw::c_uint;
+pub use self::bungee_resample_mode as bungee_resample_mode_t;
+pub const bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST: bungee_interpolation = 0;
+pub const bungee_interpolation_BUNGEE_INTERPOLATION_BILINEAR: bungee_interpolation = 1;
+pub const bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE: bungee_interpolation = 2;
+pub const bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC: bungee_interpolation = 3;
+pub type bungee_interpolation = std::os::raw::c_uint;
+pub use self::bungee_interpolation as bungee_interpolation_t;
+pub const bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED: bungee_channel_coupling = 0;
+pub const bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE: bungee_channel_coupling = 1;
+pub const bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_INDEPENDENT: bungee_channel_coupling = 2;
+pub type bungee_channel_coupling = std::os::raw::c_uint;
+pub use self::bungee_channel_coupling as bungee_channel_coupling_t;
+pub const bungee_window_shape_BUNGEE_WINDOW_HANN: bungee_window_shape = 0;
+pub const bungee_window_shape_BUNGEE_WINDOW_BLACKMAN_HARRIS: bungee_window_shape = 1;
+pub const bungee_window_shape_BUNGEE_WINDOW_KAISER: bungee_window_shape = 2;
+pub const bungee_window_shape_BUNGEE_WINDOW_COSINE_SUM: bungee_window_shape = 3;
+pub type bungee_window_shape = std::os::raw::c_uint;
+pub use self::bungee_window_shape as bungee_window_shape_t;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_breakpoint_t {
+    pub time: f64,
+    pub value: f64,
+    pub curve: bungee_curve_t,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_sample_rates_t {
+    pub input_rate: std::os::raw::c_int,
+    pub output_rate: std::os::raw::c_int,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_request_t {
+    pub position: f64,
+    pub speed: f64,
+    pub pitch: f64,
+    pub reset: bool,
+    pub preserve_formants: bool,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_input_chunk_t {
+    pub begin: i32,
+    pub end: i32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_output_chunk_t {
+    pub data: *mut f32,
+    pub frame_count: i32,
+    pub channel_stride: usize,
+    pub request: [bungee_request_t; 2usize],
+    pub first_position: f64,
+    pub last_position: f64,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct bungee_stretcher {
+    _unused: [u8; 0],
+}
+pub type bungee_stretcher_t = bungee_stretcher;
@@ -0,0 +1,534 @@
+//! The native engine on its own, and against the C++ engine when that is built
+//!
+//! `cargo test --features rustfft` compares the two, both transforming with RustFFT so that
+//! only rounding in the maths library separates them; `cargo test --features pure-rust` runs
+//! the native checks alone.
+
+use std::f64::consts::PI;
+
+use crate::{
+    bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED, bungee_channel_coupling_t, bungee_config_t,
+    bungee_error_BUNGEE_OK, bungee_input_chunk_t, bungee_interpolation_BUNGEE_INTERPOLATION_BILINEAR,
+    bungee_interpolation_t, bungee_output_chunk_t, bungee_request_t, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT,
+    bungee_resample_mode_t, bungee_sample_rates_t, bungee_window_shape_BUNGEE_WINDOW_BLACKMAN_HARRIS,
+    bungee_window_shape_BUNGEE_WINDOW_COSINE_SUM, bungee_window_shape_BUNGEE_WINDOW_HANN,
+    bungee_window_shape_BUNGEE_WINDOW_KAISER, bungee_window_shape_t, bungee_window_t,
+};
+
+const RATE: i32 = 44100;
+
+/// Parameters of one stretch
+#[derive(Clone, Copy)]
+struct Case {
+    channels: usize,
+    output_rate: i32,
+    log2_synthesis_hop: i32,
+    speed: f64,
+    pitch: f64,
+    preserve_formants: bool,
+    resample_mode: bungee_resample_mode_t,
+    interpolation: bungee_interpolation_t,
+    sinc_taps: i32,
+    channel_coupling: bungee_channel_coupling_t,
+    transient_sensitivity: f32,
+    window: bungee_window_t,
+}
+
+impl Default for Case {
+    fn default() -> Self {
+        Self {
+            channels: 2,
+            output_rate: RATE,
+            log2_synthesis_hop: 0,
+            speed: 1.0,
+            pitch: 1.0,
+            preserve_formants: false,
+            resample_mode: bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT,
+            interpolation: bungee_interpolation_BUNGEE_INTERPOLATION_BILINEAR,
+            sinc_taps: 0,
+            channel_coupling: bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_SUM_LOCKED,
+            transient_sensitivity: 0.0,
+            window: window(bungee_window_shape_BUNGEE_WINDOW_HANN, 0.0, &[]),
+        }
+    }
+}
+
+/// Four-term Nuttall coefficients, a custom cosine sum
+static NUTTALL: [f32; 4] = [0.355768, 0.487396, 0.144232, 0.012604];
+
+fn window(shape: bungee_window_shape_t, kaiser_beta: f32, coefficients: &'static [f32]) -> bungee_window_t {
+    bungee_window_t {
+        shape,
+        kaiser_beta,
+        coefficients: coefficients.as_ptr(),
+        coefficient_count: coefficients.len() as i32,
+    }
+}
+
+/// One window of each family
+fn windows() -> [bungee_window_t; 4] {
+    [
+        window(bungee_window_shape_BUNGEE_WINDOW_HANN, 0.0, &[]),
+        window(bungee_window_shape_BUNGEE_WINDOW_BLACKMAN_HARRIS, 0.0, &[]),
+        window(bungee_window_shape_BUNGEE_WINDOW_KAISER, 8.0, &[]),
+        window(bungee_window_shape_BUNGEE_WINDOW_COSINE_SUM, 0.0, &NUTTALL),
+    ]
+}
+
+/// A chord with vibrato, differing by channel, and a click every quarter second
+fn signal(frames: usize, channels: usize) -> Vec<f32> {
+    let mut planar = vec![0.0; frames * channels];
+    for c in 0..channels {
+        for (i, sample) in planar[c * frames..][..frames].iter_mut().enumerate() {
+            let t = i as f64 / RATE as f64;
+            let vibrato = 3.0 * (2.0 * PI * 5.0 * t).sin();
+            let chord: f64 = [220.0, 277.2, 330.0 * (1.0 + 0.01 * c as f64)]
+                .iter()
+                .map(|f| (2.0 * PI * (f * t + vibrato * f / 1000.0)).sin())
+                .sum();
+            let click = if i % (RATE as usize / 4) < 32 { 0.5 } else { 0.0 };
+            *sample = (0.2 * chord + click) as f32;
+        }
+    }
+    planar
+}
+
+/// Output of a stretch, interleaved, and the input position of each output frame
+struct Stretched {
+    output: Vec<f32>,
+    positions: Vec<f64>,
+}
+
+/// Stretch all of planar `input` through the functions of a bungee_c.h implementation
+macro_rules! stretch_with {
+    ($name:ident, $($api:ident)::+) => {
+        fn $name(case: &Case, input: &[f32], fft: Option<crate::bungee_fft_backend_t>) -> Stretched {
+            use $($api)::+::{
+                bungee_analyse_grain, bungee_create_with_config, bungee_destroy, bungee_is_flushed,
+                bungee_max_output_frame_count, bungee_next, bungee_preroll, bungee_set_channel_coupling,
+                bungee_set_interpolation, bungee_set_resample_mode, bungee_set_transient_sensitivity,
+                bungee_specify_grain, bungee_synthesise_grain,
+            };
+
+            let channels = case.channels;
+            let frames = input.len() / channels;
+            let rates = bungee_sample_rates_t { input_rate: RATE, output_rate: case.output_rate };
+            let config = bungee_config_t {
+                log2_synthesis_hop: case.log2_synthesis_hop,
+                max_pitch_octaves: 0,
+                fft: fft.as_ref().map_or(std::ptr::null(), |fft| fft),
+                window: &case.window,
+            };
+
+            unsafe {
+                let stretcher = bungee_create_with_config(rates, channels as i32, &config);
+                assert!(!stretcher.is_null());
+                assert_eq!(bungee_set_resample_mode(stretcher, case.resample_mode), bungee_error_BUNGEE_OK);
+                assert_eq!(bungee_set_interpolation(stretcher, case.interpolation, case.sinc_taps), bungee_error_BUNGEE_OK);
+                assert_eq!(bungee_set_channel_coupling(stretcher, case.channel_coupling), bungee_error_BUNGEE_OK);
+                assert_eq!(bungee_set_transient_sensitivity(stretcher, case.transient_sensitivity), bungee_error_BUNGEE_OK);
+
+                let mut request = bungee_request_t {
+                    position: if case.speed < 0.0 { frames as f64 } else { 0.0 },
+                    speed: case.speed,
+                    pitch: case.pitch,
+                    reset: true,
+                    preserve_formants: case.preserve_formants,
+                };
+                assert_eq!(bungee_preroll(stretcher, &mut request), bungee_error_BUNGEE_OK);
+
+                let mut buffer = vec![0.0; bungee_max_output_frame_count(stretcher) * channels];
+                let mut grain = Vec::new();
+                let mut stretched = Stretched { output: Vec::new(), positions: Vec::new() };
+                let mut flushing = 0;
+                while flushing < 8 && !(flushing > 0 && bungee_is_flushed(stretcher)) {
+                    let mut chunk = bungee_input_chunk_t { begin: 0, end: 0 };
+                    assert_eq!(bungee_specify_grain(stretcher, &mut chunk), bungee_error_BUNGEE_OK);
+
+                    let grain_frames = (chunk.end - chunk.begin).max(0) as usize;
+                    grain.clear();
+                    grain.resize(grain_frames.max(1) * channels, 0.0);
+                    for c in 0..channels {
+                        for f in 0..grain_frames {
+                            let frame = chunk.begin as i64 + f as i64;
+                            if (0..frames as i64).contains(&frame) {
+                                grain[c * grain_frames + f] = input[c * frames + frame as usize];
+                            }
+                        }
+                    }
+                    assert_eq!(bungee_analyse_grain(stretcher, grain.as_ptr(), grain_frames), bungee_error_BUNGEE_OK);
+
+                    let empty = bungee_request_t {
+                        position: f64::NAN,
+                        speed: 0.0,
+                        pitch: 1.0,
+                        reset: false,
+                        preserve_formants: false,
+                    };
+                    let mut output = bungee_output_chunk_t {
+                        data: buffer.as_mut_ptr(),
+                        frame_count: (buffer.len() / channels) as i32,
+                        channel_stride: channels,
+                        request: [empty; 2],
+                        first_position: f64::NAN,
+                        last_position: f64::NAN,
+                    };
+                    assert_eq!(bungee_synthesise_grain(stretcher, &mut output), bungee_error_BUNGEE_OK);
+                    let count = output.frame_count as usize;
+                    stretched.output.extend_from_slice(&buffer[..count * channels]);
+                    let step = if count > 1 { (output.last_position - output.first_position) / (count - 1) as f64 } else { 0.0 };
+                    stretched.positions.extend((0..count).map(|i| output.first_position + step * i as f64));
+
+                    // After the input is used up, run on with NaN positions until the pipeline empties
+                    let used_up = if case.speed < 0.0 { request.position < 0.0 } else { request.position > frames as f64 };
+                    if flushing > 0 || used_up {
+                        request.position = f64::NAN;
+                        flushing += 1;
+                    }
+                    assert_eq!(bungee_next(stretcher, &mut request), bungee_error_BUNGEE_OK);
+                }
+                bungee_destroy(stretcher);
+                stretched
+            }
+        }
+    };
+}
+
+stretch_with!(stretch_native, super::api);
+
+fn rms(samples: impl Iterator<Item = f32>) -> f64 {
+    let (sum, count) = samples.fold((0.0, 0usize), |(sum, count), x| (sum + (x as f64).powi(2), count + 1));
+    (sum / count.max(1) as f64).sqrt()
+}
+
+/// Relative RMS error of a unit-speed stretch, away from the start and end of the input
+fn unit_speed_error(case: &Case) -> f64 {
+    let input = signal(RATE as usize, case.channels);
+    let stretched = stretch_native(case, &input, None);
+    let frames = input.len() / case.channels;
+
+    // Past the start and end, where the input is windowed against silence
+    let margin = 4096.0;
+    let mut error = Vec::new();
+    let mut reference = Vec::new();
+    for (i, &position) in stretched.positions.iter().enumerate() {
+        if position > margin && position < frames as f64 - margin {
+            let frame = position.round() as usize;
+            assert!((position - frame as f64).abs() < 1e-6, "speed 1 output falls between input frames");
+            for c in 0..case.channels {
+                let expected = input[c * frames + frame];
+                error.push(stretched.output[i * case.channels + c] - expected);
+                reference.push(expected);
+            }
+        }
+    }
+    assert!(reference.len() > frames, "too few output frames: {}", reference.len());
+    rms(error.into_iter()) / rms(reference.into_iter())
+}
+
+#[test]
+fn unit_speed_reproduces_the_input() {
+    // The analysis and synthesis windows overlap-add to unity only within a ripple of 0.25%
+    let relative = unit_speed_error(&Case::default());
+    assert!(relative < 3e-3, "relative error {relative}");
+}
+
+#[test]
+fn every_window_family_keeps_unity_gain() {
+    // Each family's own overlap-add ripple, with its gain compensated
+    for window in windows() {
+        let relative = unit_speed_error(&Case { window, ..Case::default() });
+        assert!(relative < 5e-3, "window shape {}: relative error {relative}", window.shape);
+    }
+}
+
+#[test]
+fn output_length_follows_speed_and_rates() {
+    let input = signal(RATE as usize, 1);
+    for (speed, output_rate) in [(0.5, RATE), (2.0, RATE), (1.0, 48000), (1.5, 22050)] {
+        let case = Case { channels: 1, speed, output_rate, ..Case::default() };
+        let stretched = stretch_native(&case, &input, None);
+
+        // Output frames that map onto the input, against the expected count
+        let covered = stretched.positions.iter().filter(|p| (0.0..RATE as f64).contains(*p)).count() as f64;
+        let expected = RATE as f64 / speed * output_rate as f64 / RATE as f64;
+        assert!((covered / expected - 1.0).abs() < 0.01, "speed {speed}, rate {output_rate}: {covered} frames");
+    }
+}
+
+#[test]
+fn pitch_shift_moves_a_tone() {
+    let frames = RATE as usize;
+    let tone: Vec<f32> = (0..frames).map(|i| (2.0 * PI * 1000.0 * i as f64 / RATE as f64).sin() as f32).collect();
+    let case = Case { channels: 1, pitch: 1.5, ..Case::default() };
+    let stretched = stretch_native(&case, &tone, None);
+
+    // Count zero crossings over the steady middle of the output
+    let middle = &stretched.output[stretched.output.len() / 4..stretched.output.len() * 3 / 4];
+    let crossings = middle.windows(2).filter(|w| (w[0] < 0.0) != (w[1] < 0.0)).count();
+    let frequency = crossings as f64 / 2.0 / (middle.len() as f64 / RATE as f64);
+    assert!((frequency / 1500.0 - 1.0).abs() < 0.01, "frequency {frequency} Hz");
+}
+
+#[test]
+fn latency_spans_the_grain_pipeline() {
+    use super::api::{
+        bungee_analyse_grain, bungee_create_with_config, bungee_destroy, bungee_latency, bungee_next, bungee_preroll,
+        bungee_set_resample_mode, bungee_specify_grain, bungee_synthesise_grain,
+    };
+    use crate::{bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN, bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN};
+
+    for (output_rate, pitch, resample_mode) in [
+        (RATE, 1.0, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT),
+        (48000, 1.0, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT),
+        (RATE, 1.3, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT),
+        (RATE, 0.8, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN),
+        (RATE, 1.3, bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN),
+        (48000, 1.0, bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN),
+        // The default two octaves either way
+        (RATE, 4.0, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT),
+        (RATE, 0.25, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT),
+        (RATE, 4.0, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN),
+        (RATE, 0.25, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN),
+    ] {
+        let rates = bungee_sample_rates_t { input_rate: RATE, output_rate };
+        let config = bungee_config_t { log2_synthesis_hop: 0, max_pitch_octaves: 0, fft: std::ptr::null(), window: std::ptr::null() };
+        unsafe {
+            let stretcher = bungee_create_with_config(rates, 1, &config);
+            assert_eq!(bungee_set_resample_mode(stretcher, resample_mode), bungee_error_BUNGEE_OK);
+            let mut request = bungee_request_t { position: 0.0, speed: 1.0, pitch, reset: true, preserve_formants: false };
+            assert_eq!(bungee_preroll(stretcher, &mut request), bungee_error_BUNGEE_OK);
+            let latency = bungee_latency(stretcher);
+            if output_rate == RATE && pitch == 1.0 {
+                // Two hops of 512 plus half a grain of 8 hops, and the control change two hops later
+                assert_eq!((latency.input, latency.output, latency.control), (3072, 3072, 1024));
+            }
+            let output_per_input = output_rate as f64 / RATE as f64;
+            assert!((latency.output as f64 - latency.input as f64 * output_per_input).abs() <= 2.0);
+
+            // How far each grain reads beyond the input position of its output's first frame
+            let mut buffer = vec![0.0; 1 << 14];
+            let mut furthest = f64::MIN;
+            for _ in 0..32 {
+                let mut chunk = bungee_input_chunk_t { begin: 0, end: 0 };
+                assert_eq!(bungee_specify_grain(stretcher, &mut chunk), bungee_error_BUNGEE_OK);
+                let grain = vec![0.0; (chunk.end - chunk.begin) as usize];
+                assert_eq!(bungee_analyse_grain(stretcher, grain.as_ptr(), grain.len()), bungee_error_BUNGEE_OK);
+                let mut output = std::mem::zeroed::<bungee_output_chunk_t>();
+                output.data = buffer.as_mut_ptr();
+                output.frame_count = buffer.len() as i32;
+                output.channel_stride = 1;
+                assert_eq!(bungee_synthesise_grain(stretcher, &mut output), bungee_error_BUNGEE_OK);
+                if output.first_position >= 0.0 {
+                    furthest = furthest.max(chunk.end as f64 - output.first_position);
+                }
+                assert_eq!(bungee_next(stretcher, &mut request), bungee_error_BUNGEE_OK);
+            }
+            bungee_destroy(stretcher);
+            // Within the half frame by which grain positions round
+            let input = latency.input as f64;
+            assert!(furthest <= input + 0.5 && furthest >= input - 1.5, "rate {output_rate}, pitch {pitch}: {furthest} frames read ahead, latency {latency:?}");
+        }
+    }
+}
+
+/// Amplitude of the `frequency` Hz component of `block`
+fn amplitude(block: &[f32], frequency: f64) -> f64 {
+    let (mut re, mut im) = (0.0, 0.0);
+    for (i, &x) in block.iter().enumerate() {
+        let phase = 2.0 * PI * frequency * i as f64 / RATE as f64;
+        re += x as f64 * phase.cos();
+        im += x as f64 * phase.sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / block.len() as f64
+}
+
+#[test]
+fn seek_crossfades_to_the_new_position() {
+    use super::api::{
+        bungee_analyse_grain, bungee_create_with_config, bungee_destroy, bungee_next, bungee_preroll, bungee_seek,
+        bungee_specify_grain, bungee_synthesise_grain,
+    };
+
+    // 440 Hz for two seconds, then 1 kHz, so that each position is heard apart from the other
+    let frames = 4 * RATE as usize;
+    let input: Vec<f32> = (0..frames)
+        .map(|i| {
+            let frequency = if i < frames / 2 { 440.0 } else { 1000.0 };
+            (0.5 * (2.0 * PI * frequency * i as f64 / RATE as f64).sin()) as f32
+        })
+        .collect();
+
+    let (seek_from, seek_to, crossfade) = (RATE as f64, 3.0 * RATE as f64, 8192);
+    let rates = bungee_sample_rates_t { input_rate: RATE, output_rate: RATE };
+    let config = bungee_config_t { log2_synthesis_hop: 0, max_pitch_octaves: 0, fft: std::ptr::null(), window: std::ptr::null() };
+    let mut output = Vec::new();
+    let mut positions = Vec::new();
+    let mut seek_frame = None;
+    unsafe {
+        let stretcher = bungee_create_with_config(rates, 1, &config);
+        let mut request = bungee_request_t { position: 0.0, speed: 1.0, pitch: 1.0, reset: true, preserve_formants: false };
+        assert_eq!(bungee_preroll(stretcher, &mut request), bungee_error_BUNGEE_OK);
+        let mut buffer = vec![0.0; 1 << 14];
+        while output.len() < 2 * RATE as usize {
+            let mut chunk = bungee_input_chunk_t { begin: 0, end: 0 };
+            assert_eq!(bungee_specify_grain(stretcher, &mut chunk), bungee_error_BUNGEE_OK);
+            let grain: Vec<f32> = (chunk.begin..chunk.end).map(|i| if (0..frames as i32).contains(&i) { input[i as usize] } else { 0.0 }).collect();
+            assert_eq!(bungee_analyse_grain(stretcher, grain.as_ptr(), grain.len()), bungee_error_BUNGEE_OK);
+            let mut chunk = std::mem::zeroed::<bungee_output_chunk_t>();
+            chunk.data = buffer.as_mut_ptr();
+            chunk.frame_count = buffer.len() as i32;
+            chunk.channel_stride = 1;
+            assert_eq!(bungee_synthesise_grain(stretcher, &mut chunk), bungee_error_BUNGEE_OK);
+            let count = chunk.frame_count as usize;
+            output.extend_from_slice(&buffer[..count]);
+            positions.extend(std::iter::repeat_n(chunk.first_position, count));
+
+            if seek_frame.is_none() && request.position >= seek_from {
+                seek_frame = Some(output.len());
+                request.position = seek_to;
+                request.reset = true;
+                assert_eq!(bungee_seek(stretcher, &mut request, crossfade), bungee_error_BUNGEE_OK);
+            } else {
+                assert_eq!(bungee_next(stretcher, &mut request), bungee_error_BUNGEE_OK);
+            }
+        }
+        bungee_destroy(stretcher);
+    }
+
+    let seek_frame = seek_frame.unwrap();
+    let mut mixed = 0;
+    for block in output[seek_frame - 8192..seek_frame + 24576].chunks(1024) {
+        let (old, new) = (amplitude(block, 440.0), amplitude(block, 1000.0));
+        // Equal power: the two positions' levels, 0.5 each on their own, sum in quadrature
+        let level = (old * old + new * new).sqrt();
+        assert!((0.4..0.6).contains(&level), "level {level} across the seek");
+        if old > 0.1 && new > 0.1 {
+            mixed += block.len();
+        }
+    }
+    // Both are heard for much of the fade, not only the grain or two a hard cut overlaps
+    assert!(mixed >= crossfade as usize / 2, "{mixed} frames mixed");
+
+    // Output carries on from the old position until the new chain is heard, then follows it
+    assert!(positions[seek_frame..][..256].iter().all(|&p| (p - seek_from).abs() < 8192.0));
+    assert!(positions[seek_frame + 24576..].iter().all(|&p| p > seek_to), "output did not move to the new position");
+}
+
+#[test]
+fn every_kernel_keeps_unity_gain_at_dc() {
+    use super::resample::{Kernel, Padded};
+    use super::Array;
+    use crate::{
+        bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE, bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST,
+        bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC,
+    };
+
+    for (interpolation, sinc_taps) in [
+        (bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST, 0),
+        (bungee_interpolation_BUNGEE_INTERPOLATION_BILINEAR, 0),
+        (bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE, 0),
+        (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 4),
+        (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 32),
+    ] {
+        let kernel = Kernel::select(interpolation, sinc_taps);
+        for ratio in [0.7, 1.3] {
+            // Reading a constant fixed buffer, padding included, at any rate gives the constant
+            let mut fixed = Padded::new(256, 1);
+            fixed.array.fill(0.5);
+            fixed.frame_count = 256;
+            let mut variable = Array::new(512, 1);
+            let mut offset = 0.0;
+            let count = (kernel.output)(&fixed, &mut offset, &mut variable, ratio, ratio, false) as usize;
+            assert!(variable.col(0)[..count].iter().all(|&x| (x - 0.5).abs() < 1e-5), "{interpolation} {sinc_taps} at {ratio}");
+        }
+    }
+}
+
+#[cfg(not(any(feature = "pure-rust", feature = "c-stub")))]
+mod against_engine {
+    use super::*;
+    use crate::fft::{backend, RustFft};
+    use crate::{
+        bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_INDEPENDENT, bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE,
+        bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE, bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST,
+        bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN,
+        bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN, bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_OUT,
+    };
+
+    stretch_with!(stretch_engine, crate);
+
+    /// Stretch with both engines and require the same frames to within rounding
+    fn compare(case: Case) {
+        let input = signal(RATE as usize / 2, case.channels);
+        let engine = stretch_engine(&case, &input, Some(backend(RustFft::new())));
+        let native = stretch_native(&case, &input, None);
+
+        assert_eq!(engine.output.len(), native.output.len(), "frame counts differ");
+        for (e, n) in engine.positions.iter().zip(&native.positions) {
+            assert!(e == n || (e.is_nan() && n.is_nan()), "positions differ: {e} and {n}");
+        }
+
+        let error = rms(engine.output.iter().zip(&native.output).map(|(e, n)| e - n));
+        let relative = error / rms(engine.output.iter().copied());
+        assert!(relative < 1e-4, "relative difference {relative}");
+    }
+
+    #[test]
+    fn timing_and_synthesis_at_several_speeds() {
+        for speed in [1.0, 0.5, 0.75, 1.5, 3.0, -1.0, -0.8] {
+            compare(Case { speed, ..Case::default() });
+        }
+    }
+
+    #[test]
+    fn output_resampling_with_each_kernel() {
+        for (interpolation, sinc_taps) in [
+            (bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST, 0),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_BILINEAR, 0),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE, 0),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 8),
+            (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, 32),
+        ] {
+            for pitch in [0.7, 1.25] {
+                compare(Case { pitch, interpolation, sinc_taps, ..Case::default() });
+            }
+        }
+    }
+
+    #[test]
+    fn input_resampling_and_sample_rate_conversion() {
+        compare(Case { pitch: 1.3, resample_mode: bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN, ..Case::default() });
+        compare(Case { pitch: 0.8, resample_mode: bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN, ..Case::default() });
+        compare(Case { output_rate: 48000, resample_mode: bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_OUT, ..Case::default() });
+        for pitch in [0.6, 1.5] {
+            compare(Case { pitch, speed: 0.75, resample_mode: bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN, ..Case::default() });
+        }
+        compare(Case {
+            pitch: 1.2,
+            output_rate: 48000,
+            resample_mode: bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN,
+            interpolation: bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC,
+            sinc_taps: 16,
+            ..Case::default()
+        });
+    }
+
+    #[test]
+    fn window_families() {
+        for window in windows() {
+            compare(Case { window, speed: 0.8, ..Case::default() });
+        }
+        compare(Case { window: windows()[2], pitch: 1.3, log2_synthesis_hop: 8, ..Case::default() });
+    }
+
+    #[test]
+    fn hops_couplings_formants_and_transients() {
+        compare(Case { log2_synthesis_hop: 8, speed: 0.9, ..Case::default() });
+        compare(Case { channels: 1, log2_synthesis_hop: 10, speed: 1.2, ..Case::default() });
+        compare(Case { channel_coupling: bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_MID_SIDE, speed: 0.8, ..Case::default() });
+        compare(Case { channel_coupling: bungee_channel_coupling_BUNGEE_CHANNEL_COUPLING_INDEPENDENT, speed: 0.8, ..Case::default() });
+        compare(Case { pitch: 1.4, preserve_formants: true, ..Case::default() });
+        compare(Case { speed: 0.6, transient_sensitivity: 0.5, ..Case::default() });
+    }
+}
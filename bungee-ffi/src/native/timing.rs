@@ -0,0 +1,86 @@
+//! Grain positions and buffer sizes, as Timing.cpp
+
+use super::automation::Automation;
+use super::resample::{Kernel, Operations};
+use crate::{bungee_latency_t, bungee_request_t, bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT, bungee_resample_mode_t, bungee_sample_rates_t};
+
+pub struct Timing {
+    pub log2_synthesis_hop: i32,
+    pub max_pitch_octaves: i32,
+    pub sample_rates: bungee_sample_rates_t,
+    pub automation: Automation,
+    pub resample_mode: bungee_resample_mode_t,
+    pub resample_kernel: Kernel,
+}
+
+impl Timing {
+    pub fn new(sample_rates: bungee_sample_rates_t, log2_synthesis_hop_override: i32, max_pitch_octaves: i32) -> Self {
+        let log2_input_rate = 31 - sample_rates.input_rate.leading_zeros() as i32;
+        Self {
+            log2_synthesis_hop: match log2_synthesis_hop_override {
+                0 => log2_input_rate - 6,
+                hop => hop,
+            },
+            max_pitch_octaves: match max_pitch_octaves {
+                0 => 2,
+                octaves => octaves,
+            },
+            sample_rates,
+            automation: Automation::default(),
+            resample_mode: bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT,
+            resample_kernel: Kernel::default(),
+        }
+    }
+
+    pub fn max_input_frame_count(&self) -> i32 {
+        let max = ((self.sample_rates.input_rate as i64) << (self.max_pitch_octaves + self.log2_synthesis_hop + 3))
+            / self.sample_rates.output_rate as i64;
+        max as i32 + 1
+    }
+
+    pub fn max_output_frame_count(&self) -> i32 {
+        let max = ((self.sample_rates.output_rate as i64) << (self.max_pitch_octaves + self.log2_synthesis_hop))
+            / self.sample_rates.input_rate as i64;
+        max as i32 + 1
+    }
+
+    pub fn calculate_input_hop(&self, request: &bungee_request_t) -> f64 {
+        let unit_hop = (1 << self.log2_synthesis_hop) as f64
+            * Operations::default().setup(self.sample_rates, request.pitch, self.resample_mode, self.resample_kernel);
+        unit_hop * request.speed
+    }
+
+    pub fn latency(&self, pitch: f64) -> bungee_latency_t {
+        let mut operations = Operations::default();
+        let unit_hop = (1 << self.log2_synthesis_hop) as f64
+            * operations.setup(self.sample_rates, pitch, self.resample_mode, self.resample_kernel);
+
+        // Half the input of a grain, as Grain::specify
+        let mut half_input_frame_count = 4 << self.log2_synthesis_hop;
+        if operations.input.ratio != 1.0 {
+            half_input_frame_count = (half_input_frame_count as f32 / operations.input.ratio).round() as i32 + 1;
+        }
+
+        // An output chunk starts at the grain two before the latest, whose input reaches half a grain beyond its position
+        let output_per_input = self.sample_rates.output_rate as f64 / self.sample_rates.input_rate as f64;
+        bungee_latency_t {
+            input: (2.0 * unit_hop).ceil() as i32 + half_input_frame_count,
+            output: ((2.0 * unit_hop + half_input_frame_count as f64) * output_per_input).ceil() as i32,
+            control: (2.0 * unit_hop * output_per_input).ceil() as i32,
+        }
+    }
+
+    pub fn preroll(&self, request: &mut bungee_request_t) {
+        self.automation.apply(request, 0.0);
+        request.position -= 4.0 * self.calculate_input_hop(request);
+        request.reset = true;
+    }
+
+    pub fn next(&self, request: &mut bungee_request_t) {
+        if !request.speed.is_nan() && !request.position.is_nan() {
+            request.position += self.calculate_input_hop(request);
+            request.reset = false;
+            self.automation.apply(request, self.automation.output_position);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Analysis and synthesis windows, as Window.cpp
+
+use realfft::num_complex::Complex32;
+
+use super::fourier::{bin_count, transform_length, Transforms};
+
+/// Window family, as Bungee_Window with the coefficients copied
+#[derive(Debug, Clone, Default)]
+pub enum Shape {
+    #[default]
+    Hann,
+    BlackmanHarris,
+    Kaiser(f32),
+    CosineSum(Vec<f32>),
+}
+
+/// Modified Bessel function of the first kind, order zero
+fn bessel_i0(x: f64) -> f64 {
+    let (mut sum, mut term) = (1.0, 1.0);
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= (x * x) / (4.0 * k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Zero-phase window of `1 << log2_size` samples, peaking at sample 0, of the given shape and
+/// arbitrary gain
+pub fn from_shape(transforms: &mut Transforms, log2_size: i32, shape: &Shape) -> Vec<f32> {
+    let coefficients: &[f32] = match shape {
+        Shape::Hann => &[0.5, 0.5],
+        Shape::BlackmanHarris => &[0.35875, 0.48829, 0.14128, 0.01168],
+        Shape::CosineSum(coefficients) => coefficients,
+        Shape::Kaiser(beta) => {
+            let n = 1usize << log2_size;
+            let scale = 1.0 / bessel_i0(*beta as f64);
+            return (0..n)
+                .map(|i| {
+                    let x = 2.0 * (if i < n / 2 { i as f64 } else { i as f64 - n as f64 }) / n as f64;
+                    (bessel_i0(*beta as f64 * (1.0 - x * x).sqrt()) * scale) as f32
+                })
+                .collect();
+        }
+    };
+
+    // Bins that make the zero-phase form of a0 - a1 cos(2 pi n / N) + ...
+    let mut frequency_domain = vec![Complex32::default(); bin_count(log2_size)];
+    for (k, (bin, &c)) in frequency_domain.iter_mut().zip(coefficients).enumerate() {
+        *bin = Complex32::new(if k > 0 { c * 0.5 } else { c }, 0.0);
+    }
+
+    let mut window = vec![0.0; transform_length(log2_size)];
+    transforms.prepare_inverse(log2_size);
+    transforms.inverse(log2_size, &mut window, &frequency_domain);
+    window
+}
+
+/// Scale an analysis window of 8 hops and a synthesis window of 4 hops, both zero-phase, so
+/// that grains windowed by both, with the inverse transform's gain of 8 hops, and lapped every
+/// hop sum to unity on average
+pub fn normalise(log2_synthesis_hop: i32, analysis: &mut [f32], synthesis: &mut [f32]) {
+    let hop = 1usize << log2_synthesis_hop;
+    debug_assert_eq!(analysis.len(), 8 * hop);
+    debug_assert_eq!(synthesis.len(), 4 * hop);
+
+    // Lapped every hop, the synthesis windows alone sum to unity on average
+    let gain = (hop as f64 / synthesis.iter().map(|&w| w as f64).sum::<f64>()) as f32;
+    synthesis.iter_mut().for_each(|w| *w *= gain);
+
+    // As do the products of both windows over the synthesis window's span of -2 to 2 hops
+    let mut product = 0.0;
+    for i in 0..2 * hop {
+        product += analysis[i] as f64 * synthesis[i] as f64;
+    }
+    for i in 1..=2 * hop {
+        product += analysis[8 * hop - i] as f64 * synthesis[4 * hop - i] as f64;
+    }
+    debug_assert!(product > 0.0);
+    let gain = (hop as f64 / (product * analysis.len() as f64)) as f32;
+    analysis.iter_mut().for_each(|w| *w *= gain);
+}
+
+/// Multiply `input` by `window` into `output`, adding to what is there if `add`
+pub fn apply(add: bool, window: &[f32], input: &[f32], output: &mut [f32]) {
+    let products = input.iter().zip(window).map(|(x, w)| x * w);
+    if add {
+        output.iter_mut().zip(products).for_each(|(y, p)| *y += p);
+    } else {
+        output.iter_mut().zip(products).for_each(|(y, p)| *y = p);
+    }
+}
@@ -0,0 +1,276 @@
+//! One-call processing of complete in-memory buffers
+
+use std::ops::Range;
+
+use crate::{BungeeError, Interleaved, Request, SampleRates, Strided, StretcherConfig};
+
+/// Stretch and pitch-shift a complete interleaved buffer
+///
+/// Runs the whole grain loop: preroll, per-grain specify/analyse/synthesise/next, and a
+/// flush with NaN positions once the input is consumed. Output produced before input
+/// position 0 (preroll) or after the end of the input (flush) is trimmed, and the result
+/// is exactly `round(frames / |speed| * rates.output / rates.input)` interleaved frames.
+///
+/// A negative `speed` plays the input backwards from its end. Speed 0, which freezes
+/// playback and so has no end, is rejected; stream it with `StreamingStretcher` instead.
+pub fn process(
+    input: &[f32],
+    channels: usize,
+    rates: SampleRates,
+    speed: f64,
+    pitch: f64,
+) -> Result<Vec<f32>, BungeeError> {
+    let frames = input.len().checked_div(channels).unwrap_or(0);
+    process_range(input, channels, rates, speed, pitch, 0..frames)
+}
+
+/// Stretch and pitch-shift the input frames in `range` of a complete interleaved buffer
+///
+/// As `process`, but output starts at input frame `range.start` and ends at `range.end`,
+/// or the other way round when `speed` is negative, giving
+/// `round(range.len() / |speed| * rates.output / rates.input)` frames. Audio outside
+/// `range` still contributes to the grains that overlap its ends.
+pub fn process_range(
+    input: &[f32],
+    channels: usize,
+    rates: SampleRates,
+    speed: f64,
+    pitch: f64,
+    range: Range<usize>,
+) -> Result<Vec<f32>, BungeeError> {
+    if channels == 0 || input.len() % channels != 0 {
+        return Err(BungeeError::InvalidParam {
+            message: format!("Invalid input of {} samples for {channels} channels", input.len()),
+        });
+    }
+    let is_valid_ratio = |ratio: f64| ratio > 0.0 && ratio.is_finite();
+    if !is_valid_ratio(speed.abs()) || !is_valid_ratio(pitch) {
+        return Err(BungeeError::InvalidParam {
+            message: format!("Invalid speed {speed} or pitch {pitch}"),
+        });
+    }
+    if rates.input <= 0 || rates.output <= 0 {
+        return Err(BungeeError::InvalidParam {
+            message: format!("Invalid sample rates: input {} Hz, output {} Hz", rates.input, rates.output),
+        });
+    }
+    if range.start > range.end || range.end > input.len() / channels {
+        return Err(BungeeError::InvalidParam {
+            message: format!("Invalid range {range:?} of {} input frames", input.len() / channels),
+        });
+    }
+
+    let (start, stop) = (range.start as f64, range.end as f64);
+    let reverse = speed < 0.0;
+    let input_per_output = speed * rates.input as f64 / rates.output as f64;
+    let expected_frames = ((stop - start) / input_per_output.abs()).round() as usize;
+
+    // Size the grain buffers for the pitch, beyond the default two octaves if needed
+    let octaves = (pitch.log2().abs().ceil() as i32).max(2);
+    let mut stretcher = StretcherConfig::new()
+        .max_pitch_octaves(octaves)
+        .build(rates, channels as i32)?;
+    let mut request = Request {
+        position: if reverse { stop } else { start },
+        speed,
+        pitch,
+        reset: true,
+        preserve_formants: false,
+    };
+    stretcher.preroll(&mut request)?;
+
+    let mut grain = Vec::with_capacity(stretcher.max_input_frame_count() * channels);
+    let mut output = Vec::with_capacity(expected_frames * channels);
+
+    loop {
+        let (begin, end) = stretcher.specify_grain()?;
+        // Read the grain in place when it lies wholly within the input
+        let frames = (input.len() / channels) as i32;
+        if 0 <= begin && begin <= end && end <= frames {
+            let range = begin as usize * channels..end as usize * channels;
+            stretcher.analyse_grain(&Interleaved::new(&input[range], channels)?)?;
+        } else {
+            let grain_frames = gather_planar(input, channels, begin, end, &mut grain);
+            stretcher.analyse_grain(&Strided::new(&grain, channels, grain_frames, grain_frames, 1)?)?;
+        }
+
+        // The chunk spans the input positions of its begin and end requests
+        let chunk = stretcher.synthesise_grain()?;
+        let chunk_begin = chunk.begin_request().position;
+        let chunk_end = chunk.end_request().position;
+        if !chunk_begin.is_nan() && !chunk.is_empty() {
+            let step = if chunk_end.is_nan() {
+                input_per_output
+            } else {
+                (chunk_end - chunk_begin) / chunk.frame_count() as f64
+            };
+            for (i, frame) in chunk.frames().enumerate() {
+                let position = chunk_begin + step * i as f64;
+                if position >= start && position < stop {
+                    output.extend_from_slice(frame);
+                }
+            }
+        }
+
+        stretcher.next(&mut request)?;
+        if (reverse && request.position < start) || (!reverse && request.position >= stop) {
+            request.position = f64::NAN;
+        }
+
+        if stretcher.is_flushed() || output.len() >= expected_frames * channels {
+            break;
+        }
+    }
+
+    output.resize(expected_frames * channels, 0.0);
+    Ok(output)
+}
+
+/// Copy input frames `begin..end` into `grain` as planar audio, padding with silence
+/// outside the input. Returns the grain's frame count, which is also its channel stride.
+fn gather_planar(input: &[f32], channels: usize, begin: i32, end: i32, grain: &mut Vec<f32>) -> usize {
+    let frames = input.len() / channels;
+    let grain_frames = (end - begin).max(0) as usize;
+
+    grain.clear();
+    grain.resize(grain_frames * channels, 0.0);
+
+    let first = (begin.max(0) as usize).min(frames);
+    let last = (end.max(0) as usize).min(frames);
+    for position in first..last {
+        let frame = (position as i64 - begin as i64) as usize;
+        for channel in 0..channels {
+            grain[channel * grain_frames + frame] = input[position * channels + channel];
+        }
+    }
+
+    grain_frames
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    const RATE: i32 = 44100;
+    const RATES: SampleRates = SampleRates { input: RATE, output: RATE };
+
+    /// Interleaved stereo: a tone on each channel under an amplitude ramp, so that the
+    /// direction of playback shows in the envelope
+    fn ramped_tones(frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let t = i as f64 / RATE as f64;
+                let ramp = 0.1 + 0.9 * i as f64 / frames as f64;
+                [440.0, 660.0].map(|f| (ramp * (2.0 * PI * f * t).sin()) as f32)
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f64 {
+        (samples.iter().map(|&x| (x as f64).powi(2)).sum::<f64>() / samples.len().max(1) as f64).sqrt()
+    }
+
+    #[test]
+    fn output_length_follows_speed_and_rates() {
+        let input = ramped_tones(RATE as usize / 2);
+        for (speed, output_rate) in [(1.0, RATE), (2.0, RATE), (0.75, RATE), (1.0, 48000), (1.5, 22050)] {
+            let rates = SampleRates { input: RATE, output: output_rate };
+            let output = process(&input, 2, rates, speed, 1.0).unwrap();
+            let expected = (input.len() as f64 / 2.0 / speed * output_rate as f64 / RATE as f64).round() as usize;
+            assert_eq!(output.len(), expected * 2, "speed {speed}, output rate {output_rate}");
+        }
+    }
+
+    #[test]
+    fn unit_speed_reproduces_the_input() {
+        let input = ramped_tones(RATE as usize);
+        let output = process(&input, 2, RATES, 1.0, 1.0).unwrap();
+        assert_eq!(output.len(), input.len());
+
+        // Away from the ends, where the input is windowed against silence
+        let margin = 4096 * 2;
+        let error: Vec<f32> = output[margin..output.len() - margin]
+            .iter()
+            .zip(&input[margin..input.len() - margin])
+            .map(|(o, i)| o - i)
+            .collect();
+        let relative = rms(&error) / rms(&input[margin..input.len() - margin]);
+        assert!(relative < 3e-3, "relative error {relative}");
+    }
+
+    #[test]
+    fn range_selects_the_input_played() {
+        let input = ramped_tones(RATE as usize);
+        let output = process_range(&input, 2, RATES, 1.0, 1.0, 30000..40000).unwrap();
+        assert_eq!(output.len(), 10000 * 2);
+        let margin = 2048 * 2;
+        let reference = &input[30000 * 2 + margin..40000 * 2 - margin];
+        let error: Vec<f32> = output[margin..output.len() - margin].iter().zip(reference).map(|(o, i)| o - i).collect();
+        assert!(rms(&error) / rms(reference) < 3e-3);
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        let input = ramped_tones(1000);
+        let invalid = |result: Result<Vec<f32>, BungeeError>| matches!(result, Err(BungeeError::InvalidParam { .. }));
+        assert!(invalid(process(&input, 0, RATES, 1.0, 1.0)));
+        assert!(invalid(process(&input[1..], 2, RATES, 1.0, 1.0)));
+        assert!(invalid(process(&input, 2, RATES, f64::NAN, 1.0)));
+        assert!(invalid(process(&input, 2, RATES, 1.0, 0.0)));
+        assert!(invalid(process(&input, 2, SampleRates { input: 0, output: RATE }, 1.0, 1.0)));
+        assert!(invalid(process_range(&input, 2, RATES, 1.0, 1.0, 500..1001)));
+    }
+
+    #[test]
+    fn unit_reverse_speed_time_reverses_the_input() {
+        let frames = RATE as usize;
+        let input = ramped_tones(frames);
+        let output = process(&input, 2, RATES, -1.0, 1.0).unwrap();
+        assert_eq!(output.len(), input.len());
+
+        // Output frame i is input frame frames - 1 - i, to within the windows' overlap-add ripple
+        let reference: Vec<f32> = input.chunks_exact(2).rev().flatten().copied().collect();
+        let margin = 4096 * 2;
+        let error: Vec<f32> = output[margin..output.len() - margin]
+            .iter()
+            .zip(&reference[margin..reference.len() - margin])
+            .map(|(o, r)| o - r)
+            .collect();
+        let relative = rms(&error) / rms(&reference[margin..reference.len() - margin]);
+        assert!(relative < 3e-3, "relative error {relative}");
+    }
+
+    #[test]
+    fn slow_reverse_follows_the_reversed_envelope() {
+        let frames = RATE as usize;
+        let input = ramped_tones(frames);
+        let output = process(&input, 2, RATES, -0.5, 1.0).unwrap();
+        assert_eq!(output.len(), 2 * input.len());
+
+        // Each block of output carries the ramp at its input position, now falling
+        let block = 4096;
+        for (j, samples) in output.chunks_exact(block * 2).enumerate().skip(1).take(18) {
+            let position = frames as f64 - (j as f64 + 0.5) * block as f64 * 0.5;
+            let ramp = 0.1 + 0.9 * position / frames as f64;
+            let expected = ramp / 2f64.sqrt();
+            let actual = rms(samples);
+            assert!((actual / expected - 1.0).abs() < 0.05, "block {j}: rms {actual}, expected {expected}");
+        }
+    }
+
+    #[test]
+    fn range_is_played_from_its_end_in_reverse() {
+        let input = ramped_tones(RATE as usize);
+        let output = process_range(&input, 2, RATES, -2.0, 1.0, 8000..28000).unwrap();
+        assert_eq!(output.len(), 10000 * 2);
+        assert!(rms(&output[..2000]) > rms(&output[output.len() - 2000..]));
+    }
+
+    #[test]
+    fn zero_speed_is_rejected() {
+        let input = ramped_tones(1000);
+        assert!(matches!(process(&input, 2, RATES, 0.0, 1.0), Err(BungeeError::InvalidParam { .. })));
+    }
+}
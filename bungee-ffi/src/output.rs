@@ -0,0 +1,278 @@
+//! Safe view of the audio produced by one synthesised grain
+
+use std::marker::PhantomData;
+use std::slice::ChunksExact;
+
+use crate::{bungee_output_chunk_t, AudioBuffer, AudioBufferMut, Interleaved, Request, Sample};
+
+/// Output audio of one grain, borrowed from the stretcher or from a caller-owned buffer
+///
+/// The audio is a view of type `B`: interleaved `f32` unless a buffer of another layout or
+/// `Sample` type was passed to `Stretcher::synthesise_grain_into`. The chunk also carries the
+/// requests of the grains at its first frame and one past its last frame, from which the input
+/// position of every output frame is interpolated.
+#[derive(Debug, Clone, Copy)]
+pub struct OutputChunk<'a, T = f32, B = Interleaved<'a, T>> {
+    audio: B,
+    request: [Request; 2],
+    first_position: f64,
+    last_position: f64,
+    _sample: PhantomData<&'a T>,
+}
+
+impl<'a, T: Sample, B: AudioBuffer<T>> OutputChunk<'a, T, B> {
+    /// Index of the request at the chunk's first frame
+    pub const BEGIN: usize = 0;
+    /// Index of the request one past the chunk's last frame
+    pub const END: usize = 1;
+
+    pub(crate) fn new(audio: B, chunk: &bungee_output_chunk_t) -> Self {
+        debug_assert_eq!(audio.frame_count(), chunk.frame_count as usize);
+        Self {
+            audio,
+            request: chunk.request.map(Request::from),
+            first_position: chunk.first_position,
+            last_position: chunk.last_position,
+            _sample: PhantomData,
+        }
+    }
+
+    /// Number of frames in the chunk
+    pub fn frame_count(&self) -> usize {
+        self.audio.frame_count()
+    }
+
+    /// Number of audio channels
+    pub fn channel_count(&self) -> usize {
+        self.audio.channel_count()
+    }
+
+    /// True if the grain produced no audio
+    pub fn is_empty(&self) -> bool {
+        self.frame_count() == 0
+    }
+
+    /// The chunk's audio
+    pub fn audio(&self) -> &B {
+        &self.audio
+    }
+
+    /// Iterate over the samples of one channel
+    ///
+    /// Panics if `channel` is not less than `channel_count()`.
+    pub fn channel(&self, channel: usize) -> impl Iterator<Item = T> + '_ {
+        assert!(channel < self.channel_count(), "channel {channel} out of range");
+        (0..self.frame_count()).map(move |frame| self.audio.sample(channel, frame))
+    }
+
+    /// Copy the chunk into the first `frame_count()` frames of `output`
+    ///
+    /// Panics if `output` has a different channel count or too few frames.
+    pub fn copy_to<O: AudioBufferMut<T> + ?Sized>(&self, output: &mut O) {
+        assert_eq!(output.channel_count(), self.channel_count(), "channel count mismatch");
+        let frames = self.frame_count();
+        assert!(output.frame_count() >= frames, "{} frames < {frames}", output.frame_count());
+        for channel in 0..self.channel_count() {
+            for frame in 0..frames {
+                output.set_sample(channel, frame, self.audio.sample(channel, frame));
+            }
+        }
+    }
+
+    /// Copy the chunk into `output` as planar audio with channel `c` at `output[c * channel_stride]`
+    ///
+    /// Panics if `channel_stride` is less than `frame_count()` or `output` is too short.
+    pub fn copy_planar(&self, output: &mut [T], channel_stride: usize) {
+        let frames = self.frame_count();
+        assert!(channel_stride >= frames, "channel stride {channel_stride} < {frames} frames");
+        for channel in 0..self.channel_count() {
+            let destination = &mut output[channel * channel_stride..][..frames];
+            for (sample, value) in destination.iter_mut().zip(self.channel(channel)) {
+                *sample = value;
+            }
+        }
+    }
+
+    /// Planar copy of the chunk with a channel stride of `frame_count()`
+    pub fn to_planar(&self) -> Vec<T> {
+        let mut planar = vec![T::default(); self.frame_count() * self.channel_count()];
+        self.copy_planar(&mut planar, self.frame_count());
+        planar
+    }
+
+    /// Requests of the grains that bound the chunk, indexed by `BEGIN` and `END`
+    ///
+    /// A NaN position means there is no grain at that end, as at the start of
+    /// processing or while flushing.
+    pub fn request(&self) -> &[Request; 2] {
+        &self.request
+    }
+
+    /// Request of the grain at the chunk's first frame
+    pub fn begin_request(&self) -> &Request {
+        &self.request[Self::BEGIN]
+    }
+
+    /// Request of the grain one past the chunk's last frame
+    pub fn end_request(&self) -> &Request {
+        &self.request[Self::END]
+    }
+
+    /// Input position of the first frame, or `None` if the chunk is empty or not bounded
+    /// by two valid grains
+    pub fn first_position(&self) -> Option<f64> {
+        Some(self.first_position).filter(|position| !position.is_nan())
+    }
+
+    /// Input position of the last frame, or `None` as for `first_position`
+    pub fn last_position(&self) -> Option<f64> {
+        Some(self.last_position).filter(|position| !position.is_nan())
+    }
+
+    /// Input position of output frame `frame`, interpolated with sub-sample accuracy
+    ///
+    /// Returns `None` when the chunk's positions are unknown, see `first_position`.
+    pub fn position(&self, frame: usize) -> Option<f64> {
+        let first = self.first_position()?;
+        let last = self.last_position()?;
+        let frames = self.frame_count();
+        if frames < 2 {
+            return Some(first);
+        }
+        Some(first + (last - first) * frame as f64 / (frames - 1) as f64)
+    }
+}
+
+impl<'a, T: Sample> OutputChunk<'a, T> {
+    /// Iterate over frames, each a slice with one sample per channel
+    pub fn frames(&self) -> ChunksExact<'a, T> {
+        self.audio.as_slice().chunks_exact(self.channel_count())
+    }
+
+    /// Interleaved samples: frame `i` of channel `c` is at `[i * channel_count() + c]`
+    pub fn interleaved(&self) -> &'a [T] {
+        self.audio.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InterleavedMut, Request, SampleRates, Stretcher};
+
+    const RATES: SampleRates = SampleRates { input: 44100, output: 44100 };
+
+    /// Stretcher of two channels after `grains` grains of a tone at `speed`, with the current
+    /// grain analysed
+    fn analysed(grains: usize, speed: f64) -> (Stretcher, Request) {
+        let mut stretcher = Stretcher::new(RATES, 2).unwrap();
+        let mut request = Request {
+            position: 0.0,
+            speed,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        stretcher.preroll(&mut request).unwrap();
+        for grain in 0..=grains {
+            let (begin, end) = stretcher.specify_grain().unwrap();
+            let input: Vec<f32> = (begin..end).flat_map(|i| [(i as f32 * 0.03).sin(), (i as f32 * 0.05).cos()]).collect();
+            stretcher.analyse_grain(&crate::Interleaved::new(&input, 2).unwrap()).unwrap();
+            if grain < grains {
+                stretcher.synthesise_grain().unwrap();
+                stretcher.next(&mut request).unwrap();
+            }
+        }
+        (stretcher, request)
+    }
+
+    #[test]
+    fn requests_bound_each_chunk() {
+        let mut stretcher = Stretcher::new(RATES, 2).unwrap();
+        let mut request = Request {
+            position: 0.0,
+            speed: 0.5,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        stretcher.preroll(&mut request).unwrap();
+        let mut previous_end: Option<Request> = None;
+        for _ in 0..16 {
+            let (begin, end) = stretcher.specify_grain().unwrap();
+            let input = vec![0.1f32; (end - begin) as usize * 2];
+            stretcher.analyse_grain(&crate::Interleaved::new(&input, 2).unwrap()).unwrap();
+            let chunk = stretcher.synthesise_grain().unwrap();
+            assert_eq!(chunk.request()[super::OutputChunk::<f32>::BEGIN].position.to_bits(), chunk.begin_request().position.to_bits());
+            if let Some(previous) = previous_end {
+                // Each chunk starts where the one before ended
+                assert_eq!(previous.position.to_bits(), chunk.begin_request().position.to_bits());
+            }
+            let (begin, end) = (chunk.begin_request().position, chunk.end_request().position);
+            if !begin.is_nan() && !end.is_nan() {
+                // One synthesis hop of output covers half a hop of input at speed 0.5
+                assert_eq!(end - begin, 0.5 * chunk.frame_count() as f64);
+            }
+            previous_end = Some(*chunk.end_request());
+            stretcher.next(&mut request).unwrap();
+        }
+    }
+
+    #[test]
+    fn positions_interpolate_between_requests() {
+        let mut stretcher = Stretcher::new(RATES, 2).unwrap();
+        let mut request = Request {
+            position: 0.0,
+            speed: 0.75,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        stretcher.preroll(&mut request).unwrap();
+        let (mut known, mut unknown) = (0, 0);
+        for _ in 0..16 {
+            let (begin, end) = stretcher.specify_grain().unwrap();
+            let input = vec![0.1f32; (end - begin) as usize * 2];
+            stretcher.analyse_grain(&crate::Interleaved::new(&input, 2).unwrap()).unwrap();
+            let chunk = stretcher.synthesise_grain().unwrap();
+            let (begin, end) = (chunk.begin_request().position, chunk.end_request().position);
+            if chunk.frame_count() == 0 || begin.is_nan() || end.is_nan() {
+                assert_eq!(chunk.first_position(), None);
+                assert_eq!(chunk.last_position(), None);
+                assert_eq!(chunk.position(0), None);
+                unknown += 1;
+            } else {
+                let frames = chunk.frame_count();
+                let step = (end - begin) / frames as f64;
+                assert_eq!(chunk.first_position(), Some(begin));
+                assert!((chunk.last_position().unwrap() - (end - step)).abs() < 1e-9);
+                assert_eq!(chunk.position(0), chunk.first_position());
+                assert_eq!(chunk.position(frames - 1), chunk.last_position());
+                let middle = chunk.position(frames / 2).unwrap();
+                assert!((middle - (begin + step * (frames / 2) as f64)).abs() < 1e-9);
+                known += 1;
+            }
+            stretcher.next(&mut request).unwrap();
+        }
+        assert!(known > 0 && unknown > 0, "{known} chunks with positions, {unknown} without");
+    }
+
+    #[test]
+    fn views_agree_on_the_audio() {
+        let (mut stretcher, _) = analysed(8, 1.0);
+        let chunk = stretcher.synthesise_grain().unwrap();
+        let frames = chunk.frame_count();
+        assert!(frames > 0);
+        assert_eq!(chunk.channel_count(), 2);
+        assert_eq!(chunk.interleaved().len(), frames * 2);
+
+        let planar = chunk.to_planar();
+        for (i, frame) in chunk.frames().enumerate() {
+            assert_eq!(frame, [planar[i], planar[frames + i]]);
+        }
+        assert!(chunk.channel(1).eq(planar[frames..].iter().copied()));
+
+        let mut copy = vec![0.0; frames * 2];
+        chunk.copy_to(&mut InterleavedMut::new(&mut copy, 2).unwrap());
+        assert_eq!(copy, chunk.interleaved());
+    }
+}
@@ -0,0 +1,353 @@
+//! Push-mode processing for sources that deliver input in fixed-size blocks
+//!
+//! The stretcher's native API is pull-based: each grain dictates the input range it needs.
+//! `PushStretcher` buffers pushed audio until the current grain's range is complete, the
+//! same approach as `Push::InputBuffer` in the C++ command line tool.
+
+use crate::automation::{Envelope, Parameter};
+use crate::{BungeeError, ChannelCoupling, Interpolation, Latency, OutputChunk, Request, ResampleMode, SampleRates, Stretcher, Strided};
+
+/// Time-stretcher that accepts input in blocks of any size
+///
+/// Each call to `push` analyses and synthesises every grain whose input has become
+/// available and passes the resulting output chunks, zero or more, to a callback.
+#[derive(Debug)]
+pub struct PushStretcher {
+    stretcher: Stretcher,
+    request: Request,
+    buffer: InputBuffer,
+}
+
+impl PushStretcher {
+    /// Create a stretcher whose first pushed frame is input position 0
+    ///
+    /// `request` supplies the initial speed and pitch; its position is ignored.
+    pub fn new(rates: SampleRates, channels: i32, request: Request) -> Result<Self, BungeeError> {
+        let mut stretcher = Stretcher::new(rates, channels)?;
+        let mut request = Request {
+            position: 0.0,
+            reset: true,
+            ..request
+        };
+        stretcher.preroll(&mut request)?;
+
+        let mut buffer = InputBuffer::new(stretcher.max_input_frame_count(), stretcher.channel_count());
+        let (begin, end) = stretcher.specify_grain()?;
+        buffer.grain(begin, end);
+
+        Ok(Self {
+            stretcher,
+            request,
+            buffer,
+        })
+    }
+
+    /// Request for the next grain
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// Mutable request, used to change speed or pitch from the next grain onwards
+    pub fn request_mut(&mut self) -> &mut Request {
+        &mut self.request
+    }
+
+    /// Automate speed or pitch, see `Stretcher::set_automation`
+    ///
+    /// As with `push`, speed breakpoints that are not positive, NaN included, are rejected with
+    /// `BungeeError::InvalidParam`.
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    pub fn set_automation(&mut self, parameter: Parameter, envelope: &Envelope) -> Result<(), BungeeError> {
+        if parameter == Parameter::Speed {
+            if let Some(b) = envelope.breakpoints.iter().find(|b| !(b.value > 0.0)) {
+                return Err(BungeeError::InvalidParam {
+                    message: format!("Invalid speed breakpoint for pushed input: {} at time {}", b.value, b.time),
+                });
+            }
+        }
+        self.stretcher.set_automation(parameter, envelope)
+    }
+
+    /// Select where the engine resamples, see `Stretcher::set_resample_mode`
+    pub fn set_resample_mode(&mut self, mode: ResampleMode) -> Result<(), BungeeError> {
+        self.stretcher.set_resample_mode(mode)
+    }
+
+    /// Select the resampling interpolation kernel, see `Stretcher::set_interpolation`
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) -> Result<(), BungeeError> {
+        self.stretcher.set_interpolation(interpolation)
+    }
+
+    /// Set the transient detection sensitivity, see `Stretcher::set_transient_sensitivity`
+    pub fn set_transient_sensitivity(&mut self, sensitivity: f32) -> Result<(), BungeeError> {
+        self.stretcher.set_transient_sensitivity(sensitivity)
+    }
+
+    /// Select channel coupling, see `Stretcher::set_channel_coupling`
+    pub fn set_channel_coupling(&mut self, coupling: ChannelCoupling) -> Result<(), BungeeError> {
+        self.stretcher.set_channel_coupling(coupling)
+    }
+
+    /// Number of input frames still needed before the current grain can be processed
+    pub fn input_frame_count_required(&self) -> i64 {
+        self.buffer.required()
+    }
+
+    /// Latency at the current pitch, see `Stretcher::latency`
+    pub fn latency(&self) -> Latency {
+        self.stretcher.latency()
+    }
+
+    /// Deliver interleaved input audio, passing each output chunk produced to `emit`
+    ///
+    /// Pushed input can only be played forwards: a speed that is not positive would never
+    /// consume it, so is rejected with `BungeeError::InvalidParam`, as is a NaN speed.
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    pub fn push<F>(&mut self, input: &[f32], mut emit: F) -> Result<(), BungeeError>
+    where
+        F: FnMut(OutputChunk<'_>),
+    {
+        let channels = self.stretcher.channel_count();
+        if input.len() % channels != 0 {
+            return Err(BungeeError::InvalidParam {
+                message: format!("Invalid input of {} samples: not a whole number of {channels}-channel frames", input.len()),
+            });
+        }
+
+        let mut remaining = input;
+        loop {
+            while self.buffer.required() <= 0 {
+                if !(self.request.speed > 0.0) {
+                    return Err(BungeeError::InvalidParam {
+                        message: format!("Invalid speed for pushed input: {}", self.request.speed),
+                    });
+                }
+                self.process_grain(&mut emit)?;
+            }
+            if remaining.is_empty() {
+                return Ok(());
+            }
+
+            let frames = (self.buffer.required() as usize).min(remaining.len() / channels);
+            let (block, rest) = remaining.split_at(frames * channels);
+            self.buffer.deliver(block);
+            remaining = rest;
+        }
+    }
+
+    /// Signal the end of the input and emit the output of all remaining grains
+    pub fn flush<F>(&mut self, mut emit: F) -> Result<(), BungeeError>
+    where
+        F: FnMut(OutputChunk<'_>),
+    {
+        while !self.stretcher.is_flushed() {
+            self.buffer.deliver_silence();
+            self.request.position = f64::NAN;
+            self.process_grain(&mut emit)?;
+        }
+        Ok(())
+    }
+
+    fn process_grain<F>(&mut self, emit: &mut F) -> Result<(), BungeeError>
+    where
+        F: FnMut(OutputChunk<'_>),
+    {
+        self.stretcher.analyse_grain(&self.buffer.audio()?)?;
+        emit(self.stretcher.synthesise_grain()?);
+
+        self.request.reset = false;
+        self.stretcher.next(&mut self.request)?;
+        let (begin, end) = self.stretcher.specify_grain()?;
+        self.buffer.grain(begin, end);
+        Ok(())
+    }
+}
+
+/// Planar buffer holding the pushed input frames of the current grain
+///
+/// Port of `Push::InputBuffer`: `data()` always starts at the grain's first frame and
+/// frames not yet pushed, or skipped over by a grain that moved backwards, are silent.
+#[derive(Debug)]
+struct InputBuffer {
+    data: Vec<f32>,
+    capacity: usize,
+    channels: usize,
+    // Input position of data[0]
+    first: i64,
+    // Input position of the next frame to be pushed
+    position: i64,
+    // Input range of the current grain
+    end: i64,
+}
+
+impl InputBuffer {
+    fn new(capacity: usize, channels: usize) -> Self {
+        Self {
+            data: vec![0.0; capacity * channels],
+            capacity,
+            channels,
+            first: 0,
+            position: 0,
+            end: 0,
+        }
+    }
+
+    /// Start a new grain, keeping already-pushed frames that fall within it
+    fn grain(&mut self, begin: i32, end: i32) {
+        let (begin, end) = (begin as i64, end as i64);
+        let stored_end = self.position.min(self.first + self.capacity as i64);
+        let keep_begin = begin.max(self.first);
+        let keep_end = stored_end.min(begin + self.capacity as i64).max(keep_begin);
+
+        let from = (keep_begin - self.first) as usize;
+        let to = ((keep_begin - begin) as usize).min(self.capacity);
+        let count = (keep_end - keep_begin) as usize;
+        for channel in self.data.chunks_exact_mut(self.capacity) {
+            channel.copy_within(from.min(self.capacity)..(from + count).min(self.capacity), to);
+            channel[..to].fill(0.0);
+            channel[to + count..].fill(0.0);
+        }
+
+        self.first = begin;
+        self.end = end;
+    }
+
+    /// Append interleaved frames at the current input position
+    fn deliver(&mut self, input: &[f32]) {
+        for frame in input.chunks_exact(self.channels) {
+            let offset = self.position - self.first;
+            if offset >= 0 && (offset as usize) < self.capacity {
+                for (channel, &sample) in frame.iter().enumerate() {
+                    self.data[channel * self.capacity + offset as usize] = sample;
+                }
+            }
+            self.position += 1;
+        }
+    }
+
+    /// Advance the input position to the end of the current grain; the buffer is already silent there
+    fn deliver_silence(&mut self) {
+        self.position = self.position.max(self.end);
+    }
+
+    fn required(&self) -> i64 {
+        self.end - self.position
+    }
+
+    /// The buffered input, one channel of `capacity` frames after another
+    fn audio(&self) -> Result<Strided<'_>, BungeeError> {
+        Strided::planar(&self.data, self.channels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: i32 = 44100;
+
+    fn stretcher(speed: f64) -> PushStretcher {
+        let rates = SampleRates { input: RATE, output: RATE };
+        let request = Request {
+            position: 0.0,
+            speed,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        PushStretcher::new(rates, 2, request).unwrap()
+    }
+
+    /// Half a second of interleaved stereo tones
+    fn input() -> Vec<f32> {
+        (0..RATE / 2).flat_map(|i| [(i as f32 * 0.06).sin(), 0.5 * (i as f32 * 0.02).sin()]).collect()
+    }
+
+    /// Push `input` in blocks of `block` frames, then flush, returning all output
+    fn process(stretcher: &mut PushStretcher, input: &[f32], block: usize) -> Vec<f32> {
+        let mut output = Vec::new();
+        for block in input.chunks(block * 2) {
+            stretcher.push(block, |chunk| output.extend_from_slice(chunk.interleaved())).unwrap();
+        }
+        stretcher.flush(|chunk| output.extend_from_slice(chunk.interleaved())).unwrap();
+        output
+    }
+
+    #[test]
+    fn output_does_not_depend_on_block_size() {
+        let input = input();
+        let whole = process(&mut stretcher(0.75), &input, input.len());
+        assert!(whole.len() / 2 >= input.len() * 2 / 3, "{} frames", whole.len() / 2);
+        for block in [1, 64, 1000, 4096] {
+            assert_eq!(process(&mut stretcher(0.75), &input, block), whole, "block of {block} frames");
+        }
+    }
+
+    #[test]
+    fn push_waits_for_the_input_of_each_grain() {
+        let mut stretcher = stretcher(1.0);
+        // Grains of the preroll lie before position 0 and need no input
+        let mut chunks = 0;
+        stretcher.push(&[], |_| chunks += 1).unwrap();
+        let required = stretcher.input_frame_count_required();
+        assert!(required > 0);
+
+        chunks = 0;
+        let short = vec![0.0; (required as usize - 1) * 2];
+        stretcher.push(&short, |_| chunks += 1).unwrap();
+        assert_eq!(chunks, 0);
+        assert_eq!(stretcher.input_frame_count_required(), 1);
+
+        stretcher.push(&[0.0, 0.0], |_| chunks += 1).unwrap();
+        assert!(chunks > 0);
+        assert!(stretcher.input_frame_count_required() > 0);
+    }
+
+    #[test]
+    fn flush_emits_the_tail() {
+        let mut stretcher = stretcher(1.0);
+        let input = input();
+        let mut pushed = 0;
+        stretcher.push(&input, |chunk| pushed += chunk.frame_count()).unwrap();
+        let mut flushed = 0;
+        stretcher.flush(|chunk| flushed += chunk.frame_count()).unwrap();
+        assert!(flushed > 0);
+        // At unit speed, output covers the input plus at most one grain of tail
+        let total = pushed + flushed;
+        assert!(total >= input.len() / 2 && total < input.len() / 2 + stretcher.stretcher.max_input_frame_count(), "{total} frames");
+    }
+
+    #[test]
+    fn speed_must_be_positive() {
+        let input = input();
+        for speed in [0.0, -1.0, f64::NAN] {
+            let mut stretcher = stretcher(speed);
+            assert!(matches!(stretcher.push(&input, |_| ()), Err(BungeeError::InvalidParam { .. })), "speed {speed}");
+        }
+
+        let mut stretcher = stretcher(1.0);
+        stretcher.push(&input[..20000], |_| ()).unwrap();
+        for speed in [0.0, f64::NAN] {
+            stretcher.request_mut().speed = speed;
+            assert!(matches!(stretcher.push(&input[20000..], |_| ()), Err(BungeeError::InvalidParam { .. })), "speed {speed}");
+        }
+    }
+
+    #[test]
+    fn speed_automation_must_be_positive() {
+        use crate::automation::{Curve, TimeBase};
+
+        let mut stretcher = stretcher(1.0);
+        let envelope = Envelope::new(TimeBase::Input).point(0.0, 1.0, Curve::Linear).point(1000.0, 0.0, Curve::Hold);
+        assert!(matches!(stretcher.set_automation(Parameter::Speed, &envelope), Err(BungeeError::InvalidParam { .. })));
+        let envelope = Envelope::new(TimeBase::Input).point(0.0, 1.0, Curve::Linear).point(1000.0, f64::NAN, Curve::Hold);
+        assert!(matches!(stretcher.set_automation(Parameter::Speed, &envelope), Err(BungeeError::InvalidParam { .. })));
+        let envelope = Envelope::new(TimeBase::Input).point(0.0, 1.0, Curve::Linear).point(1000.0, 0.5, Curve::Hold);
+        stretcher.set_automation(Parameter::Speed, &envelope).unwrap();
+    }
+
+    #[test]
+    fn odd_sample_count_is_rejected() {
+        assert!(matches!(stretcher(1.0).push(&[0.0; 3], |_| ()), Err(BungeeError::InvalidParam { .. })));
+    }
+}
@@ -0,0 +1,129 @@
+//! Choice of where and how the engine resamples for pitch shift and sample-rate conversion
+
+use crate::*;
+
+/// Where resampling takes place
+///
+/// Input resampling changes the analysed audio before the transform and output resampling
+/// changes the synthesised audio; the two trade CPU cost against how timing follows pitch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResampleMode {
+    /// Output resampling, only when needed
+    #[default]
+    AutoOut,
+    /// Input resampling, only when needed
+    AutoIn,
+    /// Input resampling when downsampling, output resampling when upsampling
+    AutoInOut,
+    /// Output resampling, always active
+    ForceOut,
+    /// Input resampling, always active
+    ForceIn,
+}
+
+impl From<ResampleMode> for bungee_resample_mode_t {
+    fn from(mode: ResampleMode) -> Self {
+        match mode {
+            ResampleMode::AutoOut => bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_OUT,
+            ResampleMode::AutoIn => bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN,
+            ResampleMode::AutoInOut => bungee_resample_mode_BUNGEE_RESAMPLE_AUTO_IN_OUT,
+            ResampleMode::ForceOut => bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_OUT,
+            ResampleMode::ForceIn => bungee_resample_mode_BUNGEE_RESAMPLE_FORCE_IN,
+        }
+    }
+}
+
+/// Interpolation kernel used for resampling
+///
+/// Higher-order kernels alias less when pitch shifting, at the cost of CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    Nearest,
+    #[default]
+    Bilinear,
+    /// Catmull-Rom spline through four samples
+    CubicHermite,
+    /// Blackman-windowed sinc of 4, 8, 16 or 32 taps, band-limited when downsampling
+    WindowedSinc { taps: i32 },
+}
+
+impl Interpolation {
+    pub(crate) fn to_c(self) -> (bungee_interpolation_t, i32) {
+        match self {
+            Interpolation::Nearest => (bungee_interpolation_BUNGEE_INTERPOLATION_NEAREST, 0),
+            Interpolation::Bilinear => (bungee_interpolation_BUNGEE_INTERPOLATION_BILINEAR, 0),
+            Interpolation::CubicHermite => (bungee_interpolation_BUNGEE_INTERPOLATION_CUBIC_HERMITE, 0),
+            Interpolation::WindowedSinc { taps } => (bungee_interpolation_BUNGEE_INTERPOLATION_WINDOWED_SINC, taps),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::tests::{request, stretch};
+    use crate::{Request, SampleRates, StretcherConfig};
+
+    const RATE: i32 = 44100;
+
+    /// Stretch a mono 441 Hz tone at unit speed and return the fraction of the output's
+    /// energy, away from the ends, that lies at `frequency`
+    fn energy_at(config: &StretcherConfig, output_rate: i32, pitch: f64, frequency: f64) -> f64 {
+        let rates = SampleRates { input: RATE, output: output_rate };
+        let mut stretcher = config.build(rates, 1).unwrap();
+        let input: Vec<f32> = (0..RATE).map(|i| (2.0 * PI * 441.0 * i as f64 / RATE as f64).sin() as f32).collect();
+        let output = stretch(&mut stretcher, &input, Request { pitch, ..request(0.0, 1.0) });
+
+        // Hann-windowed DFT bin of the middle half against the total windowed energy
+        let middle = &output[output.len() / 4..output.len() * 3 / 4];
+        let n = middle.len() as f64;
+        let w = 2.0 * PI * frequency / output_rate as f64;
+        let (mut re, mut im, mut total) = (0.0, 0.0, 0.0);
+        for (i, &x) in middle.iter().enumerate() {
+            let x = x as f64 * (0.5 - 0.5 * (2.0 * PI * i as f64 / n).cos());
+            re += x * (w * i as f64).cos();
+            im += x * (w * i as f64).sin();
+            total += x * x;
+        }
+        // All of a windowed sinusoid's energy E at the bin gives |X|^2 = E * n / 3
+        (re * re + im * im) / (total * n / 3.0)
+    }
+
+    #[test]
+    fn every_mode_shifts_pitch() {
+        for mode in [ResampleMode::AutoOut, ResampleMode::AutoIn, ResampleMode::AutoInOut, ResampleMode::ForceOut, ResampleMode::ForceIn] {
+            let config = StretcherConfig::new().resample_mode(mode);
+            for (rate, pitch) in [(RATE, 0.75), (RATE, 1.0), (RATE, 1.5), (48000, 1.25)] {
+                // Input resampling that upsamples leaves some images, as in the C++ engine
+                let energy = energy_at(&config, rate, pitch, 441.0 * pitch);
+                assert!(energy > 0.95, "{mode:?} at pitch {pitch}, {rate} Hz: {energy} of the energy at the shifted tone");
+            }
+        }
+    }
+
+    #[test]
+    fn sinc_taps_must_be_supported() {
+        let rates = SampleRates { input: RATE, output: RATE };
+        for taps in [4, 8, 16, 32] {
+            let config = StretcherConfig::new().interpolation(Interpolation::WindowedSinc { taps });
+            assert!(config.build(rates, 1).is_ok(), "{taps} taps");
+        }
+        for taps in [0, 2, 5, 64] {
+            let config = StretcherConfig::new().interpolation(Interpolation::WindowedSinc { taps });
+            assert!(matches!(config.build(rates, 1), Err(BungeeError::InvalidParam { .. })), "{taps} taps");
+        }
+    }
+
+    #[test]
+    fn every_kernel_shifts_pitch() {
+        for interpolation in [Interpolation::Nearest, Interpolation::Bilinear, Interpolation::CubicHermite, Interpolation::WindowedSinc { taps: 16 }] {
+            let config = StretcherConfig::new().interpolation(interpolation);
+            for pitch in [0.8, 1.25] {
+                let energy = energy_at(&config, RATE, pitch, 441.0 * pitch);
+                assert!(energy > 0.99, "{interpolation:?} at pitch {pitch}: {energy} of the energy at the shifted tone");
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Sample formats that the stretcher reads and writes without a separate conversion pass
+
+use crate::*;
+
+/// 24-bit signed sample held in the low bits of an `i32`, sign-extended
+///
+/// This is how most decoders, `hound` among them, deliver 24-bit PCM.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct I24(i32);
+
+impl I24 {
+    pub const MIN: I24 = I24(-(1 << 23));
+    pub const MAX: I24 = I24((1 << 23) - 1);
+
+    /// Wrap `value`, or return `None` if it does not fit in 24 bits
+    pub fn new(value: i32) -> Option<Self> {
+        (Self::MIN.0..=Self::MAX.0).contains(&value).then_some(Self(value))
+    }
+
+    /// The sample as an `i32`
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<i16> for I24 {
+    fn from(value: i16) -> Self {
+        Self((value as i32) << 8)
+    }
+}
+
+impl From<I24> for i32 {
+    fn from(value: I24) -> Self {
+        value.0
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+    impl Sealed for i16 {}
+    impl Sealed for super::I24 {}
+    impl Sealed for i32 {}
+}
+
+/// Sample type accepted by `Stretcher::analyse_grain` and `Stretcher::synthesise_grain_into`
+///
+/// The C layer converts each sample to and from the engine's `f32` as it is read or written.
+/// Integer formats map full scale to 1.0; output beyond full scale is clamped.
+pub trait Sample: Copy + Default + sealed::Sealed {
+    /// Format tag passed to the C layer
+    const FORMAT: bungee_sample_format_t;
+}
+
+impl Sample for f32 {
+    const FORMAT: bungee_sample_format_t = bungee_sample_format_BUNGEE_SAMPLE_F32;
+}
+
+impl Sample for f64 {
+    const FORMAT: bungee_sample_format_t = bungee_sample_format_BUNGEE_SAMPLE_F64;
+}
+
+impl Sample for i16 {
+    const FORMAT: bungee_sample_format_t = bungee_sample_format_BUNGEE_SAMPLE_I16;
+}
+
+impl Sample for I24 {
+    const FORMAT: bungee_sample_format_t = bungee_sample_format_BUNGEE_SAMPLE_I24;
+}
+
+impl Sample for i32 {
+    const FORMAT: bungee_sample_format_t = bungee_sample_format_BUNGEE_SAMPLE_I32;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    const RATE: i32 = 44100;
+
+    /// Stretch `input`, mono, by 1.5 and return the output
+    fn stretch<T: Sample>(input: &[T]) -> Vec<T> {
+        let mut stretcher = Stretcher::new(SampleRates { input: RATE, output: RATE }, 1).unwrap();
+        crate::tests::stretch(&mut stretcher, input, crate::tests::request(0.0, 1.5))
+    }
+
+    /// Check that stretching `input` in its own format matches stretching it as `f32`, with
+    /// `scale` the format's full-scale value
+    fn check_format<T: Sample>(input: &[T], to_f64: impl Fn(T) -> f64, scale: f64) {
+        let reference = stretch(&input.iter().map(|&x| (to_f64(x) / scale) as f32).collect::<Vec<_>>());
+        assert!(reference.iter().any(|x| x.abs() > 0.1));
+        let output = stretch(input);
+        assert_eq!(output.len(), reference.len());
+        for (&x, &r) in output.iter().zip(&reference) {
+            // Rounding to the format, plus float rounding of the reference
+            let error = (to_f64(x) - r as f64 * scale).abs();
+            assert!(error <= 0.5 + scale * 1e-7, "{} against {}", to_f64(x), r as f64 * scale);
+        }
+    }
+
+    fn tone(i: i32) -> f64 {
+        0.25 * (2.0 * PI * 440.0 * i as f64 / RATE as f64).sin()
+    }
+
+    #[test]
+    fn integer_and_double_formats_match_float() {
+        let frames = 0..20000;
+        let f64: Vec<f64> = frames.clone().map(|i| tone(i) as f32 as f64).collect();
+        check_format(&f64, |x| x, 1.0);
+        let i16: Vec<i16> = frames.clone().map(|i| (tone(i) * 32768.0).round() as i16).collect();
+        check_format(&i16, |x| x as f64, 32768.0);
+        let i24: Vec<I24> = frames.clone().map(|i| I24::new((tone(i) * 8388608.0).round() as i32).unwrap()).collect();
+        check_format(&i24, |x| x.get() as f64, 8388608.0);
+        let i32: Vec<i32> = frames.map(|i| (tone(i) * 2147483648.0).round() as i32).collect();
+        check_format(&i32, |x| x as f64, 2147483648.0);
+    }
+
+    #[test]
+    fn i24_range() {
+        assert_eq!(I24::new(-(1 << 23)), Some(I24::MIN));
+        assert_eq!(I24::new(1 << 23), None);
+        assert_eq!(I24::from(i16::MIN).get(), I24::MIN.get());
+    }
+}
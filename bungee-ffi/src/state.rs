@@ -0,0 +1,144 @@
+//! Call-order tracking for `Stretcher`
+
+use std::fmt;
+
+use crate::BungeeError;
+
+/// Position of a `Stretcher` in its per-grain call sequence
+///
+/// `preroll` may be called in any state to restart, and `seek` in place of `next` or
+/// `preroll`; otherwise each grain must go through `specify_grain`, `analyse_grain`,
+/// `synthesise_grain` and `next` in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// Created but not yet prerolled
+    Created,
+    /// Prerolled, advanced by `next` or moved by `seek`, ready for `specify_grain`
+    Prerolled,
+    /// Grain specified, ready for `analyse_grain`
+    Specified,
+    /// Grain analysed, ready for `synthesise_grain`
+    Analysed,
+    /// Grain synthesised, ready for `next`
+    Synthesised,
+}
+
+impl State {
+    /// The call that is valid in this state, besides `preroll`
+    pub fn expected_call(self) -> &'static str {
+        match self {
+            State::Created => "preroll",
+            State::Prerolled => "specify_grain",
+            State::Specified => "analyse_grain",
+            State::Analysed => "synthesise_grain",
+            State::Synthesised => "next",
+        }
+    }
+
+    /// Check that `call`, which requires state `required`, may be made in this state
+    pub(crate) fn check(self, call: &'static str, required: State) -> Result<(), BungeeError> {
+        if self == required {
+            Ok(())
+        } else {
+            Err(self.error(call))
+        }
+    }
+
+    /// Check that `call`, which may not interrupt a grain between `specify_grain` and
+    /// `synthesise_grain`, may be made in this state
+    pub(crate) fn check_between_grains(self, call: &'static str) -> Result<(), BungeeError> {
+        if matches!(self, State::Specified | State::Analysed) {
+            Err(self.error(call))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn error(self, call: &'static str) -> BungeeError {
+        BungeeError::InvalidState {
+            state: Some(self),
+            message: format!("{call} called in state {self}, expected {}", self.expected_call()),
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interleaved, Request, SampleRates, Stretcher};
+
+    const STATES: [State; 5] = [State::Created, State::Prerolled, State::Specified, State::Analysed, State::Synthesised];
+
+    fn request() -> Request {
+        Request {
+            position: 0.0,
+            speed: 1.0,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        }
+    }
+
+    #[test]
+    fn check_accepts_only_the_required_state() {
+        for state in STATES {
+            for required in STATES {
+                let result = state.check("call", required);
+                if state == required {
+                    assert!(result.is_ok());
+                } else {
+                    let error = result.unwrap_err();
+                    assert!(matches!(error, BungeeError::InvalidState { state: Some(s), .. } if s == state));
+                    let message = error.to_string();
+                    assert!(message.contains("call") && message.contains(state.expected_call()), "{message}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_between_grains_rejects_a_grain_in_progress() {
+        for state in STATES {
+            let in_grain = matches!(state, State::Specified | State::Analysed);
+            assert_eq!(state.check_between_grains("seek").is_err(), in_grain, "{state}");
+        }
+    }
+
+    #[test]
+    fn stretcher_rejects_out_of_order_calls() {
+        let mut stretcher = Stretcher::new(SampleRates { input: 44100, output: 44100 }, 1).unwrap();
+        let rejected = |result: Result<(), BungeeError>, state: State| {
+            matches!(result, Err(BungeeError::InvalidState { state: Some(s), .. }) if s == state)
+        };
+        assert!(rejected(stretcher.specify_grain().map(drop), State::Created));
+        assert!(rejected(stretcher.next(&mut request()), State::Created));
+
+        let mut request = request();
+        stretcher.preroll(&mut request).unwrap();
+        assert!(rejected(stretcher.analyse_grain(&Interleaved::new(&[0.0f32; 0], 1).unwrap()), State::Prerolled));
+        assert!(rejected(stretcher.synthesise_grain().map(drop), State::Prerolled));
+
+        let (begin, end) = stretcher.specify_grain().unwrap();
+        assert!(rejected(stretcher.next(&mut request), State::Specified));
+        assert!(rejected(stretcher.seek(&mut request, 0), State::Specified));
+        let input = vec![0.0f32; (end - begin) as usize];
+        stretcher.analyse_grain(&Interleaved::new(&input, 1).unwrap()).unwrap();
+        assert!(rejected(stretcher.specify_grain().map(drop), State::Analysed));
+        assert!(rejected(stretcher.seek(&mut request, 0), State::Analysed));
+        stretcher.synthesise_grain().unwrap();
+        assert!(rejected(stretcher.synthesise_grain().map(drop), State::Synthesised));
+
+        // A rejected call leaves the state unchanged, and preroll restarts from any state
+        assert_eq!(stretcher.state(), State::Synthesised);
+        stretcher.next(&mut request).unwrap();
+        stretcher.specify_grain().unwrap();
+        stretcher.preroll(&mut request).unwrap();
+        assert_eq!(stretcher.state(), State::Prerolled);
+    }
+}
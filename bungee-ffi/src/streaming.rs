@@ -0,0 +1,321 @@
+//! Real-time streaming with fixed-size output blocks
+//!
+//! Audio callbacks ask for a fixed number of frames, whereas each grain produces a
+//! variable-length chunk. `StreamingStretcher` bridges the two with an output FIFO that is
+//! allocated once at construction, so `fill` does not allocate.
+
+use std::collections::VecDeque;
+
+use crate::automation::{Envelope, Parameter};
+use crate::{BungeeError, ChannelCoupling, Interpolation, Latency, Request, ResampleMode, SampleRates, Stretcher, Strided};
+
+/// Source of input audio for a `StreamingStretcher`
+pub trait InputSource {
+    /// Write input frames `begin..end` into `data` as planar audio
+    ///
+    /// Channel `c` starts at `data[c * channel_stride]`. `begin` may be negative and `end`
+    /// may be beyond the end of the source; such frames must be written as silence.
+    fn read(&mut self, begin: i32, end: i32, data: &mut [f32], channel_stride: usize);
+
+    /// Length of the source in frames, or `None` if the stream has no end
+    ///
+    /// Once playback passes the end, or the start when the speed is negative, the stretcher
+    /// is flushed and `fill` reports fewer frames than requested. At zero speed playback
+    /// holds its position and never ends.
+    fn frame_count(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Time-stretcher that fills caller buffers of any size from an `InputSource`
+#[derive(Debug)]
+pub struct StreamingStretcher<S: InputSource> {
+    stretcher: Stretcher,
+    source: S,
+    request: Request,
+    fifo: VecDeque<f32>,
+    grain: Vec<f32>,
+}
+
+impl<S: InputSource> StreamingStretcher<S> {
+    /// Create a stretcher whose output starts at `request.position` of `source`
+    pub fn new(rates: SampleRates, channels: i32, source: S, request: Request) -> Result<Self, BungeeError> {
+        let mut stretcher = Stretcher::new(rates, channels)?;
+        let mut request = request;
+        stretcher.preroll(&mut request)?;
+
+        let channels = stretcher.channel_count();
+        let fifo = VecDeque::with_capacity(stretcher.max_output_frame_count() * channels);
+        let grain = Vec::with_capacity(stretcher.max_input_frame_count() * channels);
+        Ok(Self {
+            stretcher,
+            source,
+            request,
+            fifo,
+            grain,
+        })
+    }
+
+    /// Request for the next grain
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// Mutable request, used to change speed or pitch from the next grain onwards
+    pub fn request_mut(&mut self) -> &mut Request {
+        &mut self.request
+    }
+
+    /// Automate speed or pitch, see `Stretcher::set_automation`
+    ///
+    /// Playback ends at the end of the source while the speed is positive and at its start
+    /// while the speed is negative.
+    pub fn set_automation(&mut self, parameter: Parameter, envelope: &Envelope) -> Result<(), BungeeError> {
+        self.stretcher.set_automation(parameter, envelope)
+    }
+
+    /// Select where the engine resamples, see `Stretcher::set_resample_mode`
+    pub fn set_resample_mode(&mut self, mode: ResampleMode) -> Result<(), BungeeError> {
+        self.stretcher.set_resample_mode(mode)
+    }
+
+    /// Select the resampling interpolation kernel, see `Stretcher::set_interpolation`
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) -> Result<(), BungeeError> {
+        self.stretcher.set_interpolation(interpolation)
+    }
+
+    /// Set the transient detection sensitivity, see `Stretcher::set_transient_sensitivity`
+    pub fn set_transient_sensitivity(&mut self, sensitivity: f32) -> Result<(), BungeeError> {
+        self.stretcher.set_transient_sensitivity(sensitivity)
+    }
+
+    /// Select channel coupling, see `Stretcher::set_channel_coupling`
+    pub fn set_channel_coupling(&mut self, coupling: ChannelCoupling) -> Result<(), BungeeError> {
+        self.stretcher.set_channel_coupling(coupling)
+    }
+
+    /// Continue playback from `position` of the source, crossfading from the current position
+    /// over `crossfade_frames` output frames, see `Stretcher::seek`
+    ///
+    /// Output already buffered for `fill` is returned first.
+    pub fn seek(&mut self, position: f64, crossfade_frames: usize) -> Result<(), BungeeError> {
+        let mut request = Request {
+            position,
+            reset: true,
+            ..self.request
+        };
+        self.stretcher.seek(&mut request, crossfade_frames)?;
+        self.request = request;
+        Ok(())
+    }
+
+    /// Latency at the current pitch, see `Stretcher::latency`
+    pub fn latency(&self) -> Latency {
+        self.stretcher.latency()
+    }
+
+    /// The input source
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Mutable access to the input source
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Fill `output` with interleaved audio and return the number of frames written
+    ///
+    /// All of `output` is written unless the source has ended and the stretcher is
+    /// flushed, in which case the remaining frames are set to silence.
+    pub fn fill(&mut self, output: &mut [f32]) -> Result<usize, BungeeError> {
+        let channels = self.stretcher.channel_count();
+        if output.len() % channels != 0 {
+            return Err(BungeeError::InvalidParam {
+                message: format!("Invalid output of {} samples: not a whole number of {channels}-channel frames", output.len()),
+            });
+        }
+
+        let mut written = self.drain(output);
+        while written < output.len() && !self.is_finished() {
+            self.process_grain()?;
+            written += self.drain(&mut output[written..]);
+        }
+
+        output[written..].fill(0.0);
+        Ok(written / channels)
+    }
+
+    /// True once the source has ended and all buffered output has been returned
+    pub fn is_finished(&self) -> bool {
+        self.fifo.is_empty() && self.request.position.is_nan() && self.stretcher.is_flushed()
+    }
+
+    fn drain(&mut self, output: &mut [f32]) -> usize {
+        let count = output.len().min(self.fifo.len());
+        for (sample, value) in output.iter_mut().zip(self.fifo.drain(..count)) {
+            *sample = value;
+        }
+        count
+    }
+
+    fn process_grain(&mut self) -> Result<(), BungeeError> {
+        let channels = self.stretcher.channel_count();
+        let (begin, end) = self.stretcher.specify_grain()?;
+        let grain_frames = (end - begin).max(0) as usize;
+        self.grain.clear();
+        self.grain.resize(grain_frames * channels, 0.0);
+        self.source.read(begin, end, &mut self.grain, grain_frames);
+        self.stretcher.analyse_grain(&Strided::new(&self.grain, channels, grain_frames, grain_frames, 1)?)?;
+
+        let chunk = self.stretcher.synthesise_grain()?;
+        self.fifo.extend(chunk.interleaved());
+
+        self.request.reset = false;
+        self.stretcher.next(&mut self.request)?;
+        let ended = if self.request.speed < 0.0 {
+            self.request.position < 0.0
+        } else {
+            self.source.frame_count().is_some_and(|n| self.request.position >= n as f64)
+        };
+        if ended {
+            self.request.position = f64::NAN;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    const RATE: i32 = 44100;
+
+    /// One second of a mono 440 Hz tone
+    struct Tone;
+
+    impl InputSource for Tone {
+        fn read(&mut self, begin: i32, end: i32, data: &mut [f32], _channel_stride: usize) {
+            for (sample, i) in data.iter_mut().zip(begin..end) {
+                *sample = if (0..RATE).contains(&i) { (2.0 * PI * 440.0 * i as f32 / RATE as f32).sin() } else { 0.0 };
+            }
+        }
+
+        fn frame_count(&self) -> Option<usize> {
+            Some(RATE as usize)
+        }
+    }
+
+    fn stretcher(position: f64, speed: f64) -> StreamingStretcher<Tone> {
+        let rates = SampleRates { input: RATE, output: RATE };
+        let request = Request {
+            position,
+            speed,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        StreamingStretcher::new(rates, 1, Tone, request).unwrap()
+    }
+
+    /// Fill blocks of `block` frames until the stretcher finishes, returning all output
+    fn drain(stretcher: &mut StreamingStretcher<Tone>, block: usize) -> Vec<f32> {
+        let mut output = Vec::new();
+        let mut buffer = vec![0.0; block];
+        while !stretcher.is_finished() {
+            let frames = stretcher.fill(&mut buffer).unwrap();
+            output.extend_from_slice(&buffer[..frames]);
+        }
+        output
+    }
+
+    #[test]
+    fn output_does_not_depend_on_block_size() {
+        let whole = drain(&mut stretcher(0.0, 1.5), 1 << 16);
+        assert!(!whole.is_empty());
+        for block in [1, 100, 512, 3000] {
+            assert_eq!(drain(&mut stretcher(0.0, 1.5), block), whole, "block of {block} frames");
+        }
+    }
+
+    #[test]
+    fn end_of_stream_is_reported() {
+        let mut stretcher = stretcher(RATE as f64 * 0.9, 1.0);
+        let mut block = [1.0; 1024];
+        let mut last = block.len();
+        while last == block.len() {
+            last = stretcher.fill(&mut block).unwrap();
+        }
+        // The short block is padded with silence and later fills produce nothing
+        assert!(block[last..].iter().all(|&x| x == 0.0));
+        assert!(stretcher.is_finished());
+        block.fill(1.0);
+        assert_eq!(stretcher.fill(&mut block).unwrap(), 0);
+        assert!(block.iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    fn partial_frames_are_rejected() {
+        let rates = SampleRates { input: RATE, output: RATE };
+        let request = Request {
+            position: 0.0,
+            speed: 1.0,
+            pitch: 1.0,
+            reset: true,
+            preserve_formants: false,
+        };
+        let mut stretcher = StreamingStretcher::new(rates, 2, Tone, request).unwrap();
+        assert!(matches!(stretcher.fill(&mut [0.0; 1023]), Err(BungeeError::InvalidParam { .. })));
+        assert_eq!(stretcher.fill(&mut [0.0; 1024]).unwrap(), 512);
+    }
+
+    #[test]
+    fn zero_speed_holds_the_sound_indefinitely() {
+        let mut stretcher = stretcher(RATE as f64 / 2.0, 0.0);
+        let mut block = [0.0; 1024];
+        for _ in 0..200 {
+            assert_eq!(stretcher.fill(&mut block).unwrap(), block.len());
+        }
+        let rms = (block.iter().map(|x| x * x).sum::<f32>() / block.len() as f32).sqrt();
+        assert!((rms - 0.5f32.sqrt()).abs() < 0.05, "rms {rms}");
+        assert_eq!(stretcher.request().position, RATE as f64 / 2.0);
+    }
+
+    #[test]
+    fn seek_continues_output_from_the_new_position() {
+        let mut stretcher = stretcher(0.0, 1.0);
+        let mut block = [0.0; 1024];
+        for _ in 0..8 {
+            stretcher.fill(&mut block).unwrap();
+        }
+        stretcher.seek(RATE as f64 * 0.75, 2048).unwrap();
+        assert!(stretcher.request().position < RATE as f64 * 0.75, "seek did not preroll");
+        for _ in 0..8 {
+            assert_eq!(stretcher.fill(&mut block).unwrap(), block.len());
+            let rms = (block.iter().map(|x| x * x).sum::<f32>() / block.len() as f32).sqrt();
+            assert!(rms > 0.5, "rms {rms} across the seek");
+        }
+        assert!(stretcher.request().position > RATE as f64 * 0.75);
+
+        // Playback from the new position runs to the end of the source
+        let mut frames = 0;
+        while !stretcher.is_finished() {
+            frames += stretcher.fill(&mut block).unwrap();
+        }
+        assert!(frames < RATE as usize / 3, "{frames} frames after the seek");
+    }
+
+    #[test]
+    fn negative_speed_finishes_at_the_start() {
+        let mut stretcher = stretcher(RATE as f64 / 2.0, -1.0);
+        let mut block = [0.0; 1024];
+        let mut frames = 0;
+        while !stretcher.is_finished() {
+            frames += stretcher.fill(&mut block).unwrap();
+            assert!(frames < RATE as usize, "reverse playback did not end");
+        }
+        assert!(frames >= RATE as usize / 2, "{frames} frames");
+    }
+}
@@ -0,0 +1,96 @@
+//! Choice of the window family used to analyse and synthesise each grain
+
+use crate::*;
+
+/// Largest Kaiser `beta` and number of cosine-sum coefficients accepted by the C layer
+pub const MAX_KAISER_BETA: f32 = BUNGEE_MAX_KAISER_BETA as f32;
+pub const MAX_WINDOW_COEFFICIENTS: usize = BUNGEE_MAX_WINDOW_COEFFICIENTS as usize;
+
+/// Shape of the analysis and synthesis windows
+///
+/// Both windows take the same shape. Their gains are computed from it, so that grains sum to
+/// unity once overlapped whatever the family: the choice trades frequency resolution against
+/// sidelobe leakage without changing level.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Window {
+    /// Raised cosine, with good frequency resolution
+    #[default]
+    Hann,
+    /// Four-term Blackman-Harris: much lower sidelobes than Hann, with a wider main lobe
+    BlackmanHarris,
+    /// Kaiser-Bessel: sidelobes fall and the main lobe widens as `beta` rises from 0 to
+    /// `MAX_KAISER_BETA`
+    Kaiser { beta: f32 },
+    /// `a0 - a1 cos(2 pi n / N) + a2 cos(4 pi n / N) - ...` with coefficients `[a0, a1, ...]`,
+    /// up to `MAX_WINDOW_COEFFICIENTS` of them
+    ///
+    /// `a0` must be positive and the window should be nowhere negative, as are flat-top and
+    /// Nuttall windows.
+    CosineSum(Vec<f32>),
+}
+
+impl Window {
+    /// Descriptor for the C layer, borrowing any coefficients from `self`
+    pub(crate) fn raw(&self) -> bungee_window_t {
+        let mut window = bungee_window_t {
+            shape: bungee_window_shape_BUNGEE_WINDOW_HANN,
+            kaiser_beta: 0.0,
+            coefficients: std::ptr::null(),
+            coefficient_count: 0,
+        };
+        match self {
+            Window::Hann => {}
+            Window::BlackmanHarris => window.shape = bungee_window_shape_BUNGEE_WINDOW_BLACKMAN_HARRIS,
+            Window::Kaiser { beta } => {
+                window.shape = bungee_window_shape_BUNGEE_WINDOW_KAISER;
+                window.kaiser_beta = *beta;
+            }
+            Window::CosineSum(coefficients) => {
+                window.shape = bungee_window_shape_BUNGEE_WINDOW_COSINE_SUM;
+                window.coefficients = coefficients.as_ptr();
+                window.coefficient_count = coefficients.len().min(i32::MAX as usize) as i32;
+            }
+        }
+        window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: SampleRates = SampleRates { input: 44100, output: 44100 };
+
+    fn build(window: Window) -> Result<Stretcher, BungeeError> {
+        StretcherConfig::new().window(window).build(RATES, 2)
+    }
+
+    #[test]
+    fn valid_windows_build() {
+        for window in [
+            Window::Hann,
+            Window::BlackmanHarris,
+            Window::Kaiser { beta: 0.0 },
+            Window::Kaiser { beta: MAX_KAISER_BETA },
+            Window::CosineSum(vec![1.0]),
+            Window::CosineSum(vec![0.21557895, 0.41663158, 0.27726316, 0.08357895, 0.006947368]),
+        ] {
+            assert!(build(window.clone()).is_ok(), "{window:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_windows_are_rejected() {
+        for window in [
+            Window::Kaiser { beta: -1.0 },
+            Window::Kaiser { beta: f32::NAN },
+            Window::Kaiser { beta: MAX_KAISER_BETA * 2.0 },
+            Window::CosineSum(Vec::new()),
+            Window::CosineSum(vec![0.0, 0.5]),
+            Window::CosineSum(vec![0.5, f32::INFINITY]),
+            Window::CosineSum(vec![0.1; MAX_WINDOW_COEFFICIENTS + 1]),
+        ] {
+            assert!(matches!(build(window.clone()), Err(BungeeError::InvalidParam { .. })), "{window:?}");
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright (C) 2020-2024 Parabola Research Limited
+// SPDX-License-Identifier: MPL-2.0
+
+#pragma once
+
+// C-compatible interface to the Bungee stretcher implementations.
+// This header may be included from C or C++. The C++ implementation in src/ uses the
+// Bungee:: aliases at the bottom of this file; C callers use the Bungee_ prefixed types
+// and reach an implementation only through Bungee_Stretcher_FunctionTable.
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Bungee_Request
+{
+	// Frame-offset within the input audio of the centre-point of the current audio grain.
+	// NaN signifies an invalid grain that produces no audio output and may be used for flushing.
+	double position;
+
+	// Output audio speed. Value of 1 means speed should be the same as the input audio.
+	double speed;
+
+	// Adjustment as a frequency multiplier with a value of 1 meaning no pitch adjustment.
+	double pitch;
+
+	// Set to have the stretcher forget all previous grains and restart on this grain.
+	bool reset;
+
+	// Set to keep the spectral envelope (formants) in place when pitch is not 1, avoiding
+	// "chipmunk" voices. The envelope is a cepstral estimate made per grain.
+	bool preserveFormants;
+} Bungee_Request;
+
+typedef struct Bungee_InputChunk
+{
+	// Frame offsets relative to the start of the audio buffer provided by the caller.
+	int begin, end;
+} Bungee_InputChunk;
+
+typedef struct Bungee_OutputChunk
+{
+	float *data; // audio output data, not aligned and not interleaved
+	int frameCount;
+	intptr_t channelStride; // nth audio channel audio starts at data[n * channelStride]
+
+#ifdef __cplusplus
+	static constexpr int begin = 0, end = 1;
+#endif
+	const Bungee_Request *request[2 /* 0=begin, 1=end */];
+} Bungee_OutputChunk;
+
+typedef struct Bungee_SampleRates
+{
+	int input;
+	int output;
+} Bungee_SampleRates;
+
+// Shape of an automation envelope segment, from one breakpoint to the next.
+typedef enum Bungee_Curve
+{
+	Bungee_Curve_hold, // keep the breakpoint's value until the next breakpoint
+	Bungee_Curve_linear,
+	Bungee_Curve_exponential, // falls back to linear unless both values have the same sign and are non-zero
+} Bungee_Curve;
+
+// Request field controlled by an automation envelope.
+typedef enum Bungee_Parameter
+{
+	Bungee_Parameter_speed,
+	Bungee_Parameter_pitch,
+} Bungee_Parameter;
+
+// Clock on which automation breakpoint times are measured.
+typedef enum Bungee_TimeBase
+{
+	Bungee_TimeBase_input, // input frames, as in Request::position
+	Bungee_TimeBase_output, // output frames synthesised since the last reset
+} Bungee_TimeBase;
+
+typedef struct Bungee_Breakpoint
+{
+	double time;
+	double value;
+	Bungee_Curve curve; // shape of the segment that starts at this breakpoint
+} Bungee_Breakpoint;
+
+// Where pitch-shift and sample-rate conversion resampling happens, see src/Modes.h.
+typedef enum Bungee_ResampleMode
+{
+	Bungee_ResampleMode_autoOut, // output resampling, activated as needed (default)
+	Bungee_ResampleMode_autoIn, // input resampling, activated as needed
+	Bungee_ResampleMode_autoInOut, // input resampling when downsampling and output resampling when upsampling
+	Bungee_ResampleMode_forceOut, // output resampling, always active
+	Bungee_ResampleMode_forceIn, // input resampling, always active
+} Bungee_ResampleMode;
+
+// Interpolation kernel used by input and output resampling.
+typedef enum Bungee_Interpolation
+{
+	Bungee_Interpolation_nearest,
+	Bungee_Interpolation_bilinear, // default
+	Bungee_Interpolation_cubicHermite, // Catmull-Rom spline through four samples
+	Bungee_Interpolation_windowedSinc, // Blackman-windowed sinc, band-limited when downsampling
+} Bungee_Interpolation;
+
+// How the phases of multiple channels are related during stretching.
+typedef enum Bungee_ChannelCoupling
+{
+	// One phase rotation per bin, derived from the sum of all channels and applied to each (default).
+	// Inter-channel phase is preserved exactly, so the mono fold-down stretches as cleanly as a mono input.
+	Bungee_ChannelCoupling_sumLocked,
+	// Stereo only: mid (L+R)/2 and side (L-R)/2 each have their own rotation. The mono fold-down is the mid
+	// channel stretched on its own, while wide material in the side channel keeps its own phase evolution.
+	Bungee_ChannelCoupling_midSide,
+	// Each channel has its own rotation, as if stretched separately: best for unrelated (dual-mono) channels,
+	// but inter-channel phase drifts, so correlated material can lose its image and mono compatibility.
+	Bungee_ChannelCoupling_independent,
+} Bungee_ChannelCoupling;
+
+// Delays of a stretcher in frames, for the sample rates, hop, resampling and pitch it is working with.
+typedef struct Bungee_Latency
+{
+	int input; // input frames that grains read beyond the input position of the first frame of the output chunk they make
+	int output; // output frames from an input frame being supplied to its appearing in the output, at unit speed
+	int control; // output frames produced between a request passed to next and the first output frame it affects
+} Bungee_Latency;
+
+// Shape of the analysis and synthesis windows, both derived from the same family. Window gains are computed
+// from the shapes so that grains, windowed on analysis and on synthesis and then overlapped, sum to unity on average.
+typedef enum Bungee_WindowShape
+{
+	Bungee_WindowShape_hann, // default
+	Bungee_WindowShape_blackmanHarris, // four-term: lower sidelobes than Hann, wider main lobe
+	Bungee_WindowShape_kaiser, // Kaiser-Bessel: sidelobes fall and the main lobe widens as kaiserBeta rises
+	Bungee_WindowShape_cosineSum, // a0 - a1 cos(2 pi n / N) + a2 cos(4 pi n / N) - ... with coefficients a
+} Bungee_WindowShape;
+
+typedef struct Bungee_Window
+{
+	Bungee_WindowShape shape;
+	float kaiserBeta; // kaiser only, 0 or more
+	const float *coefficients; // cosineSum only, read during create
+	int coefficientCount;
+} Bungee_Window;
+
+// Real FFT supplied by the host in place of the built-in kissfft. A transform of length n = 1 << log2TransformLength
+// maps n real samples to n / 2 + 1 complex bins, stored as interleaved (real, imaginary) float pairs. Both directions
+// are unnormalised, forward using exp(-2 pi i k t / n), so that an inverse of a forward transform scales by n.
+// Transforms of each length are prepared before first use. Calls for one stretcher never overlap, and destroy is
+// called once when the stretcher is destroyed.
+typedef struct Bungee_FourierCallbacks
+{
+	void *context;
+	void (*prepare)(void *context, int log2TransformLength, bool inverse);
+	void (*forward)(void *context, int log2TransformLength, const float *time, float *frequency);
+	void (*inverse)(void *context, int log2TransformLength, float *time, const float *frequency);
+	void (*destroy)(void *context);
+} Bungee_FourierCallbacks;
+
+typedef struct Bungee_Stretcher_FunctionTable
+{
+	const char *(*version)(void);
+	// maxPitchOctaves sizes the grain buffers for pitch from 2^-maxPitchOctaves to 2^maxPitchOctaves.
+	// fourier may be null for the built-in kissfft, else the stretcher takes ownership of its context.
+	// window may be null for Hann windows.
+	void *(*create)(Bungee_SampleRates sampleRates, int channelCount, int log2SynthesisHopOverride, int maxPitchOctaves, const Bungee_FourierCallbacks *fourier, const Bungee_Window *window);
+	void (*destroy)(void *implementation);
+	int (*maxInputFrameCount)(const void *implementation);
+	void (*preroll)(const void *implementation, Bungee_Request *request);
+	void (*next)(const void *implementation, Bungee_Request *request);
+	Bungee_InputChunk (*specifyGrain)(void *implementation, const Bungee_Request *request);
+	void (*analyseGrain)(void *implementation, const float *data, intptr_t channelStride);
+	void (*synthesiseGrain)(void *implementation, Bungee_OutputChunk *outputChunk);
+	bool (*isFlushed)(const void *implementation);
+	int (*maxOutputFrameCount)(const void *implementation);
+	// Replaces the envelope of one parameter, applied by preroll and next; count of zero removes it.
+	// Breakpoint times must be non-decreasing.
+	void (*setAutomation)(void *implementation, Bungee_Parameter parameter, Bungee_TimeBase timeBase, const Bungee_Breakpoint *breakpoints, int count);
+	// Selects the resample mode used by preroll, next and the grains specified from now on,
+	// so it may be changed between requests.
+	void (*setResampleMode)(void *implementation, Bungee_ResampleMode resampleMode);
+	// Selects the resampling interpolation kernel, like setResampleMode. sincTaps is used only
+	// by Bungee_Interpolation_windowedSinc and must be 4, 8, 16 or 32.
+	void (*setInterpolation)(void *implementation, Bungee_Interpolation interpolation, int sincTaps);
+	// Sets how readily a grain is treated as a transient, from 0 (never, the default) to 1. Partials
+	// whose energy rises sharply in a transient grain have their phase reset, keeping attacks sharp.
+	void (*setTransientSensitivity)(void *implementation, float sensitivity);
+	// Selects channel coupling from the next grain with Request::reset set, as from preroll. midSide requires two channels.
+	void (*setChannelCoupling)(void *implementation, Bungee_ChannelCoupling channelCoupling);
+	// Reports latency at the given pitch with the current resample mode and interpolation.
+	Bungee_Latency (*latency)(const void *implementation, double pitch);
+} Bungee_Stretcher_FunctionTable;
+
+Bungee_Stretcher_FunctionTable Bungee_Stretcher_getFunctionTable(void);
+
+#ifdef __cplusplus
+} // extern "C"
+
+namespace Bungee {
+
+typedef Bungee_Request Request;
+typedef Bungee_InputChunk InputChunk;
+typedef Bungee_OutputChunk OutputChunk;
+typedef Bungee_SampleRates SampleRates;
+typedef Bungee_Breakpoint Breakpoint;
+typedef Bungee_Latency Latency;
+
+} // namespace Bungee
+#endif
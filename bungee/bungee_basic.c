@@ -0,0 +1,1277 @@
+/**
+ * @file bungee_basic.c
+ * @brief bungee_c.h implemented on top of the Bungee::Basic engine
+ *
+ * This file provides the pure C API declared in bungee_c.h by forwarding each
+ * call through Bungee_Stretcher_FunctionTable (see Bungee.h). The phase-vocoder
+ * engine itself lives in src/ and is only reached through the function table,
+ * so this layer does parameter passing and validation only.
+ */
+
+#include "bungee_c.h"
+#include "bungee_error.h"
+#include "bungee_sample.h"
+#include "bungee_window.h"
+#include "Bungee.h"
+#include <limits.h>
+#include <math.h>
+#include <stdlib.h>
+#include <string.h>
+
+/**
+ * @brief Progress through the calls that process one grain
+ *
+ * The engine requires specify, analyse and synthesise in that order for each grain;
+ * calling them out of order would silently corrupt its grain pipeline.
+ */
+typedef enum grain_stage {
+    GRAIN_READY,        /**< After create, preroll or next: specify is next */
+    GRAIN_SPECIFIED,    /**< analyse is next */
+    GRAIN_ANALYSED,     /**< synthesise is next */
+    GRAIN_SYNTHESISED   /**< next is next */
+} grain_stage_t;
+
+static const char* grain_stage_name(grain_stage_t stage) {
+    switch (stage) {
+    case GRAIN_READY: return "ready to specify";
+    case GRAIN_SPECIFIED: return "specified";
+    case GRAIN_ANALYSED: return "analysed";
+    case GRAIN_SYNTHESISED: return "synthesised";
+    }
+    return "unknown";
+}
+
+/**
+ * @brief Progress of a crossfading seek
+ *
+ * While a seek crossfades, grains alternate between the chain being left, run by the
+ * outgoing engine, and the chain primed at the new position, run by the main engine.
+ */
+typedef enum fade_stage {
+    FADE_NONE,      /**< One chain plays */
+    FADE_PRIMING,   /**< The new chain has yet to output: outgoing output passes through */
+    FADE_MIXING     /**< Outgoing output is mixed under the new chain's */
+} fade_stage_t;
+
+/**
+ * @brief Settings passed to the engine, kept to configure the engine a seek creates
+ */
+typedef struct engine_settings {
+    Bungee_ResampleMode resample_mode;
+    Bungee_Interpolation interpolation;
+    int sinc_taps;
+    float transient_sensitivity;
+    Bungee_ChannelCoupling channel_coupling;
+    Bungee_TimeBase automation_time_base[2];  /**< Per Bungee_Parameter */
+    Bungee_Breakpoint* automation[2];         /**< Per Bungee_Parameter, NULL without an envelope */
+    int automation_count[2];
+} engine_settings_t;
+
+/**
+ * @brief Internal stretcher structure
+ *
+ * Holds the engine instance and the request for the current grain. The request
+ * is owned here because the engine's preroll and next functions update it in place.
+ */
+struct bungee_stretcher {
+    Bungee_Stretcher_FunctionTable table; /**< Engine entry points */
+    void* implementation;                 /**< Engine instance created by table.create */
+    Bungee_Request request;               /**< Request for the current grain */
+    float* grain;                         /**< Planar float copy of typed input, maxInputFrameCount per channel */
+    int grain_frames;                     /**< Input frame count of the grain last specified */
+    int channels;                         /**< Number of audio channels */
+    int max_pitch_octaves;                /**< Pitch range either side of unity */
+    grain_stage_t stage;                  /**< Progress through the current grain */
+    Bungee_SampleRates sample_rates;      /**< Creation options, kept for the engine a seek creates */
+    int log2_synthesis_hop;
+    Bungee_FourierCallbacks fourier;      /**< Caller's FFT less destroy, given to each engine */
+    bool custom_fft;                      /**< Whether fourier holds a caller's FFT */
+    void (*fft_destroy)(void* context);   /**< Caller's FFT destroy, called once the engines are destroyed */
+    Bungee_Window window;                 /**< Window option, coefficients in window_coefficients */
+    float window_coefficients[BUNGEE_MAX_WINDOW_COEFFICIENTS];
+    engine_settings_t settings;           /**< Settings given to the engine */
+    void* outgoing;                       /**< Engine running the chain a seek left, NULL before the first */
+    Bungee_Request outgoing_request;      /**< Request for the outgoing chain's next grain */
+    fade_stage_t fade;                    /**< Progress of the current seek */
+    bool outgoing_turn;                   /**< Whether the current grain is the outgoing chain's */
+    float* fade_buffer;                   /**< Outgoing output awaiting mixing, planar, fade_capacity per channel */
+    int fade_capacity;                    /**< Frames per channel of fade_buffer */
+    int fade_buffered;                    /**< Frames of outgoing output in fade_buffer */
+    Bungee_Request fade_request[2];       /**< Requests bounding the outgoing output in fade_buffer */
+    int fade_frames;                      /**< Length of the crossfade in output frames */
+    int fade_position;                    /**< Output frames of the crossfade mixed so far */
+};
+
+/**
+ * @brief Checks that a grain function is called at the right stage
+ *
+ * @param stretcher Stretcher instance
+ * @param function Name of the function being called, for the error message
+ * @param expected Stage the current grain must be at
+ * @return BUNGEE_OK, or BUNGEE_GRAIN_OUT_OF_ORDER with the last error message set
+ */
+static bungee_error_t check_stage(const bungee_stretcher_t* stretcher, const char* function, grain_stage_t expected) {
+    if (stretcher->stage != expected) {
+        return bungee_set_error(BUNGEE_GRAIN_OUT_OF_ORDER, "%s called while grain is %s, expected %s",
+                                function, grain_stage_name(stretcher->stage), grain_stage_name(expected));
+    }
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Checks that a pitch lies within the range the stretcher was created for
+ *
+ * @param stretcher Stretcher instance
+ * @param function Name of the function being called, for the error message
+ * @param pitch Pitch as a frequency multiplier
+ * @return BUNGEE_OK, BUNGEE_INVALID_PARAM if pitch is not positive or
+ *         BUNGEE_PITCH_OUT_OF_RANGE if it is outside the configured octaves
+ */
+static bungee_error_t check_pitch(const bungee_stretcher_t* stretcher, const char* function, double pitch) {
+    if (!(pitch > 0.0)) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid pitch in %s: %f", function, pitch);
+    }
+    if (pitch < ldexp(1.0, -stretcher->max_pitch_octaves) || pitch > ldexp(1.0, stretcher->max_pitch_octaves)) {
+        return bungee_set_error(BUNGEE_PITCH_OUT_OF_RANGE, "Pitch %f in %s is outside +/-%d octaves",
+                                pitch, function, stretcher->max_pitch_octaves);
+    }
+    return BUNGEE_OK;
+}
+
+static Bungee_Request to_engine_request(const bungee_request_t* request) {
+    Bungee_Request r;
+    r.position = request->position;
+    r.speed = request->speed;
+    r.pitch = request->pitch;
+    r.reset = request->reset;
+    r.preserveFormants = request->preserve_formants;
+    return r;
+}
+
+static void from_engine_request(const Bungee_Request* r, bungee_request_t* request) {
+    request->position = r->position;
+    request->speed = r->speed;
+    request->pitch = r->pitch;
+    request->reset = r->reset;
+    request->preserve_formants = r->preserveFormants;
+}
+
+/**
+ * @brief Interpolates the input positions of a chunk's first and last frames
+ *
+ * Output frames are evenly spaced in input position between request[0], at the
+ * first frame, and request[1], one frame past the last.
+ *
+ * @param chunk Output chunk with frame_count and request already set
+ */
+static void set_frame_positions(bungee_output_chunk_t* chunk) {
+    double begin = chunk->request[0].position;
+    double end = chunk->request[1].position;
+    if (chunk->frame_count <= 0 || isnan(begin) || isnan(end)) {
+        chunk->first_position = NAN;
+        chunk->last_position = NAN;
+        return;
+    }
+    chunk->first_position = begin;
+    chunk->last_position = begin + (end - begin) * (chunk->frame_count - 1) / chunk->frame_count;
+}
+
+/**
+ * @brief The engine that runs the current grain
+ *
+ * @param stretcher Stretcher instance
+ * @return The outgoing engine on its turns during a seek, otherwise the main engine
+ */
+static void* grain_engine(const bungee_stretcher_t* stretcher) {
+    return stretcher->outgoing_turn ? stretcher->outgoing : stretcher->implementation;
+}
+
+/**
+ * @brief Gives an engine the settings recorded so far
+ *
+ * @param stretcher Stretcher instance
+ * @param implementation Engine instance, newly created
+ */
+static void configure_engine(const bungee_stretcher_t* stretcher, void* implementation) {
+    const engine_settings_t* settings = &stretcher->settings;
+    stretcher->table.setResampleMode(implementation, settings->resample_mode);
+    stretcher->table.setInterpolation(implementation, settings->interpolation, settings->sinc_taps);
+    stretcher->table.setTransientSensitivity(implementation, settings->transient_sensitivity);
+    stretcher->table.setChannelCoupling(implementation, settings->channel_coupling);
+    for (int parameter = 0; parameter < 2; parameter++) {
+        stretcher->table.setAutomation(implementation, (Bungee_Parameter)parameter,
+                                       settings->automation_time_base[parameter], settings->automation[parameter],
+                                       settings->automation_count[parameter]);
+    }
+}
+
+/**
+ * @brief Runs grains without input through an engine until its pipeline is empty
+ *
+ * A chain then primed on the engine starts from silence rather than overlapping
+ * whatever the engine last played.
+ *
+ * @param stretcher Stretcher instance
+ * @param implementation Engine instance
+ */
+static void flush_engine(bungee_stretcher_t* stretcher, void* implementation) {
+    Bungee_Request request;
+    memset(&request, 0, sizeof(request));
+    request.position = NAN;
+    request.speed = 1.0;
+    request.pitch = 1.0;
+    while (!stretcher->table.isFlushed(implementation)) {
+        Bungee_OutputChunk output;
+        stretcher->table.specifyGrain(implementation, &request);
+        stretcher->table.analyseGrain(implementation, stretcher->grain, 0);
+        stretcher->table.synthesiseGrain(implementation, &output);
+    }
+}
+
+/**
+ * @brief Initialize the Bungee library
+ *
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_init(void) {
+    BUNGEE_LOG("=== Bungee library initializing: %s ===",
+           Bungee_Stretcher_getFunctionTable().version());
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Clean up the Bungee library
+ */
+void bungee_cleanup(void) {
+    BUNGEE_LOG_SIMPLE("=== Bungee library cleanup ===");
+}
+
+/**
+ * @brief Creates a new stretcher instance
+ *
+ * @param rates Sample rate configuration
+ * @param channels Number of audio channels
+ * @return Stretcher instance or NULL on error
+ */
+bungee_stretcher_t* bungee_create(bungee_sample_rates_t rates, int channels) {
+    return bungee_create_with_config(rates, channels, NULL);
+}
+
+/**
+ * @brief Creates a new stretcher instance with creation options
+ *
+ * @param rates Sample rate configuration
+ * @param channels Number of audio channels
+ * @param config Creation options, or NULL for the defaults
+ * @return Stretcher instance or NULL on error
+ */
+bungee_stretcher_t* bungee_create_with_config(bungee_sample_rates_t rates, int channels, const bungee_config_t* config) {
+    const int log2_synthesis_hop = config ? config->log2_synthesis_hop : 0;
+    int max_pitch_octaves = config ? config->max_pitch_octaves : 0;
+    const bungee_fft_backend_t* fft = config ? config->fft : NULL;
+    const bungee_window_t* window = config ? config->window : NULL;
+
+    BUNGEE_LOG("Creating stretcher: input_rate=%d, output_rate=%d, channels=%d, log2_synthesis_hop=%d, max_pitch_octaves=%d",
+           rates.input_rate, rates.output_rate, channels, log2_synthesis_hop, max_pitch_octaves);
+
+    if (channels <= 0) {
+        bungee_set_error(BUNGEE_INVALID_CHANNEL_COUNT, "Invalid channel count: %d", channels);
+        return NULL;
+    }
+
+    if (rates.input_rate < BUNGEE_MIN_SAMPLE_RATE || rates.input_rate > BUNGEE_MAX_SAMPLE_RATE ||
+        rates.output_rate < BUNGEE_MIN_SAMPLE_RATE || rates.output_rate > BUNGEE_MAX_SAMPLE_RATE) {
+        bungee_set_error(BUNGEE_RATE_OUT_OF_RANGE, "Sample rates input=%d, output=%d outside %d..%d Hz",
+                         rates.input_rate, rates.output_rate, BUNGEE_MIN_SAMPLE_RATE, BUNGEE_MAX_SAMPLE_RATE);
+        return NULL;
+    }
+
+    if (log2_synthesis_hop != 0 &&
+        (log2_synthesis_hop < BUNGEE_MIN_LOG2_SYNTHESIS_HOP || log2_synthesis_hop > BUNGEE_MAX_LOG2_SYNTHESIS_HOP)) {
+        bungee_set_error(BUNGEE_INVALID_PARAM, "log2_synthesis_hop %d outside %d..%d", log2_synthesis_hop,
+                         BUNGEE_MIN_LOG2_SYNTHESIS_HOP, BUNGEE_MAX_LOG2_SYNTHESIS_HOP);
+        return NULL;
+    }
+
+    if (max_pitch_octaves == 0) {
+        max_pitch_octaves = BUNGEE_DEFAULT_MAX_PITCH_OCTAVES;
+    } else if (max_pitch_octaves < 1 || max_pitch_octaves > BUNGEE_MAX_PITCH_OCTAVES) {
+        bungee_set_error(BUNGEE_INVALID_PARAM, "max_pitch_octaves %d outside 1..%d", max_pitch_octaves,
+                         BUNGEE_MAX_PITCH_OCTAVES);
+        return NULL;
+    }
+
+    if (fft && (!fft->forward || !fft->inverse)) {
+        bungee_set_error(BUNGEE_INVALID_PARAM, "FFT backend must provide forward and inverse transforms");
+        return NULL;
+    }
+
+    if (bungee_check_window(window) != BUNGEE_OK) {
+        return NULL;
+    }
+
+    bungee_stretcher_t* stretcher = (bungee_stretcher_t*)malloc(sizeof(bungee_stretcher_t));
+    if (!stretcher) {
+        bungee_set_error(BUNGEE_MEMORY, "Failed to allocate stretcher");
+        return NULL;
+    }
+
+    Bungee_SampleRates sample_rates;
+    sample_rates.input = rates.input_rate;
+    sample_rates.output = rates.output_rate;
+
+    Bungee_FourierCallbacks fourier;
+    if (fft) {
+        fourier.context = fft->context;
+        fourier.prepare = fft->prepare;
+        fourier.forward = fft->forward;
+        fourier.inverse = fft->inverse;
+        /* Released by bungee_destroy, so that a failed create leaves the context with the caller */
+        fourier.destroy = NULL;
+    }
+
+    memset(&stretcher->window, 0, sizeof(stretcher->window));
+    if (window) {
+        stretcher->window.shape = (Bungee_WindowShape)window->shape;
+        stretcher->window.kaiserBeta = window->kaiser_beta;
+        if (window->shape == BUNGEE_WINDOW_COSINE_SUM) {
+            memcpy(stretcher->window_coefficients, window->coefficients, window->coefficient_count * sizeof(float));
+            stretcher->window.coefficients = stretcher->window_coefficients;
+            stretcher->window.coefficientCount = window->coefficient_count;
+        }
+    }
+
+    stretcher->table = Bungee_Stretcher_getFunctionTable();
+    stretcher->implementation = stretcher->table.create(sample_rates, channels, log2_synthesis_hop, max_pitch_octaves,
+                                                        fft ? &fourier : NULL, &stretcher->window);
+    if (!stretcher->implementation) {
+        bungee_set_error(BUNGEE_MEMORY, "Failed to create engine instance");
+        free(stretcher);
+        return NULL;
+    }
+
+    const size_t grain_bytes =
+        (size_t)stretcher->table.maxInputFrameCount(stretcher->implementation) * channels * sizeof(float);
+    stretcher->grain = (float*)malloc(grain_bytes);
+    if (!stretcher->grain) {
+        bungee_set_error(BUNGEE_MEMORY, "Failed to allocate grain buffer: %zu bytes", grain_bytes);
+        stretcher->table.destroy(stretcher->implementation);
+        free(stretcher);
+        return NULL;
+    }
+    stretcher->grain_frames = 0;
+
+    memset(&stretcher->request, 0, sizeof(stretcher->request));
+    stretcher->request.speed = 1.0;
+    stretcher->request.pitch = 1.0;
+    stretcher->request.reset = true;
+    stretcher->channels = channels;
+    stretcher->max_pitch_octaves = max_pitch_octaves;
+    stretcher->stage = GRAIN_READY;
+
+    stretcher->sample_rates = sample_rates;
+    stretcher->log2_synthesis_hop = log2_synthesis_hop;
+    stretcher->custom_fft = fft != NULL;
+    stretcher->fft_destroy = fft ? fft->destroy : NULL;
+    if (fft) {
+        stretcher->fourier = fourier;
+    }
+    memset(&stretcher->settings, 0, sizeof(stretcher->settings));
+    stretcher->settings.resample_mode = Bungee_ResampleMode_autoOut;
+    stretcher->settings.interpolation = Bungee_Interpolation_bilinear;
+    stretcher->settings.channel_coupling = Bungee_ChannelCoupling_sumLocked;
+    stretcher->outgoing = NULL;
+    stretcher->fade = FADE_NONE;
+    stretcher->outgoing_turn = false;
+    stretcher->fade_buffer = NULL;
+    stretcher->fade_capacity = 0;
+    stretcher->fade_buffered = 0;
+
+    BUNGEE_LOG("Stretcher created successfully: max_input_frames=%d",
+           stretcher->table.maxInputFrameCount(stretcher->implementation));
+    return stretcher;
+}
+
+/**
+ * @brief Destroys a time-stretcher instance
+ *
+ * @param stretcher Stretcher instance to destroy (may be NULL)
+ */
+void bungee_destroy(bungee_stretcher_t* stretcher) {
+    if (stretcher) {
+        stretcher->table.destroy(stretcher->implementation);
+        if (stretcher->outgoing) {
+            stretcher->table.destroy(stretcher->outgoing);
+        }
+        if (stretcher->fft_destroy) {
+            stretcher->fft_destroy(stretcher->fourier.context);
+        }
+        free(stretcher->settings.automation[0]);
+        free(stretcher->settings.automation[1]);
+        free(stretcher->fade_buffer);
+        free(stretcher->grain);
+        free(stretcher);
+    }
+}
+
+/**
+ * @brief Prepares the stretcher to start output at the request's position
+ *
+ * The request is moved back by the engine's pipeline latency so that the
+ * first grains fill the pipeline before output reaches the requested position.
+ * Any crossfade from a seek is abandoned.
+ *
+ * @param stretcher Stretcher instance
+ * @param request Initial processing parameters, updated in place
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_preroll(bungee_stretcher_t* stretcher, bungee_request_t* request) {
+    if (!stretcher || !request) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in preroll: stretcher=%p, request=%p",
+                                (void*)stretcher, (void*)request);
+    }
+
+    bungee_error_t error = check_pitch(stretcher, "bungee_preroll", request->pitch);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    stretcher->request = to_engine_request(request);
+    stretcher->table.preroll(stretcher->implementation, &stretcher->request);
+    stretcher->stage = GRAIN_READY;
+    stretcher->fade = FADE_NONE;
+    stretcher->fade_buffered = 0;
+    stretcher->outgoing_turn = false;
+
+    BUNGEE_LOG("Preroll: position=%f -> %f", request->position, stretcher->request.position);
+    from_engine_request(&stretcher->request, request);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Specifies the current grain
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Receives the input frame range required by bungee_analyse_grain
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_specify_grain(bungee_stretcher_t* stretcher, bungee_input_chunk_t* chunk) {
+    if (!stretcher || !chunk) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in specify_grain: stretcher=%p, chunk=%p",
+                                (void*)stretcher, (void*)chunk);
+    }
+
+    bungee_error_t error = check_stage(stretcher, "bungee_specify_grain", GRAIN_READY);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    Bungee_Request* request = stretcher->outgoing_turn ? &stretcher->outgoing_request : &stretcher->request;
+    Bungee_InputChunk input_chunk = stretcher->table.specifyGrain(grain_engine(stretcher), request);
+    chunk->begin = input_chunk.begin;
+    chunk->end = input_chunk.end;
+    stretcher->grain_frames = input_chunk.end > input_chunk.begin ? input_chunk.end - input_chunk.begin : 0;
+    stretcher->stage = GRAIN_SPECIFIED;
+
+    BUNGEE_LOG("Grain specified: position=%f, begin=%d, end=%d",
+           request->position, chunk->begin, chunk->end);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Checks that analyse or synthesise may be called with a sample format
+ *
+ * @param stretcher Stretcher instance
+ * @param function Name of the function being called, for the error message
+ * @param expected Stage the current grain must be at
+ * @param format Sample format from the caller
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+static bungee_error_t check_grain_format(const bungee_stretcher_t* stretcher, const char* function,
+                                         grain_stage_t expected, bungee_sample_format_t format) {
+    bungee_error_t error = check_stage(stretcher, function, expected);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+    return bungee_check_sample_format(format);
+}
+
+/**
+ * @brief Analyses the input audio of the current grain
+ *
+ * @param stretcher Stretcher instance
+ * @param input_data Input audio at frame chunk.begin, planar
+ * @param channel_stride Stride between channels in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_analyse_grain(bungee_stretcher_t* stretcher, const float* input_data, size_t channel_stride) {
+    return bungee_analyse_grain_format(stretcher, input_data, BUNGEE_SAMPLE_F32, channel_stride);
+}
+
+/**
+ * @brief Analyses the input audio of the current grain, converting it from any sample format
+ *
+ * Float input is passed to the engine as it is; other formats are converted
+ * into the stretcher's grain buffer first.
+ *
+ * @param stretcher Stretcher instance
+ * @param input_data Input audio at frame chunk.begin, planar, in the given format
+ * @param format Sample format of input_data
+ * @param channel_stride Stride between channels in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_analyse_grain_format(bungee_stretcher_t* stretcher, const void* input_data,
+                                           bungee_sample_format_t format, size_t channel_stride) {
+    if (!stretcher || !input_data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in analyse_grain: stretcher=%p, input_data=%p",
+                                (void*)stretcher, input_data);
+    }
+
+    bungee_error_t error = check_grain_format(stretcher, "bungee_analyse_grain", GRAIN_SPECIFIED, format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    if (format == BUNGEE_SAMPLE_F32) {
+        stretcher->table.analyseGrain(grain_engine(stretcher), (const float*)input_data, (intptr_t)channel_stride);
+    } else {
+        const size_t frames = (size_t)stretcher->grain_frames;
+        for (int ch = 0; ch < stretcher->channels; ch++) {
+            bungee_samples_to_float(input_data, format, ch * channel_stride, 1, stretcher->grain + ch * frames, frames);
+        }
+        stretcher->table.analyseGrain(grain_engine(stretcher), stretcher->grain, (intptr_t)frames);
+    }
+    stretcher->stage = GRAIN_ANALYSED;
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Analyses the input audio of the current grain from per-channel buffers
+ *
+ * Each channel is gathered into the stretcher's grain buffer, converting its
+ * format, before being passed to the engine.
+ *
+ * @param stretcher Stretcher instance
+ * @param channel_data Input audio at frame chunk.begin of each channel, in the given format
+ * @param format Sample format of the channel buffers
+ * @param frame_stride Stride between frames in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_analyse_grain_channels(bungee_stretcher_t* stretcher, const void* const* channel_data,
+                                             bungee_sample_format_t format, size_t frame_stride) {
+    if (!stretcher || !channel_data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in analyse_grain: stretcher=%p, channel_data=%p",
+                                (void*)stretcher, (const void*)channel_data);
+    }
+
+    bungee_error_t error = check_grain_format(stretcher, "bungee_analyse_grain", GRAIN_SPECIFIED, format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    const size_t frames = (size_t)stretcher->grain_frames;
+    for (int ch = 0; ch < stretcher->channels; ch++) {
+        if (frames && !channel_data[ch]) {
+            return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in analyse_grain: channel %d", ch);
+        }
+    }
+    for (int ch = 0; ch < stretcher->channels && frames; ch++) {
+        bungee_samples_to_float(channel_data[ch], format, 0, frame_stride, stretcher->grain + ch * frames, frames);
+    }
+    stretcher->table.analyseGrain(grain_engine(stretcher), stretcher->grain, (intptr_t)frames);
+    stretcher->stage = GRAIN_ANALYSED;
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Removes frames from the front of the fade buffer
+ *
+ * @param stretcher Stretcher instance
+ * @param frames Frames to remove; any beyond those buffered are ignored
+ */
+static void fade_consume(bungee_stretcher_t* stretcher, int frames) {
+    const int remaining = stretcher->fade_buffered > frames ? stretcher->fade_buffered - frames : 0;
+    for (int ch = 0; ch < stretcher->channels && remaining > 0; ch++) {
+        float* buffer = stretcher->fade_buffer + (size_t)ch * stretcher->fade_capacity;
+        memmove(buffer, buffer + frames, (size_t)remaining * sizeof(float));
+    }
+    stretcher->fade_buffered = remaining;
+}
+
+/**
+ * @brief Frames of outgoing output still to be buffered that will be heard
+ *
+ * Outgoing output beyond the end of the crossfade, where its gain is zero, is not buffered.
+ *
+ * @param stretcher Stretcher instance, with a seek in progress
+ * @return Frames up to the end of the crossfade beyond those buffered, or INT_MAX before it starts
+ */
+static int fade_heard(const bungee_stretcher_t* stretcher) {
+    if (stretcher->fade != FADE_MIXING) {
+        return INT_MAX;
+    }
+    const int heard = stretcher->fade_frames - stretcher->fade_position - stretcher->fade_buffered;
+    return heard > 0 ? heard : 0;
+}
+
+/**
+ * @brief Routes a grain's output through the crossfade of a seek
+ *
+ * The outgoing chain's output is buffered and output is redirected to nothing. Until the
+ * new chain starts, its grains deliver the buffered output instead of their own; after
+ * that, their output is mixed over the buffered output, frame for frame, with any
+ * shortfall of buffered output silent. Outgoing output is buffered up to fade_heard(),
+ * for which synthesise() has checked there is room.
+ *
+ * @param stretcher Stretcher instance, with a seek in progress
+ * @param output Output of the grain just synthesised, redirected to the frames to deliver
+ * @param available Frames the caller's buffer holds
+ * @return true if output now points into the fade buffer, whose frames are then to be consumed
+ */
+static bool fade(bungee_stretcher_t* stretcher, Bungee_OutputChunk* output, int available) {
+    if (stretcher->outgoing_turn) {
+        const int heard = fade_heard(stretcher);
+        const int frames = output->frameCount < heard ? output->frameCount : heard;
+        for (int ch = 0; ch < stretcher->channels && frames > 0; ch++) {
+            float* buffer = stretcher->fade_buffer + (size_t)ch * stretcher->fade_capacity;
+            memcpy(buffer + stretcher->fade_buffered, output->data + ch * output->channelStride,
+                   (size_t)frames * sizeof(float));
+        }
+        if (stretcher->fade_buffered == 0) {
+            stretcher->fade_request[0] = *output->request[0];
+        }
+        stretcher->fade_request[1] = *output->request[1];
+        stretcher->fade_buffered += frames;
+        output->frameCount = 0;
+        return false;
+    }
+
+    if (stretcher->table.isFlushed(stretcher->implementation)) {
+        /* The new chain has been flushed by the caller: nothing remains to fade into */
+        stretcher->fade = FADE_NONE;
+        stretcher->fade_buffered = 0;
+        return false;
+    }
+
+    if (stretcher->fade == FADE_PRIMING &&
+        (output->frameCount == 0 || isnan(output->request[0]->position))) {
+        if (stretcher->fade_buffered == 0) {
+            output->frameCount = 0;
+            return false;
+        }
+        output->data = stretcher->fade_buffer;
+        output->channelStride = stretcher->fade_capacity;
+        output->frameCount = stretcher->fade_buffered < available ? stretcher->fade_buffered : available;
+        output->request[0] = &stretcher->fade_request[0];
+        output->request[1] = &stretcher->fade_request[1];
+        return true;
+    }
+
+    stretcher->fade = FADE_MIXING;
+    for (int ch = 0; ch < stretcher->channels; ch++) {
+        float* buffer = stretcher->fade_buffer + (size_t)ch * stretcher->fade_capacity;
+        const float* incoming = output->data + ch * output->channelStride;
+        for (int i = 0; i < output->frameCount; i++) {
+            const double x = fmin((stretcher->fade_position + i + 0.5) / stretcher->fade_frames, 1.0);
+            const float outgoing = i < stretcher->fade_buffered ? buffer[i] : 0.0f;
+            buffer[i] = (float)(outgoing * cos(x * M_PI_2) + incoming[i] * sin(x * M_PI_2));
+        }
+    }
+    stretcher->fade_position += output->frameCount;
+    output->data = stretcher->fade_buffer;
+    output->channelStride = stretcher->fade_capacity;
+    return true;
+}
+
+/**
+ * @brief Synthesises the current grain and copies it out, converting its format
+ *
+ * Frame i of channel c is written at index offset + i * step of its buffer, where
+ * the buffer is channel_data[c] with offset 0 if channel_data is given, otherwise
+ * data with offset c.
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output descriptor; frame_count is updated to the frames written
+ * @param data Interleaved output buffer, used when channel_data is NULL
+ * @param channel_data Per-channel output buffers, or NULL
+ * @param format Sample format of the output buffers
+ * @param step Stride between frames in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+static bungee_error_t synthesise(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk, void* data,
+                                 void* const* channel_data, bungee_sample_format_t format, size_t step) {
+    /* Checked before the engine consumes the grain, so that it can be synthesised again into a larger buffer */
+    const int max_output = stretcher->table.maxOutputFrameCount(stretcher->implementation);
+    if (chunk->frame_count < max_output) {
+        return bungee_set_error(BUNGEE_BUFFER_TOO_SMALL, "Buffer too small: available=%d, required=%d",
+                                chunk->frame_count, max_output);
+    }
+    if (stretcher->fade != FADE_NONE && stretcher->outgoing_turn) {
+        const int heard = fade_heard(stretcher);
+        const int required = max_output < heard ? max_output : heard;
+        if (required > stretcher->fade_capacity - stretcher->fade_buffered) {
+            return bungee_set_error(BUNGEE_BUFFER_TOO_SMALL, "Fade buffer too small: buffered=%d, required=%d, capacity=%d",
+                                    stretcher->fade_buffered, required, stretcher->fade_capacity);
+        }
+    }
+
+    Bungee_OutputChunk output;
+    stretcher->table.synthesiseGrain(grain_engine(stretcher), &output);
+    stretcher->stage = GRAIN_SYNTHESISED;
+
+    const bool faded = stretcher->fade != FADE_NONE && fade(stretcher, &output, chunk->frame_count);
+
+    for (int ch = 0; ch < stretcher->channels && output.frameCount > 0; ch++) {
+        const float* source = output.data + ch * output.channelStride;
+        void* destination = channel_data ? channel_data[ch] : data;
+        const size_t offset = channel_data ? 0 : (size_t)ch;
+        bungee_samples_from_float(source, (size_t)output.frameCount, destination, format, offset, step);
+    }
+    chunk->frame_count = output.frameCount;
+    from_engine_request(output.request[0], &chunk->request[0]);
+    from_engine_request(output.request[1], &chunk->request[1]);
+    set_frame_positions(chunk);
+
+    if (faded) {
+        fade_consume(stretcher, output.frameCount);
+        if (stretcher->fade == FADE_MIXING && stretcher->fade_position >= stretcher->fade_frames) {
+            stretcher->fade = FADE_NONE;
+            stretcher->fade_buffered = 0;
+        }
+    }
+
+    BUNGEE_LOG("Grain synthesised: output_frames=%d", output.frameCount);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Synthesises the current grain into a caller-owned buffer
+ *
+ * The engine's output is planar and owned by the engine; it is copied into
+ * chunk->data using the interleaved layout described in bungee_c.h, together
+ * with the requests of the grains that bound the chunk.
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output buffer; frame_count is updated to the frames written
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk) {
+    return bungee_synthesise_grain_format(stretcher, chunk, chunk ? chunk->data : NULL, BUNGEE_SAMPLE_F32);
+}
+
+/**
+ * @brief Synthesises the current grain into a caller-owned buffer of any sample format
+ *
+ * As bungee_synthesise_grain, converting each sample as it is copied into data.
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output descriptor; frame_count is updated to the frames written
+ * @param data Interleaved output buffer in the given format, used in place of chunk->data
+ * @param format Sample format of data
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_synthesise_grain_format(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk, void* data,
+                                              bungee_sample_format_t format) {
+    if (!stretcher || !chunk || !data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in synthesise_grain: stretcher=%p, chunk=%p",
+                                (void*)stretcher, (void*)chunk);
+    }
+
+    bungee_error_t error = check_grain_format(stretcher, "bungee_synthesise_grain", GRAIN_ANALYSED, format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    if (chunk->channel_stride < (size_t)stretcher->channels) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid channel stride: %zu < %d channels",
+                                chunk->channel_stride, stretcher->channels);
+    }
+
+    return synthesise(stretcher, chunk, data, NULL, format, chunk->channel_stride);
+}
+
+/**
+ * @brief Synthesises the current grain into per-channel caller-owned buffers
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output descriptor; frame_count is updated to the frames written
+ * @param channel_data Output buffer of each channel, in the given format
+ * @param format Sample format of the channel buffers
+ * @param frame_stride Stride between frames in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_synthesise_grain_channels(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk,
+                                                void* const* channel_data, bungee_sample_format_t format,
+                                                size_t frame_stride) {
+    if (!stretcher || !chunk || !channel_data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in synthesise_grain: stretcher=%p, chunk=%p",
+                                (void*)stretcher, (void*)chunk);
+    }
+
+    bungee_error_t error = check_grain_format(stretcher, "bungee_synthesise_grain", GRAIN_ANALYSED, format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    for (int ch = 0; ch < stretcher->channels; ch++) {
+        if (!channel_data[ch]) {
+            return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in synthesise_grain: channel %d", ch);
+        }
+    }
+
+    if (frame_stride == 0) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid frame stride: 0");
+    }
+
+    return synthesise(stretcher, chunk, NULL, channel_data, format, frame_stride);
+}
+
+/**
+ * @brief Advances to the next grain
+ *
+ * The caller's request (which may carry new speed or pitch values) becomes the
+ * current request and its position is advanced according to its speed.
+ *
+ * @param stretcher Stretcher instance
+ * @param request Request parameters to update
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_next(bungee_stretcher_t* stretcher, bungee_request_t* request) {
+    if (!stretcher || !request) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in next: stretcher=%p, request=%p",
+                                (void*)stretcher, (void*)request);
+    }
+
+    if (stretcher->stage == GRAIN_SPECIFIED || stretcher->stage == GRAIN_ANALYSED) {
+        return bungee_set_error(BUNGEE_GRAIN_OUT_OF_ORDER, "bungee_next called while grain is %s, expected %s",
+                                grain_stage_name(stretcher->stage), grain_stage_name(GRAIN_SYNTHESISED));
+    }
+
+    bungee_error_t error = check_pitch(stretcher, "bungee_next", request->pitch);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    if (stretcher->outgoing_turn) {
+        /* The outgoing chain follows the caller's speed and pitch; the new chain has yet to use its request */
+        stretcher->outgoing_request.speed = request->speed;
+        stretcher->outgoing_request.pitch = request->pitch;
+        stretcher->outgoing_request.preserveFormants = request->preserve_formants;
+        stretcher->table.next(stretcher->outgoing, &stretcher->outgoing_request);
+        const bool reset = stretcher->request.reset;
+        stretcher->request = to_engine_request(request);
+        stretcher->request.reset = stretcher->request.reset || reset;
+        stretcher->outgoing_turn = false;
+        stretcher->stage = GRAIN_READY;
+        BUNGEE_LOG("Advanced outgoing chain: position=%f", stretcher->outgoing_request.position);
+        return BUNGEE_OK;
+    }
+
+    stretcher->request = to_engine_request(request);
+    stretcher->table.next(stretcher->implementation, &stretcher->request);
+    stretcher->stage = GRAIN_READY;
+    stretcher->outgoing_turn = stretcher->fade != FADE_NONE;
+    from_engine_request(&stretcher->request, request);
+
+    BUNGEE_LOG("Advanced to next grain: position=%f", request->position);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Grows the fade buffer to hold a crossfade of outgoing output and one grain more
+ *
+ * Frames already buffered are kept.
+ *
+ * @param stretcher Stretcher instance
+ * @param crossfade_frames Length of the crossfade in output frames
+ * @return BUNGEE_OK on success, BUNGEE_INVALID_PARAM or BUNGEE_MEMORY otherwise
+ */
+static bungee_error_t reserve_fade(bungee_stretcher_t* stretcher, int crossfade_frames) {
+    const int max_output = stretcher->table.maxOutputFrameCount(stretcher->implementation);
+    if (crossfade_frames > INT_MAX - max_output) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid crossfade: %d frames", crossfade_frames);
+    }
+    const int capacity = crossfade_frames + max_output;
+    if (capacity <= stretcher->fade_capacity) {
+        return BUNGEE_OK;
+    }
+
+    const size_t buffer_bytes = (size_t)capacity * stretcher->channels * sizeof(float);
+    float* buffer = (float*)malloc(buffer_bytes);
+    if (!buffer) {
+        return bungee_set_error(BUNGEE_MEMORY, "Failed to allocate fade buffer: %zu bytes", buffer_bytes);
+    }
+    for (int ch = 0; ch < stretcher->channels && stretcher->fade_buffered > 0; ch++) {
+        memcpy(buffer + (size_t)ch * capacity, stretcher->fade_buffer + (size_t)ch * stretcher->fade_capacity,
+               (size_t)stretcher->fade_buffered * sizeof(float));
+    }
+    free(stretcher->fade_buffer);
+    stretcher->fade_buffer = buffer;
+    stretcher->fade_capacity = capacity;
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Creates the engine that runs the outgoing chain of a seek
+ *
+ * @param stretcher Stretcher instance without an outgoing engine
+ * @return BUNGEE_OK on success, BUNGEE_MEMORY otherwise
+ */
+static bungee_error_t create_outgoing(bungee_stretcher_t* stretcher) {
+    stretcher->outgoing = stretcher->table.create(stretcher->sample_rates, stretcher->channels,
+                                                  stretcher->log2_synthesis_hop, stretcher->max_pitch_octaves,
+                                                  stretcher->custom_fft ? &stretcher->fourier : NULL,
+                                                  &stretcher->window);
+    if (!stretcher->outgoing) {
+        return bungee_set_error(BUNGEE_MEMORY, "Failed to create engine instance for seek");
+    }
+    configure_engine(stretcher, stretcher->outgoing);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Moves playback to a new position, crossfading from the current one
+ *
+ * The chain of grains being left carries on in the outgoing engine while a new chain,
+ * prerolled at the request's position, fills the main engine from silence. Grains then
+ * alternate between the chains until crossfade_frames of the new chain's output have
+ * been mixed over the old.
+ *
+ * @param stretcher Stretcher instance
+ * @param request Request at the new position, updated in place as by bungee_preroll
+ * @param crossfade_frames Length of the crossfade in output frames; 0 cuts at once
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_seek(bungee_stretcher_t* stretcher, bungee_request_t* request, int32_t crossfade_frames) {
+    if (!stretcher || !request) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in seek: stretcher=%p, request=%p",
+                                (void*)stretcher, (void*)request);
+    }
+
+    if (stretcher->stage == GRAIN_SPECIFIED || stretcher->stage == GRAIN_ANALYSED) {
+        return bungee_set_error(BUNGEE_GRAIN_OUT_OF_ORDER, "bungee_seek called while grain is %s, expected %s",
+                                grain_stage_name(stretcher->stage), grain_stage_name(GRAIN_SYNTHESISED));
+    }
+
+    bungee_error_t error = check_pitch(stretcher, "bungee_seek", request->pitch);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    if (crossfade_frames < 0) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid crossfade: %d frames", (int)crossfade_frames);
+    }
+
+    if (crossfade_frames == 0) {
+        return bungee_preroll(stretcher, request);
+    }
+
+    error = reserve_fade(stretcher, crossfade_frames);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    if (!stretcher->outgoing) {
+        error = create_outgoing(stretcher);
+        if (error != BUNGEE_OK) {
+            return error;
+        }
+    }
+
+    const bool synthesised = stretcher->stage == GRAIN_SYNTHESISED;
+    if (stretcher->fade == FADE_PRIMING) {
+        /* The new chain is not yet heard: keep the outgoing chain and prime afresh */
+        if (synthesised && stretcher->outgoing_turn) {
+            stretcher->outgoing_request.speed = request->speed;
+            stretcher->outgoing_request.pitch = request->pitch;
+            stretcher->outgoing_request.preserveFormants = request->preserve_formants;
+            stretcher->table.next(stretcher->outgoing, &stretcher->outgoing_request);
+            stretcher->outgoing_turn = false;
+        } else if (synthesised) {
+            stretcher->outgoing_turn = true;
+        }
+    } else {
+        /* The chain playing, or being faded in, becomes the outgoing chain */
+        Bungee_Request continuing = stretcher->request;
+        continuing.speed = request->speed;
+        continuing.pitch = request->pitch;
+        continuing.preserveFormants = request->preserve_formants;
+        if (synthesised && !stretcher->outgoing_turn) {
+            stretcher->table.next(stretcher->implementation, &continuing);
+        }
+        void* implementation = stretcher->outgoing;
+        stretcher->outgoing = stretcher->implementation;
+        stretcher->implementation = implementation;
+        stretcher->outgoing_request = continuing;
+        stretcher->outgoing_turn = true;
+        stretcher->fade_buffered = 0;
+    }
+
+    flush_engine(stretcher, stretcher->implementation);
+    stretcher->request = to_engine_request(request);
+    stretcher->table.preroll(stretcher->implementation, &stretcher->request);
+    stretcher->stage = GRAIN_READY;
+    stretcher->fade = FADE_PRIMING;
+    stretcher->fade_frames = crossfade_frames;
+    stretcher->fade_position = 0;
+
+    BUNGEE_LOG("Seek: position=%f -> %f, crossfade_frames=%d, outgoing position=%f", request->position,
+               stretcher->request.position, (int)crossfade_frames, stretcher->outgoing_request.position);
+    from_engine_request(&stretcher->request, request);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Sets the automation envelope of speed or pitch
+ *
+ * Breakpoints are validated here and then copied into the engine, which applies
+ * the envelope to the request in its preroll and next functions.
+ *
+ * @param stretcher Stretcher instance
+ * @param parameter Request field to automate
+ * @param time_base Clock on which breakpoint times are measured
+ * @param breakpoints Breakpoints in non-decreasing time order (may be NULL if count is 0)
+ * @param count Number of breakpoints; 0 removes the envelope
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_set_automation(bungee_stretcher_t* stretcher, bungee_parameter_t parameter,
+                                     bungee_time_base_t time_base, const bungee_breakpoint_t* breakpoints,
+                                     size_t count) {
+    if (!stretcher || (count && !breakpoints)) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_automation: stretcher=%p, breakpoints=%p",
+                                (void*)stretcher, (const void*)breakpoints);
+    }
+
+    if ((unsigned)parameter > BUNGEE_PARAMETER_PITCH || (unsigned)time_base > BUNGEE_TIME_BASE_OUTPUT ||
+        count > INT_MAX) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid automation: parameter=%d, time_base=%d, count=%zu",
+                                (int)parameter, (int)time_base, count);
+    }
+
+    Bungee_Breakpoint* points = NULL;
+    if (count) {
+        points = (Bungee_Breakpoint*)malloc(count * sizeof(Bungee_Breakpoint));
+        if (!points) {
+            return bungee_set_error(BUNGEE_MEMORY, "Failed to allocate %zu breakpoints", count);
+        }
+    }
+
+    for (size_t i = 0; i < count; i++) {
+        const bungee_breakpoint_t* b = &breakpoints[i];
+        bool valid = isfinite(b->time) && isfinite(b->value) && (unsigned)b->curve <= BUNGEE_CURVE_EXPONENTIAL;
+        if (i > 0 && b->time < breakpoints[i - 1].time) {
+            valid = false;
+        }
+        if (!valid) {
+            free(points);
+            return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid breakpoint %zu: time=%f, value=%f, curve=%d",
+                                    i, b->time, b->value, (int)b->curve);
+        }
+        if (parameter == BUNGEE_PARAMETER_PITCH) {
+            bungee_error_t error = check_pitch(stretcher, "bungee_set_automation", b->value);
+            if (error != BUNGEE_OK) {
+                free(points);
+                return error;
+            }
+        }
+        points[i].time = b->time;
+        points[i].value = b->value;
+        points[i].curve = (Bungee_Curve)b->curve;
+    }
+
+    stretcher->table.setAutomation(stretcher->implementation, (Bungee_Parameter)parameter,
+                                   (Bungee_TimeBase)time_base, points, (int)count);
+    if (stretcher->outgoing) {
+        stretcher->table.setAutomation(stretcher->outgoing, (Bungee_Parameter)parameter,
+                                       (Bungee_TimeBase)time_base, points, (int)count);
+    }
+    free(stretcher->settings.automation[parameter]);
+    stretcher->settings.automation[parameter] = points;
+    stretcher->settings.automation_count[parameter] = (int)count;
+    stretcher->settings.automation_time_base[parameter] = (Bungee_TimeBase)time_base;
+
+    BUNGEE_LOG("Automation set: parameter=%d, time_base=%d, breakpoints=%zu",
+           (int)parameter, (int)time_base, count);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Selects where the engine resamples
+ *
+ * The mode applies from the next call to bungee_preroll, bungee_specify_grain or
+ * bungee_next, so it may be changed between grains.
+ *
+ * @param stretcher Stretcher instance
+ * @param mode Resample mode
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_set_resample_mode(bungee_stretcher_t* stretcher, bungee_resample_mode_t mode) {
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_resample_mode");
+    }
+
+    if ((unsigned)mode > BUNGEE_RESAMPLE_FORCE_IN) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid resample mode: %d", (int)mode);
+    }
+
+    stretcher->settings.resample_mode = (Bungee_ResampleMode)mode;
+    stretcher->table.setResampleMode(stretcher->implementation, stretcher->settings.resample_mode);
+    if (stretcher->outgoing) {
+        stretcher->table.setResampleMode(stretcher->outgoing, stretcher->settings.resample_mode);
+    }
+
+    BUNGEE_LOG("Resample mode set: %d", (int)mode);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Selects the interpolation kernel used for resampling
+ *
+ * @param stretcher Stretcher instance
+ * @param interpolation Interpolation kernel
+ * @param sinc_taps Windowed-sinc length: 4, 8, 16 or 32 (ignored by other kernels)
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_set_interpolation(bungee_stretcher_t* stretcher, bungee_interpolation_t interpolation,
+                                        int sinc_taps) {
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_interpolation");
+    }
+
+    if ((unsigned)interpolation > BUNGEE_INTERPOLATION_WINDOWED_SINC) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid interpolation: %d", (int)interpolation);
+    }
+
+    if (interpolation == BUNGEE_INTERPOLATION_WINDOWED_SINC &&
+        sinc_taps != 4 && sinc_taps != 8 && sinc_taps != 16 && sinc_taps != 32) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid sinc taps: %d, expected 4, 8, 16 or 32", sinc_taps);
+    }
+
+    stretcher->settings.interpolation = (Bungee_Interpolation)interpolation;
+    stretcher->settings.sinc_taps = sinc_taps;
+    stretcher->table.setInterpolation(stretcher->implementation, stretcher->settings.interpolation, sinc_taps);
+    if (stretcher->outgoing) {
+        stretcher->table.setInterpolation(stretcher->outgoing, stretcher->settings.interpolation, sinc_taps);
+    }
+
+    BUNGEE_LOG("Interpolation set: %d, sinc_taps=%d", (int)interpolation, sinc_taps);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Sets the sensitivity of transient detection
+ *
+ * @param stretcher Stretcher instance
+ * @param sensitivity From 0 (detection off) to 1 (most sensitive)
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_set_transient_sensitivity(bungee_stretcher_t* stretcher, float sensitivity) {
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_transient_sensitivity");
+    }
+
+    if (!(sensitivity >= 0.0f && sensitivity <= 1.0f)) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Transient sensitivity %f outside 0..1", sensitivity);
+    }
+
+    stretcher->settings.transient_sensitivity = sensitivity;
+    stretcher->table.setTransientSensitivity(stretcher->implementation, sensitivity);
+    if (stretcher->outgoing) {
+        stretcher->table.setTransientSensitivity(stretcher->outgoing, sensitivity);
+    }
+
+    BUNGEE_LOG("Transient sensitivity set: %f", sensitivity);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Selects how channel phases are coupled, from the next preroll or reset request
+ *
+ * @param stretcher Stretcher instance
+ * @param coupling Channel coupling mode; BUNGEE_CHANNEL_COUPLING_MID_SIDE requires two channels
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_set_channel_coupling(bungee_stretcher_t* stretcher, bungee_channel_coupling_t coupling) {
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_channel_coupling");
+    }
+
+    if ((unsigned)coupling > BUNGEE_CHANNEL_COUPLING_INDEPENDENT) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid channel coupling: %d", (int)coupling);
+    }
+
+    if (coupling == BUNGEE_CHANNEL_COUPLING_MID_SIDE && stretcher->channels != 2) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Mid/side coupling requires 2 channels, not %d",
+                                stretcher->channels);
+    }
+
+    stretcher->settings.channel_coupling = (Bungee_ChannelCoupling)coupling;
+    stretcher->table.setChannelCoupling(stretcher->implementation, stretcher->settings.channel_coupling);
+    if (stretcher->outgoing) {
+        stretcher->table.setChannelCoupling(stretcher->outgoing, stretcher->settings.channel_coupling);
+    }
+
+    BUNGEE_LOG("Channel coupling set: %d", (int)coupling);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Checks if all grains have been flushed from the engine's pipeline
+ *
+ * A seek's crossfade counts as output still to come until it completes.
+ *
+ * @param stretcher Stretcher instance
+ * @return true if no valid grain remains in the pipeline and no crossfade is in progress
+ */
+bool bungee_is_flushed(const bungee_stretcher_t* stretcher) {
+    if (!stretcher) {
+        return true;
+    }
+    if (stretcher->fade != FADE_NONE || stretcher->fade_buffered > 0) {
+        return false;
+    }
+    return stretcher->table.isFlushed(stretcher->implementation);
+}
+
+/**
+ * @brief Gets the maximum number of input frames a grain can require
+ *
+ * @param stretcher Stretcher instance
+ * @return Maximum number of input frames, or 0 if stretcher is NULL
+ */
+size_t bungee_max_input_frame_count(const bungee_stretcher_t* stretcher) {
+    if (!stretcher) {
+        return 0;
+    }
+    return (size_t)stretcher->table.maxInputFrameCount(stretcher->implementation);
+}
+
+/**
+ * @brief Gets the maximum number of output frames a single grain can produce
+ *
+ * @param stretcher Stretcher instance
+ * @return Maximum number of output frames, or 0 if stretcher is NULL
+ */
+size_t bungee_max_output_frame_count(const bungee_stretcher_t* stretcher) {
+    if (!stretcher) {
+        return 0;
+    }
+    return (size_t)stretcher->table.maxOutputFrameCount(stretcher->implementation);
+}
+
+/**
+ * @brief Reports the latency of the stretcher at the pitch of its latest request
+ *
+ * @param stretcher Stretcher instance
+ * @return Input, output and control latency in frames, or zeros if stretcher is NULL
+ */
+bungee_latency_t bungee_latency(const bungee_stretcher_t* stretcher) {
+    bungee_latency_t latency = {0, 0, 0};
+    if (stretcher) {
+        Bungee_Latency engine = stretcher->table.latency(stretcher->implementation, stretcher->request.pitch);
+        latency.input = engine.input;
+        latency.output = engine.output;
+        latency.control = engine.control;
+    }
+    return latency;
+}
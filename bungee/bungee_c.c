@@ -5,9 +5,15 @@
  * This file provides a minimal, pure C implementation of audio time-stretching
  * functionality using a grain-based processing approach. The implementation
  * uses only standard C library features and avoids any C++ dependencies.
+ *
+ * This is a fallback, built only with the `c-stub` cargo feature. The default
+ * build uses bungee_basic.c, which drives the phase-vocoder engine in src/.
  */
 
 #include "bungee_c.h"
+#include "bungee_error.h"
+#include "bungee_window.h"
+#include "bungee_sample.h"
 #include <stdlib.h>
 #include <string.h>
 #include <math.h>
@@ -47,6 +53,26 @@ static void create_hann_window(float* window, size_t size) {
     }
 }
 
+/**
+ * @brief Interpolates the input positions of a chunk's first and last frames
+ *
+ * Output frames are evenly spaced in input position between request[0], at the
+ * first frame, and request[1], one frame past the last.
+ *
+ * @param chunk Output chunk with frame_count and request already set
+ */
+static void set_frame_positions(bungee_output_chunk_t* chunk) {
+    double begin = chunk->request[0].position;
+    double end = chunk->request[1].position;
+    if (chunk->frame_count <= 0 || isnan(begin) || isnan(end)) {
+        chunk->first_position = NAN;
+        chunk->last_position = NAN;
+        return;
+    }
+    chunk->first_position = begin;
+    chunk->last_position = begin + (end - begin) * (chunk->frame_count - 1) / chunk->frame_count;
+}
+
 /**
  * @brief Initialize the Bungee library
  *
@@ -72,23 +98,63 @@ void bungee_cleanup(void) {
  * @return Stretcher instance or NULL on error
  */
 bungee_stretcher_t* bungee_create(bungee_sample_rates_t rates, int channels) {
-    BUNGEE_LOG("Creating stretcher: input_rate=%d, output_rate=%d, channels=%d",
-           rates.input_rate, rates.output_rate, channels);
+    return bungee_create_with_config(rates, channels, NULL);
+}
+
+/**
+ * @brief Creates a new stretcher instance with creation options
+ *
+ * The stub validates the options but its fixed windowing does not use them.
+ *
+ * @param rates Sample rate configuration
+ * @param channels Number of audio channels
+ * @param config Creation options, or NULL for the defaults
+ * @return Stretcher instance or NULL on error
+ */
+bungee_stretcher_t* bungee_create_with_config(bungee_sample_rates_t rates, int channels, const bungee_config_t* config) {
+    const int log2_synthesis_hop = config ? config->log2_synthesis_hop : 0;
+    const int max_pitch_octaves = config ? config->max_pitch_octaves : 0;
+
+    BUNGEE_LOG("Creating stretcher: input_rate=%d, output_rate=%d, channels=%d, log2_synthesis_hop=%d",
+           rates.input_rate, rates.output_rate, channels, log2_synthesis_hop);
 
     if (channels <= 0) {
-        BUNGEE_LOG("Invalid channel count: %d", channels);
+        bungee_set_error(BUNGEE_INVALID_CHANNEL_COUNT, "Invalid channel count: %d", channels);
+        return NULL;
+    }
+
+    if (rates.input_rate < BUNGEE_MIN_SAMPLE_RATE || rates.input_rate > BUNGEE_MAX_SAMPLE_RATE ||
+        rates.output_rate < BUNGEE_MIN_SAMPLE_RATE || rates.output_rate > BUNGEE_MAX_SAMPLE_RATE) {
+        bungee_set_error(BUNGEE_RATE_OUT_OF_RANGE, "Sample rates input=%d, output=%d outside %d..%d Hz",
+                         rates.input_rate, rates.output_rate, BUNGEE_MIN_SAMPLE_RATE, BUNGEE_MAX_SAMPLE_RATE);
+        return NULL;
+    }
+
+    if (log2_synthesis_hop != 0 &&
+        (log2_synthesis_hop < BUNGEE_MIN_LOG2_SYNTHESIS_HOP || log2_synthesis_hop > BUNGEE_MAX_LOG2_SYNTHESIS_HOP)) {
+        bungee_set_error(BUNGEE_INVALID_PARAM, "log2_synthesis_hop %d outside %d..%d", log2_synthesis_hop,
+                         BUNGEE_MIN_LOG2_SYNTHESIS_HOP, BUNGEE_MAX_LOG2_SYNTHESIS_HOP);
         return NULL;
     }
 
-    if (rates.input_rate <= 0 || rates.output_rate <= 0) {
-        BUNGEE_LOG("Invalid sample rates: input=%d, output=%d",
-               rates.input_rate, rates.output_rate);
+    if (max_pitch_octaves < 0 || max_pitch_octaves > BUNGEE_MAX_PITCH_OCTAVES) {
+        bungee_set_error(BUNGEE_INVALID_PARAM, "max_pitch_octaves %d outside 1..%d", max_pitch_octaves,
+                         BUNGEE_MAX_PITCH_OCTAVES);
+        return NULL;
+    }
+
+    if (config && config->fft) {
+        bungee_set_error(BUNGEE_INVALID_STATE, "FFT backends require the Bungee::Basic engine");
+        return NULL;
+    }
+
+    if (bungee_check_window(config ? config->window : NULL) != BUNGEE_OK) {
         return NULL;
     }
 
     bungee_stretcher_t* stretcher = (bungee_stretcher_t*)malloc(sizeof(bungee_stretcher_t));
     if (!stretcher) {
-        BUNGEE_LOG_SIMPLE("Failed to allocate stretcher");
+        bungee_set_error(BUNGEE_MEMORY, "Failed to allocate stretcher");
         return NULL;
     }
 
@@ -113,7 +179,7 @@ bungee_stretcher_t* bungee_create(bungee_sample_rates_t rates, int channels) {
     size_t buffer_bytes = stretcher->buffer_size * channels * sizeof(float);
     stretcher->input_buffer = (float*)malloc(buffer_bytes);
     if (!stretcher->input_buffer) {
-        BUNGEE_LOG("Failed to allocate input buffer: %zu bytes", buffer_bytes);
+        bungee_set_error(BUNGEE_MEMORY, "Failed to allocate input buffer: %zu bytes", buffer_bytes);
         free(stretcher);
         return NULL;
     }
@@ -121,8 +187,8 @@ bungee_stretcher_t* bungee_create(bungee_sample_rates_t rates, int channels) {
     /* Create window function */
     stretcher->window_buffer = (float*)malloc(stretcher->window_size * sizeof(float));
     if (!stretcher->window_buffer) {
-        BUNGEE_LOG("Failed to allocate window buffer: %zu bytes",
-               stretcher->window_size * sizeof(float));
+        bungee_set_error(BUNGEE_MEMORY, "Failed to allocate window buffer: %zu bytes",
+                         stretcher->window_size * sizeof(float));
         free(stretcher->input_buffer);
         free(stretcher);
         return NULL;
@@ -163,9 +229,10 @@ void bungee_destroy(bungee_stretcher_t* stretcher) {
  * @param request New processing parameters
  * @return BUNGEE_OK on success, error code otherwise
  */
-bungee_error_t bungee_preroll(bungee_stretcher_t* stretcher, const bungee_request_t* request) {
+bungee_error_t bungee_preroll(bungee_stretcher_t* stretcher, bungee_request_t* request) {
     if (!stretcher || !request) {
-        return BUNGEE_NULL_POINTER;
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in preroll: stretcher=%p, request=%p",
+                                (void*)stretcher, (void*)request);
     }
 
     stretcher->position = request->position;
@@ -174,7 +241,7 @@ bungee_error_t bungee_preroll(bungee_stretcher_t* stretcher, const bungee_reques
     
     if (request->reset) {
         memset(stretcher->input_buffer, 0, stretcher->buffer_size * stretcher->channels * sizeof(float));
-        stretcher->is_flushed = true;
+        stretcher->is_flushed = isnan(request->position);
     }
 
     return BUNGEE_OK;
@@ -184,146 +251,195 @@ bungee_error_t bungee_preroll(bungee_stretcher_t* stretcher, const bungee_reques
  * @brief Specifies the next grain to process
  *
  * Calculates grain boundaries based on current position and window size.
+ * Playing backwards, the grain ends at the current position; once flushed
+ * (position NaN) the grain is empty.
  *
  * @param stretcher Stretcher instance
- * @param input_data Input audio data
- * @param frame_count Total number of input frames
  * @param chunk Output chunk parameters
  * @return BUNGEE_OK on success, error code otherwise
  */
-bungee_error_t bungee_specify_grain(bungee_stretcher_t* stretcher, const float* input_data, 
-                                   size_t frame_count, bungee_input_chunk_t* chunk) {
-    if (!stretcher || !input_data || !chunk) {
-        BUNGEE_LOG("Null pointer in specify_grain: stretcher=%p, input_data=%p, chunk=%p",
-               (void*)stretcher, (void*)input_data, (void*)chunk);
-        return BUNGEE_NULL_POINTER;
+bungee_error_t bungee_specify_grain(bungee_stretcher_t* stretcher, bungee_input_chunk_t* chunk) {
+    if (!stretcher || !chunk) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in specify_grain: stretcher=%p, chunk=%p",
+                                (void*)stretcher, (void*)chunk);
     }
 
-    BUNGEE_LOG("Specify grain start: position=%f, window_size=%zu, frame_count=%zu",
-           stretcher->position, stretcher->window_size, frame_count);
-
-    if (frame_count > stretcher->buffer_size) {
-        BUNGEE_LOG("Buffer too small: requested=%zu, available=%zu",
-               frame_count, stretcher->buffer_size);
-        return BUNGEE_BUFFER_TOO_SMALL;
-    }
+    BUNGEE_LOG("Specify grain start: position=%f, window_size=%zu",
+           stretcher->position, stretcher->window_size);
 
     /* Calculate grain boundaries */
-    chunk->begin = (int32_t)stretcher->position;
-    chunk->end = chunk->begin + (int32_t)stretcher->window_size;
-    
-    BUNGEE_LOG("Initial grain boundaries: begin=%d, end=%d", chunk->begin, chunk->end);
-
-    /* Adjust for frame count */
-    if (chunk->end > (int32_t)frame_count) {
-        BUNGEE_LOG("Adjusting end boundary: old=%d, new=%d", 
-               chunk->end, (int32_t)frame_count);
-        chunk->end = (int32_t)frame_count;
-    }
-
-    /* Calculate copy size */
-    size_t copy_size = (chunk->end - chunk->begin) * stretcher->channels * sizeof(float);
-    BUNGEE_LOG("Copy parameters: begin_sample=%d, channels=%d, copy_size=%zu bytes",
-           chunk->begin, stretcher->channels, copy_size);
-
-    /* Copy input data */
-    if (chunk->begin >= 0 && chunk->end > chunk->begin) {
-        memcpy(stretcher->input_buffer, 
-               input_data + (chunk->begin * stretcher->channels), 
-               copy_size);
-        BUNGEE_LOG("Copied %zu bytes from input position %d", 
-               copy_size, chunk->begin * stretcher->channels);
+    if (isnan(stretcher->position)) {
+        chunk->begin = chunk->end = 0;
+    } else if (stretcher->speed < 0.0) {
+        chunk->end = (int32_t)stretcher->position;
+        chunk->begin = chunk->end - (int32_t)stretcher->window_size;
     } else {
-        BUNGEE_LOG_SIMPLE("No data copied: invalid boundaries");
+        chunk->begin = (int32_t)stretcher->position;
+        chunk->end = chunk->begin + (int32_t)stretcher->window_size;
     }
 
-    BUNGEE_LOG("Grain specification complete: begin=%d, end=%d", 
+    BUNGEE_LOG("Grain specification complete: begin=%d, end=%d",
            chunk->begin, chunk->end);
     return BUNGEE_OK;
 }
 
 /**
- * @brief Analyzes a grain of audio data
+ * @brief Windows a grain of input audio of any format into the input buffer
  *
- * Applies the window function to the input data for analysis.
+ * Sample i of channel c is read from channel_data[c] at index i * frame_stride
+ * if channel_data is given, otherwise from data at c * channel_stride + i * frame_stride.
  *
  * @param stretcher Stretcher instance
- * @param input_data Input audio data
- * @param channel_stride Stride between channels in samples
- * @return BUNGEE_OK on success, error code otherwise
+ * @param data Planar or interleaved input, used when channel_data is NULL
+ * @param channel_data Per-channel input buffers, or NULL
+ * @param format Sample format of the input
+ * @param channel_stride Stride between channels of data in samples
+ * @param frame_stride Stride between frames in samples
  */
-bungee_error_t bungee_analyse_grain(bungee_stretcher_t* stretcher, const float* input_data, size_t channel_stride) {
-    if (!stretcher || !input_data) {
-        BUNGEE_LOG("Null pointer in analyse_grain: stretcher=%p, input_data=%p",
-               (void*)stretcher, (void*)input_data);
-        return BUNGEE_NULL_POINTER;
-    }
-
+static void analyse(bungee_stretcher_t* stretcher, const void* data, const void* const* channel_data,
+                    bungee_sample_format_t format, size_t channel_stride, size_t frame_stride) {
     BUNGEE_LOG("Analyse grain start: window_size=%zu, channels=%d, stride=%zu",
            stretcher->window_size, stretcher->channels, channel_stride);
 
-    /* Check for non-zero input */
-    int has_nonzero = 0;
-    for (size_t i = 0; i < stretcher->window_size * stretcher->channels; i++) {
-        if (input_data[i] != 0.0f) {
-            has_nonzero = 1;
-            break;
-        }
+    if (isnan(stretcher->position)) {
+        BUNGEE_LOG_SIMPLE("Flushed: no grain to analyse");
+        return;
     }
-    BUNGEE_LOG("Input data check: contains_nonzero=%d", has_nonzero);
 
     /* Apply window function to input data */
     for (int ch = 0; ch < stretcher->channels; ch++) {
         BUNGEE_LOG("Processing channel %d", ch);
         float max_sample = 0.0f;
-        
+        const void* source = channel_data ? channel_data[ch] : data;
+        const size_t offset = channel_data ? 0 : ch * channel_stride;
+
         for (size_t i = 0; i < stretcher->window_size; i++) {
             size_t idx = i * stretcher->channels + ch;
-            float windowed = input_data[idx] * stretcher->window_buffer[i];
+            float sample;
+            bungee_samples_to_float(source, format, offset + i * frame_stride, 1, &sample, 1);
+            float windowed = sample * stretcher->window_buffer[i];
             stretcher->input_buffer[idx] = windowed;
-            
+
             /* Track maximum amplitude for debugging */
             float abs_val = fabsf(windowed);
             if (abs_val > max_sample) max_sample = abs_val;
         }
-        
+
         BUNGEE_LOG("Channel %d max amplitude: %f", ch, max_sample);
     }
 
     BUNGEE_LOG_SIMPLE("Analysis complete");
+}
+
+/**
+ * @brief Analyzes a grain of audio data
+ *
+ * Applies the window function to the input data for analysis.
+ *
+ * @param stretcher Stretcher instance
+ * @param input_data Input audio data, planar
+ * @param channel_stride Stride between channels in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_analyse_grain(bungee_stretcher_t* stretcher, const float* input_data, size_t channel_stride) {
+    return bungee_analyse_grain_format(stretcher, input_data, BUNGEE_SAMPLE_F32, channel_stride);
+}
+
+/**
+ * @brief Analyzes a grain of audio data of any sample format
+ *
+ * As bungee_analyse_grain, converting each sample to float as it is read.
+ *
+ * @param stretcher Stretcher instance
+ * @param input_data Input audio data, planar, in the given format
+ * @param format Sample format of input_data
+ * @param channel_stride Stride between channels in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_analyse_grain_format(bungee_stretcher_t* stretcher, const void* input_data,
+                                           bungee_sample_format_t format, size_t channel_stride) {
+    if (!stretcher || !input_data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in analyse_grain: stretcher=%p, input_data=%p",
+                                (void*)stretcher, input_data);
+    }
+
+    bungee_error_t error = bungee_check_sample_format(format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    analyse(stretcher, input_data, NULL, format, channel_stride, 1);
     return BUNGEE_OK;
 }
 
 /**
- * @brief Synthesizes a grain of audio data
+ * @brief Analyzes a grain of audio data from per-channel buffers
  *
- * Applies time-stretching and pitch-shifting to the analyzed grain and
- * writes the result to the output buffer.
+ * As bungee_analyse_grain_format with channel c read from channel_data[c].
  *
  * @param stretcher Stretcher instance
- * @param chunk Output parameters including destination buffer
+ * @param channel_data Input audio of each channel, in the given format
+ * @param format Sample format of the channel buffers
+ * @param frame_stride Stride between frames in samples
  * @return BUNGEE_OK on success, error code otherwise
  */
-bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk) {
-    if (!stretcher || !chunk || !chunk->data) {
-        BUNGEE_LOG("Null pointer in synthesise_grain: stretcher=%p, chunk=%p", 
-               (void*)stretcher, (void*)chunk);
-        return BUNGEE_NULL_POINTER;
+bungee_error_t bungee_analyse_grain_channels(bungee_stretcher_t* stretcher, const void* const* channel_data,
+                                             bungee_sample_format_t format, size_t frame_stride) {
+    if (!stretcher || !channel_data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in analyse_grain: stretcher=%p, channel_data=%p",
+                                (void*)stretcher, (const void*)channel_data);
+    }
+
+    bungee_error_t error = bungee_check_sample_format(format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    for (int ch = 0; ch < stretcher->channels; ch++) {
+        if (!channel_data[ch]) {
+            return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in analyse_grain: channel %d", ch);
+        }
     }
 
+    analyse(stretcher, NULL, channel_data, format, 0, frame_stride);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Stretches the analysed grain into output buffers of any format
+ *
+ * Frame i of channel c is written to channel_data[c] at index i * step if
+ * channel_data is given, otherwise to data at i * step + c.
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output parameters
+ * @param data Interleaved destination, used when channel_data is NULL
+ * @param channel_data Per-channel destination buffers, or NULL
+ * @param format Sample format of the destination
+ * @param step Stride between frames in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+static bungee_error_t synthesise(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk, void* data,
+                                 void* const* channel_data, bungee_sample_format_t format, size_t step) {
     BUNGEE_LOG("Synthesise grain start: buffer_size=%zu, frame_count=%d, channels=%d",
            stretcher->buffer_size, chunk->frame_count, stretcher->channels);
     BUNGEE_LOG("Window size=%zu, position=%f, speed=%f", 
            stretcher->window_size, stretcher->position, stretcher->speed);
 
     if ((size_t)chunk->frame_count > stretcher->buffer_size) {
-        BUNGEE_LOG("Buffer too small: requested=%d, available=%zu",
-               chunk->frame_count, stretcher->buffer_size);
-        return BUNGEE_BUFFER_TOO_SMALL;
+        return bungee_set_error(BUNGEE_BUFFER_TOO_SMALL, "Buffer too small: requested=%d, available=%zu",
+                                chunk->frame_count, stretcher->buffer_size);
     }
 
     /* Calculate output frame count based on time-stretching */
-    size_t output_frames = (size_t)(stretcher->window_size * fabs(stretcher->speed));
+    const double rate = fabs(stretcher->speed);
+    size_t output_frames = (size_t)(stretcher->window_size * rate);
+    if (rate == 0.0) {
+        output_frames = stretcher->window_size - stretcher->overlap;
+    }
+    if (isnan(stretcher->position)) {
+        output_frames = 0;
+    }
     if (output_frames > (size_t)chunk->frame_count) {
         BUNGEE_LOG("Clamping output frames: %zu -> %d", output_frames, chunk->frame_count);
         output_frames = chunk->frame_count;
@@ -347,7 +463,13 @@ bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_out
         /* Apply time-stretching and window function */
         for (size_t i = 0; i < output_frames; i++) {
             /* Calculate input sample position with time-stretching */
-            float input_pos = (float)i / stretcher->speed;
+            float input_pos = rate == 0.0 ? (float)i : (float)(i / rate);
+            if (stretcher->speed < 0.0) {
+                input_pos = (float)(stretcher->window_size - 2) - input_pos;
+                if (input_pos < 0.0f) {
+                    break;
+                }
+            }
             size_t in_idx = (size_t)input_pos;
             float frac = input_pos - in_idx;
             
@@ -374,8 +496,9 @@ bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_out
             float windowed = interpolated * stretcher->window_buffer[in_idx];
             
             /* Write to output */
-            size_t out_idx = i * chunk->channel_stride + ch;
-            chunk->data[out_idx] = windowed;
+            void* destination = channel_data ? channel_data[ch] : data;
+            size_t out_idx = channel_data ? i * step : i * step + ch;
+            bungee_samples_from_float(&windowed, 1, destination, format, out_idx, 1);
 
             if (i == 0 || i == output_frames/2 || i == output_frames-1) {
                 BUNGEE_LOG("Output sample [%zu]: window=%f, final=%f, out_idx=%zu",
@@ -392,16 +515,102 @@ bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_out
 
     /* Update output frame count */
     chunk->frame_count = (int32_t)output_frames;
+
+    /* The stub has no grain pipeline: the chunk spans the current position onwards */
+    for (int i = 0; i < 2; i++) {
+        chunk->request[i].speed = stretcher->speed;
+        chunk->request[i].pitch = stretcher->pitch;
+        chunk->request[i].reset = false;
+        chunk->request[i].preserve_formants = false;
+    }
+    chunk->request[0].position = stretcher->position;
+    chunk->request[1].position = stretcher->position + output_frames * stretcher->speed;
+    set_frame_positions(chunk);
     BUNGEE_LOG("Synthesis complete: output_frames=%zu", output_frames);
 
     return BUNGEE_OK;
 }
 
+/**
+ * @brief Synthesizes a grain of audio data
+ *
+ * Applies time-stretching and pitch-shifting to the analyzed grain and
+ * writes the result to the output buffer. A negative speed reads the grain
+ * from its end; zero speed repeats the grain's first hop, freezing the sound.
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output parameters including destination buffer
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk) {
+    return bungee_synthesise_grain_format(stretcher, chunk, chunk ? chunk->data : NULL, BUNGEE_SAMPLE_F32);
+}
+
+/**
+ * @brief Synthesizes a grain of audio data into a buffer of any sample format
+ *
+ * As bungee_synthesise_grain, converting each sample as it is written to data.
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output parameters
+ * @param data Destination buffer in the given format, used in place of chunk->data
+ * @param format Sample format of data
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_synthesise_grain_format(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk, void* data,
+                                              bungee_sample_format_t format) {
+    if (!stretcher || !chunk || !data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in synthesise_grain: stretcher=%p, chunk=%p",
+                                (void*)stretcher, (void*)chunk);
+    }
+
+    bungee_error_t error = bungee_check_sample_format(format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    return synthesise(stretcher, chunk, data, NULL, format, chunk->channel_stride);
+}
+
+/**
+ * @brief Synthesizes a grain of audio data into per-channel buffers
+ *
+ * As bungee_synthesise_grain_format with channel c written to channel_data[c].
+ *
+ * @param stretcher Stretcher instance
+ * @param chunk Output parameters
+ * @param channel_data Destination buffer of each channel, in the given format
+ * @param format Sample format of the channel buffers
+ * @param frame_stride Stride between frames in samples
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_synthesise_grain_channels(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk,
+                                                void* const* channel_data, bungee_sample_format_t format,
+                                                size_t frame_stride) {
+    if (!stretcher || !chunk || !channel_data) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in synthesise_grain: stretcher=%p, chunk=%p",
+                                (void*)stretcher, (void*)chunk);
+    }
+
+    bungee_error_t error = bungee_check_sample_format(format);
+    if (error != BUNGEE_OK) {
+        return error;
+    }
+
+    for (int ch = 0; ch < stretcher->channels; ch++) {
+        if (!channel_data[ch]) {
+            return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in synthesise_grain: channel %d", ch);
+        }
+    }
+
+    return synthesise(stretcher, chunk, NULL, channel_data, format, frame_stride);
+}
+
 /**
  * @brief Advances to the next grain
  *
- * Updates the stretcher's position based on the current speed and
- * window overlap settings.
+ * Takes speed and pitch from the request and advances its position by one
+ * hop at that speed. A NaN request position flushes the stretcher.
  *
  * @param stretcher Stretcher instance
  * @param request Request parameters to update
@@ -409,32 +618,147 @@ bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_out
  */
 bungee_error_t bungee_next(bungee_stretcher_t* stretcher, bungee_request_t* request) {
     if (!stretcher || !request) {
-        BUNGEE_LOG("Null pointer in next: stretcher=%p, request=%p",
-               (void*)stretcher, (void*)request);
-        return BUNGEE_NULL_POINTER;
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in next: stretcher=%p, request=%p",
+                                (void*)stretcher, (void*)request);
     }
 
     BUNGEE_LOG("Next grain start: position=%f, speed=%f",
            stretcher->position, stretcher->speed);
 
+    stretcher->speed = request->speed;
+    stretcher->pitch = request->pitch;
+
+    /* Check if the caller has reached the end of its input */
+    if (isnan(request->position)) {
+        stretcher->position = NAN;
+        stretcher->is_flushed = true;
+        BUNGEE_LOG_SIMPLE("Reached end of input");
+        return BUNGEE_OK;
+    }
+
     /* Calculate hop size based on window size and overlap */
     double hop_size = (double)(stretcher->window_size - stretcher->overlap);
-    
-    /* Advance position based on speed */
-    stretcher->position += hop_size * stretcher->speed;
+
+    /* Advance position based on speed: backwards when negative, held when zero */
+    stretcher->position = request->position + hop_size * request->speed;
+    stretcher->is_flushed = false;
     request->position = stretcher->position;
-    
+
     BUNGEE_LOG("Advanced position: hop_size=%f, new_position=%f",
            hop_size, stretcher->position);
 
-    /* Check if we've processed all input */
-    if (stretcher->position >= stretcher->buffer_size) {
-        stretcher->is_flushed = true;
-        BUNGEE_LOG("Reached end of input: position=%f, buffer_size=%zu",
-               stretcher->position, stretcher->buffer_size);
+    return BUNGEE_OK;
+}
+
+/**
+ * @brief Moves playback to a new position
+ *
+ * The stub's grains do not overlap, so there is no tail to fade out: the
+ * crossfade length is checked and the move is made at once, as by
+ * bungee_preroll.
+ *
+ * @param stretcher Stretcher instance
+ * @param request Request at the new position
+ * @param crossfade_frames Length of the crossfade in output frames
+ * @return BUNGEE_OK on success, error code otherwise
+ */
+bungee_error_t bungee_seek(bungee_stretcher_t* stretcher, bungee_request_t* request, int32_t crossfade_frames) {
+    if (!stretcher || !request) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in seek: stretcher=%p, request=%p",
+                                (void*)stretcher, (void*)request);
     }
 
-    return BUNGEE_OK;
+    if (crossfade_frames < 0) {
+        return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid crossfade: %d frames", (int)crossfade_frames);
+    }
+
+    return bungee_preroll(stretcher, request);
+}
+
+/**
+ * @brief Automation is not available in the stub
+ *
+ * @param stretcher Stretcher instance
+ * @param parameter Request field to automate
+ * @param time_base Clock on which breakpoint times are measured
+ * @param breakpoints Breakpoints in non-decreasing time order
+ * @param count Number of breakpoints
+ * @return BUNGEE_INVALID_STATE, or BUNGEE_NULL_POINTER if stretcher is NULL
+ */
+bungee_error_t bungee_set_automation(bungee_stretcher_t* stretcher, bungee_parameter_t parameter,
+                                     bungee_time_base_t time_base, const bungee_breakpoint_t* breakpoints,
+                                     size_t count) {
+    (void)parameter;
+    (void)time_base;
+    (void)breakpoints;
+    (void)count;
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_automation");
+    }
+    return bungee_set_error(BUNGEE_INVALID_STATE, "Automation requires the Bungee::Basic engine");
+}
+
+/**
+ * @brief Resample modes are not available in the stub
+ *
+ * @param stretcher Stretcher instance
+ * @param mode Resample mode
+ * @return BUNGEE_INVALID_STATE, or BUNGEE_NULL_POINTER if stretcher is NULL
+ */
+bungee_error_t bungee_set_resample_mode(bungee_stretcher_t* stretcher, bungee_resample_mode_t mode) {
+    (void)mode;
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_resample_mode");
+    }
+    return bungee_set_error(BUNGEE_INVALID_STATE, "Resample modes require the Bungee::Basic engine");
+}
+
+/**
+ * @brief Interpolation kernels are not available in the stub
+ *
+ * @param stretcher Stretcher instance
+ * @param interpolation Interpolation kernel
+ * @param sinc_taps Windowed-sinc length
+ * @return BUNGEE_INVALID_STATE, or BUNGEE_NULL_POINTER if stretcher is NULL
+ */
+bungee_error_t bungee_set_interpolation(bungee_stretcher_t* stretcher, bungee_interpolation_t interpolation,
+                                        int sinc_taps) {
+    (void)interpolation;
+    (void)sinc_taps;
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_interpolation");
+    }
+    return bungee_set_error(BUNGEE_INVALID_STATE, "Interpolation kernels require the Bungee::Basic engine");
+}
+
+/**
+ * @brief Transient detection is not available in the stub
+ *
+ * @param stretcher Stretcher instance
+ * @param sensitivity From 0 (detection off) to 1 (most sensitive)
+ * @return BUNGEE_INVALID_STATE, or BUNGEE_NULL_POINTER if stretcher is NULL
+ */
+bungee_error_t bungee_set_transient_sensitivity(bungee_stretcher_t* stretcher, float sensitivity) {
+    (void)sensitivity;
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_transient_sensitivity");
+    }
+    return bungee_set_error(BUNGEE_INVALID_STATE, "Transient detection requires the Bungee::Basic engine");
+}
+
+/**
+ * @brief Channel coupling modes are not available in the stub
+ *
+ * @param stretcher Stretcher instance
+ * @param coupling Channel coupling mode
+ * @return BUNGEE_INVALID_STATE, or BUNGEE_NULL_POINTER if stretcher is NULL
+ */
+bungee_error_t bungee_set_channel_coupling(bungee_stretcher_t* stretcher, bungee_channel_coupling_t coupling) {
+    (void)coupling;
+    if (!stretcher) {
+        return bungee_set_error(BUNGEE_NULL_POINTER, "Null pointer in set_channel_coupling");
+    }
+    return bungee_set_error(BUNGEE_INVALID_STATE, "Channel coupling modes require the Bungee::Basic engine");
 }
 
 /**
@@ -461,4 +785,35 @@ size_t bungee_max_input_frame_count(const bungee_stretcher_t* stretcher) {
         return 0;
     }
     return stretcher->buffer_size;
-} 
\ No newline at end of file
+}
+
+/**
+ * @brief Gets the maximum number of output frames a single grain can produce
+ *
+ * @param stretcher Stretcher instance
+ * @return Maximum number of output frames, or 0 if stretcher is NULL
+ */
+size_t bungee_max_output_frame_count(const bungee_stretcher_t* stretcher) {
+    if (!stretcher) {
+        return 0;
+    }
+    return stretcher->buffer_size;
+}
+
+/**
+ * @brief Reports the latency of the stretcher
+ *
+ * Each stub grain reads one window ahead of its position and is output at once, so only the
+ * window delays the audio and requests take effect immediately.
+ *
+ * @param stretcher Stretcher instance
+ * @return Input, output and control latency in frames, or zeros if stretcher is NULL
+ */
+bungee_latency_t bungee_latency(const bungee_stretcher_t* stretcher) {
+    bungee_latency_t latency = {0, 0, 0};
+    if (stretcher) {
+        latency.input = (int32_t)stretcher->window_size;
+        latency.output = (int32_t)ceil((double)stretcher->window_size * stretcher->output_rate / stretcher->input_rate);
+    }
+    return latency;
+}
@@ -22,9 +22,25 @@ typedef enum bungee_error {
     BUNGEE_INVALID_PARAM,
     BUNGEE_MEMORY,
     BUNGEE_INVALID_STATE,
-    BUNGEE_BUFFER_TOO_SMALL
+    BUNGEE_BUFFER_TOO_SMALL,
+    BUNGEE_INVALID_CHANNEL_COUNT,  // bungee_create: channels is not positive
+    BUNGEE_RATE_OUT_OF_RANGE,      // bungee_create: a sample rate is outside the range below
+    BUNGEE_GRAIN_OUT_OF_ORDER,     // specify, analyse and synthesise not called in that order
+    BUNGEE_PITCH_OUT_OF_RANGE      // pitch outside the range set by bungee_config_t.max_pitch_octaves
 } bungee_error_t;
 
+// Sample rates accepted by bungee_create, in Hz
+#define BUNGEE_MIN_SAMPLE_RATE 8000
+#define BUNGEE_MAX_SAMPLE_RATE 768000
+
+// Range of bungee_config_t.log2_synthesis_hop, a synthesis hop of 64 to 8192 frames
+#define BUNGEE_MIN_LOG2_SYNTHESIS_HOP 6
+#define BUNGEE_MAX_LOG2_SYNTHESIS_HOP 13
+
+// Pitch range in octaves either side of unity; buffers grow by a factor of two per octave
+#define BUNGEE_DEFAULT_MAX_PITCH_OCTAVES 2
+#define BUNGEE_MAX_PITCH_OCTAVES 6
+
 // Audio configuration
 typedef struct {
     int input_rate;
@@ -37,6 +53,7 @@ typedef struct {
     double speed;     // Output audio speed (1.0 = normal)
     double pitch;     // Pitch adjustment (1.0 = normal)
     bool reset;       // Reset flag
+    bool preserve_formants;  // Keep the spectral envelope in place when pitch shifting
 } bungee_request_t;
 
 // Input/Output chunk descriptors
@@ -45,12 +62,144 @@ typedef struct {
     int32_t end;
 } bungee_input_chunk_t;
 
+// Caller-owned output buffer. On entry frame_count is the capacity of data in frames, at least
+// bungee_max_output_frame_count, otherwise synthesis fails with BUNGEE_BUFFER_TOO_SMALL and the
+// grain may be synthesised again into a larger buffer; on return it is the number of frames written. Frame i of channel c is written to
+// data[i * channel_stride + c], so channel_stride is the channel count for interleaved audio.
+// On return request[0] and request[1] hold the requests of the grains at the chunk's first
+// frame and one past its last frame; a NaN position means no grain is at that end.
+// first_position and last_position are the input positions of the first and last frames,
+// interpolated between the two requests, or NaN if either request position is NaN or the
+// chunk is empty.
 typedef struct {
     float* data;
     int32_t frame_count;
     size_t channel_stride;
+    bungee_request_t request[2];  // 0 = begin, 1 = end
+    double first_position;
+    double last_position;
 } bungee_output_chunk_t;
 
+// Sample formats of bungee_analyse_grain_format input and bungee_synthesise_grain_format output.
+// Integer samples are scaled by their full-scale value to and from the engine's -1..1 floats;
+// out-of-range output is clamped.
+typedef enum bungee_sample_format {
+    BUNGEE_SAMPLE_F32 = 0,  // float, as bungee_analyse_grain and bungee_synthesise_grain
+    BUNGEE_SAMPLE_F64,      // double
+    BUNGEE_SAMPLE_I16,      // int16_t
+    BUNGEE_SAMPLE_I24,      // int32_t holding a 24-bit sample, sign-extended
+    BUNGEE_SAMPLE_I32       // int32_t
+} bungee_sample_format_t;
+
+// Automation envelopes
+typedef enum bungee_curve {
+    BUNGEE_CURVE_HOLD = 0,     // Keep the breakpoint's value until the next breakpoint
+    BUNGEE_CURVE_LINEAR,
+    BUNGEE_CURVE_EXPONENTIAL   // Linear unless both values are non-zero with the same sign
+} bungee_curve_t;
+
+typedef enum bungee_parameter {
+    BUNGEE_PARAMETER_SPEED = 0,
+    BUNGEE_PARAMETER_PITCH
+} bungee_parameter_t;
+
+typedef enum bungee_time_base {
+    BUNGEE_TIME_BASE_INPUT = 0,  // Breakpoint times are input frames, as in request.position
+    BUNGEE_TIME_BASE_OUTPUT      // Breakpoint times are output frames since the last reset
+} bungee_time_base_t;
+
+typedef struct {
+    double time;
+    double value;
+    bungee_curve_t curve;  // Shape of the segment starting at this breakpoint
+} bungee_breakpoint_t;
+
+// Where resampling for pitch shift and sample-rate conversion takes place. Input resampling
+// changes the analysed audio before the transform; output resampling changes the synthesised
+// audio. They differ in CPU cost and in how the stretcher's timing responds to pitch.
+typedef enum bungee_resample_mode {
+    BUNGEE_RESAMPLE_AUTO_OUT = 0,  // Output resampling, only when needed (default)
+    BUNGEE_RESAMPLE_AUTO_IN,       // Input resampling, only when needed
+    BUNGEE_RESAMPLE_AUTO_IN_OUT,   // Input resampling when downsampling, output when upsampling
+    BUNGEE_RESAMPLE_FORCE_OUT,     // Output resampling, always active
+    BUNGEE_RESAMPLE_FORCE_IN       // Input resampling, always active
+} bungee_resample_mode_t;
+
+// Interpolation kernel used for resampling. Higher orders alias less when pitch shifting
+// at the cost of CPU.
+typedef enum bungee_interpolation {
+    BUNGEE_INTERPOLATION_NEAREST = 0,
+    BUNGEE_INTERPOLATION_BILINEAR,       // Default
+    BUNGEE_INTERPOLATION_CUBIC_HERMITE,  // Catmull-Rom spline through four samples
+    BUNGEE_INTERPOLATION_WINDOWED_SINC   // Blackman-windowed sinc, band-limited when downsampling
+} bungee_interpolation_t;
+
+// How the phases of multiple channels are related during stretching
+typedef enum bungee_channel_coupling {
+    BUNGEE_CHANNEL_COUPLING_SUM_LOCKED = 0,  // Default: one phase per bin from the channel sum; best image and mono fold-down
+    BUNGEE_CHANNEL_COUPLING_MID_SIDE,        // Stereo only: mid and side locked separately; suits wide stereo pads
+    BUNGEE_CHANNEL_COUPLING_INDEPENDENT      // Each channel stretched on its own; suits dual-mono material
+} bungee_channel_coupling_t;
+
+// Family of the analysis and synthesis windows, fixed when the stretcher is created. The gains
+// of both windows are computed from their shape so that overlapped grains sum to unity on
+// average, whatever the family. Cosine-sum coefficients should describe a window that is
+// nowhere negative, such as a flat-top or Nuttall window.
+typedef enum bungee_window_shape {
+    BUNGEE_WINDOW_HANN = 0,          // Default
+    BUNGEE_WINDOW_BLACKMAN_HARRIS,   // Four-term: lower sidelobes than Hann, wider main lobe
+    BUNGEE_WINDOW_KAISER,            // Kaiser-Bessel: sidelobes fall and the main lobe widens as beta rises
+    BUNGEE_WINDOW_COSINE_SUM         // a0 - a1 cos(2 pi n / N) + a2 cos(4 pi n / N) - ... from coefficients
+} bungee_window_shape_t;
+
+#define BUNGEE_MAX_KAISER_BETA 40.0f
+#define BUNGEE_MAX_WINDOW_COEFFICIENTS 16
+
+typedef struct {
+    bungee_window_shape_t shape;
+    float kaiser_beta;          // BUNGEE_WINDOW_KAISER: 0 to BUNGEE_MAX_KAISER_BETA
+    const float* coefficients;  // BUNGEE_WINDOW_COSINE_SUM: a0, a1, ..., with a0 positive; copied by create
+    int32_t coefficient_count;  // BUNGEE_WINDOW_COSINE_SUM: 1 to BUNGEE_MAX_WINDOW_COEFFICIENTS
+} bungee_window_t;
+
+// Real FFT supplied by the caller in place of the engine's built-in kissfft.
+//
+// A transform of length n = 1 << log2_length maps n real samples to n / 2 + 1 complex bins,
+// stored as interleaved (real, imaginary) pairs. Both directions are unnormalised, the forward
+// transform using exp(-2 pi i k t / n), so an inverse of a forward transform scales by n.
+// prepare (optional) is called before a length is first used in each direction. Calls for one
+// stretcher never overlap. Once bungee_create_with_config succeeds the stretcher owns context
+// and calls destroy (optional) from bungee_destroy; on failure the caller keeps ownership.
+typedef struct {
+    void* context;
+    void (*prepare)(void* context, int log2_length, bool inverse);
+    void (*forward)(void* context, int log2_length, const float* time, float* frequency);
+    void (*inverse)(void* context, int log2_length, float* time, const float* frequency);
+    void (*destroy)(void* context);
+} bungee_fft_backend_t;
+
+// Creation options for bungee_create_with_config; zero-initialise for the defaults.
+//
+// The synthesis hop is the number of output frames between grains before resampling. Each
+// grain spans eight hops, so a smaller hop lowers latency and a larger hop gives finer frequency
+// resolution. The default derives it from the input sample rate: 512 frames at 44.1 or 48 kHz.
+//
+// Request pitch must lie within 2^-max_pitch_octaves to 2^max_pitch_octaves; other values are
+// rejected with BUNGEE_PITCH_OUT_OF_RANGE.
+typedef struct {
+    int32_t log2_synthesis_hop;  // 0 for the default, else BUNGEE_MIN_ to BUNGEE_MAX_LOG2_SYNTHESIS_HOP
+    int32_t max_pitch_octaves;   // 0 for BUNGEE_DEFAULT_MAX_PITCH_OCTAVES, else 1 to BUNGEE_MAX_PITCH_OCTAVES
+    const bungee_fft_backend_t* fft;  // NULL for the built-in kissfft
+    const bungee_window_t* window;    // NULL for Hann windows
+} bungee_config_t;
+
+// Delays of a stretcher in frames, see bungee_latency
+typedef struct {
+    int32_t input;    // Input frames read beyond the input position of the first frame of each output chunk
+    int32_t output;   // Output frames between an input frame being supplied and its output, at speed 1
+    int32_t control;  // Output frames produced between a request passed to bungee_next and its first effect
+} bungee_latency_t;
+
 // Opaque handle to the stretcher
 typedef struct bungee_stretcher bungee_stretcher_t;
 
@@ -58,19 +207,113 @@ typedef struct bungee_stretcher bungee_stretcher_t;
 bungee_error_t bungee_init(void);
 void bungee_cleanup(void);
 
+// Error reporting
+//
+// Every failing call, including bungee_create returning NULL, records its error code and a
+// message describing the cause. The record is per thread and persists until the next failure.
+bungee_error_t bungee_last_error(void);
+const char* bungee_last_error_message(void);
+
 // Creation/Destruction
 bungee_stretcher_t* bungee_create(bungee_sample_rates_t rates, int channels);
+// As bungee_create with options; config may be NULL. An invalid option fails with BUNGEE_INVALID_PARAM.
+bungee_stretcher_t* bungee_create_with_config(bungee_sample_rates_t rates, int channels, const bungee_config_t* config);
 void bungee_destroy(bungee_stretcher_t* stretcher);
 
 // Processing functions
-bungee_error_t bungee_preroll(bungee_stretcher_t* stretcher, const bungee_request_t* request);
-bungee_error_t bungee_specify_grain(bungee_stretcher_t* stretcher, const float* input_data, size_t frame_count, bungee_input_chunk_t* chunk);
+//
+// bungee_preroll and bungee_next update request in place; pass the updated request back
+// to bungee_next for every subsequent grain.
+// bungee_specify_grain reports the input frame range [begin, end) needed for the current grain.
+// bungee_analyse_grain then reads that range: input_data points at frame begin of the first
+// channel and channel c starts at input_data[c * channel_stride] (planar layout).
+bungee_error_t bungee_preroll(bungee_stretcher_t* stretcher, bungee_request_t* request);
+bungee_error_t bungee_specify_grain(bungee_stretcher_t* stretcher, bungee_input_chunk_t* chunk);
 bungee_error_t bungee_analyse_grain(bungee_stretcher_t* stretcher, const float* input_data, size_t channel_stride);
 bungee_error_t bungee_synthesise_grain(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk);
 bungee_error_t bungee_next(bungee_stretcher_t* stretcher, bungee_request_t* request);
 
+// Typed sample input and output
+//
+// As bungee_analyse_grain and bungee_synthesise_grain with samples of the given format.
+// Conversion to and from float happens inside the call, so the caller's audio is not copied.
+// channel_stride and chunk->channel_stride count samples of that format; bungee_synthesise_grain_format
+// writes to data and ignores chunk->data.
+bungee_error_t bungee_analyse_grain_format(bungee_stretcher_t* stretcher, const void* input_data,
+                                           bungee_sample_format_t format, size_t channel_stride);
+bungee_error_t bungee_synthesise_grain_format(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk, void* data,
+                                              bungee_sample_format_t format);
+
+// Per-channel buffers
+//
+// As the _format functions with channel c at channel_data[c] and successive frames frame_stride
+// samples apart, so one call serves planar audio in separate buffers (frame_stride 1), interleaved
+// audio (channel_data[c] = data + c, frame_stride the channel count) or any other strided layout.
+// bungee_synthesise_grain_channels ignores chunk->data and chunk->channel_stride.
+bungee_error_t bungee_analyse_grain_channels(bungee_stretcher_t* stretcher, const void* const* channel_data,
+                                             bungee_sample_format_t format, size_t frame_stride);
+bungee_error_t bungee_synthesise_grain_channels(bungee_stretcher_t* stretcher, bungee_output_chunk_t* chunk,
+                                                void* const* channel_data, bungee_sample_format_t format,
+                                                size_t frame_stride);
+
+// Automation
+//
+// Replaces the envelope of one parameter. bungee_preroll and bungee_next then overwrite the
+// request's speed or pitch with the envelope's value for each grain. Breakpoint times must be
+// non-decreasing; a count of zero removes the envelope.
+bungee_error_t bungee_set_automation(bungee_stretcher_t* stretcher, bungee_parameter_t parameter,
+                                     bungee_time_base_t time_base, const bungee_breakpoint_t* breakpoints,
+                                     size_t count);
+
+// Resampling
+//
+// Selects the resample mode for bungee_preroll, bungee_next and every grain specified
+// afterwards. It may be changed between grains, so each request can use its own mode.
+bungee_error_t bungee_set_resample_mode(bungee_stretcher_t* stretcher, bungee_resample_mode_t mode);
+
+// Selects the interpolation kernel, applied like the resample mode. sinc_taps is the length of
+// the windowed-sinc kernel, one of 4, 8, 16 or 32, and is ignored by the other kernels.
+bungee_error_t bungee_set_interpolation(bungee_stretcher_t* stretcher, bungee_interpolation_t interpolation,
+                                        int sinc_taps);
+
+// Transients
+//
+// Sets how readily a grain is detected as a transient by spectral flux, from 0 (never, the
+// default) to 1 (most sensitive). Partials that rise sharply in a transient grain have their
+// phase reset, which keeps drum attacks sharp at slow speeds.
+bungee_error_t bungee_set_transient_sensitivity(bungee_stretcher_t* stretcher, float sensitivity);
+
+// Channel coupling
+//
+// Selects how channel phases are coupled. Takes effect from the next preroll or reset request.
+// BUNGEE_CHANNEL_COUPLING_MID_SIDE requires exactly two channels.
+bungee_error_t bungee_set_channel_coupling(bungee_stretcher_t* stretcher, bungee_channel_coupling_t coupling);
+
+// Seeking
+//
+// Moves playback to request->position without the discontinuity of a reset request. Call in
+// place of bungee_next (or bungee_preroll); request is moved back as by bungee_preroll and
+// updated in place. The chain of grains being left keeps running from where it was, with the
+// speed and pitch of later requests, while a new chain fills at the new position; their outputs
+// are then crossfaded at equal power over crossfade_frames output frames. Until the crossfade
+// ends grains alternate between the two chains, so bungee_specify_grain may ask for input near
+// the old position, and after such a grain bungee_next leaves request unchanged. A crossfade of
+// 0 frames cuts at once, as bungee_preroll does. The first crossfading seek creates a second
+// engine instance, and a seek with a longer crossfade than before grows the buffer of outgoing
+// output, failing with BUNGEE_MEMORY if either cannot be allocated; seeking during a crossfade
+// drops the chain being faded out.
+bungee_error_t bungee_seek(bungee_stretcher_t* stretcher, bungee_request_t* request, int32_t crossfade_frames);
+
 // Query functions
+//
+// bungee_is_flushed is false while a seek's crossfade is in progress.
 bool bungee_is_flushed(const bungee_stretcher_t* stretcher);
 size_t bungee_max_input_frame_count(const bungee_stretcher_t* stretcher);
+size_t bungee_max_output_frame_count(const bungee_stretcher_t* stretcher);
+
+// Latency for the stretcher's sample rates, hop, resample mode and interpolation, at the pitch of
+// the request last passed to bungee_preroll or bungee_next (1 before the first). A host streaming
+// at unit speed delays other tracks by output frames to compensate; all zero if stretcher is NULL.
+bungee_latency_t bungee_latency(const bungee_stretcher_t* stretcher);
 
 #endif // BUNGEE_C_H 
\ No newline at end of file
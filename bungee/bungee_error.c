@@ -0,0 +1,50 @@
+/**
+ * @file bungee_error.c
+ * @brief Thread-local record of the most recent error
+ *
+ * Each thread keeps its own code and message so that errors from concurrent
+ * stretchers on different threads do not overwrite one another.
+ */
+
+#include "bungee_error.h"
+#include <stdarg.h>
+#include <stdio.h>
+
+#if defined(_MSC_VER)
+#define BUNGEE_THREAD_LOCAL __declspec(thread)
+#else
+#define BUNGEE_THREAD_LOCAL _Thread_local
+#endif
+
+static BUNGEE_THREAD_LOCAL bungee_error_t last_error = BUNGEE_OK;
+static BUNGEE_THREAD_LOCAL char last_error_message[256];
+
+bungee_error_t bungee_set_error(bungee_error_t code, const char* format, ...) {
+    va_list args;
+    va_start(args, format);
+    vsnprintf(last_error_message, sizeof(last_error_message), format, args);
+    va_end(args);
+
+    last_error = code;
+    BUNGEE_LOG("Error %d: %s", (int)code, last_error_message);
+    return code;
+}
+
+/**
+ * @brief Gets the code of the most recent error on the calling thread
+ *
+ * @return Error code, or BUNGEE_OK if no call has failed on this thread
+ */
+bungee_error_t bungee_last_error(void) {
+    return last_error;
+}
+
+/**
+ * @brief Gets a description of the most recent error on the calling thread
+ *
+ * @return Null-terminated message, empty if no call has failed on this thread.
+ *         Valid until the next failing call on the same thread.
+ */
+const char* bungee_last_error_message(void) {
+    return last_error_message;
+}
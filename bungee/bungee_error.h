@@ -0,0 +1,26 @@
+/**
+ * @file bungee_error.h
+ * @brief Internal error reporting shared by the C layer implementations
+ */
+
+#ifndef BUNGEE_ERROR_H
+#define BUNGEE_ERROR_H
+
+#include "bungee_c.h"
+
+/**
+ * @brief Records an error for bungee_last_error() and bungee_last_error_message()
+ *
+ * The message is formatted printf-style and also written with BUNGEE_LOG in debug builds.
+ *
+ * @param code Error code to record and return
+ * @param format printf-style format of the message
+ * @return code, so that callers can write `return bungee_set_error(...)`
+ */
+bungee_error_t bungee_set_error(bungee_error_t code, const char* format, ...)
+#if defined(__GNUC__) || defined(__clang__)
+    __attribute__((format(printf, 2, 3)))
+#endif
+    ;
+
+#endif // BUNGEE_ERROR_H
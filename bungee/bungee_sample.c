@@ -0,0 +1,82 @@
+/**
+ * @file bungee_sample.c
+ * @brief Conversion between the engine's float samples and the formats of bungee_sample_format_t
+ *
+ * Integer formats map full scale to 1.0: -32768 is -1.0 for 16-bit samples and
+ * 32767 is just below 1.0. Floats outside -1..1 are clamped on conversion back.
+ */
+
+#include "bungee_sample.h"
+#include "bungee_error.h"
+#include <math.h>
+
+#define I16_SCALE 32768.0
+#define I24_SCALE 8388608.0
+#define I32_SCALE 2147483648.0
+
+bungee_error_t bungee_check_sample_format(bungee_sample_format_t format) {
+    switch (format) {
+    case BUNGEE_SAMPLE_F32:
+    case BUNGEE_SAMPLE_F64:
+    case BUNGEE_SAMPLE_I16:
+    case BUNGEE_SAMPLE_I24:
+    case BUNGEE_SAMPLE_I32:
+        return BUNGEE_OK;
+    }
+    return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid sample format: %d", (int)format);
+}
+
+/**
+ * @brief Scales a float to an integer of the given full scale, rounding and clamping
+ */
+static double to_integer(float value, double scale) {
+    double scaled = nearbyint((double)value * scale);
+    if (isnan(scaled)) return 0.0;
+    if (scaled < -scale) return -scale;
+    if (scaled > scale - 1.0) return scale - 1.0;
+    return scaled;
+}
+
+void bungee_samples_to_float(const void* source, bungee_sample_format_t format, size_t offset, size_t step,
+                             float* destination, size_t count) {
+    size_t j = offset;
+    switch (format) {
+    case BUNGEE_SAMPLE_F32:
+        for (size_t i = 0; i < count; i++, j += step) destination[i] = ((const float*)source)[j];
+        break;
+    case BUNGEE_SAMPLE_F64:
+        for (size_t i = 0; i < count; i++, j += step) destination[i] = (float)((const double*)source)[j];
+        break;
+    case BUNGEE_SAMPLE_I16:
+        for (size_t i = 0; i < count; i++, j += step) destination[i] = (float)(((const int16_t*)source)[j] / I16_SCALE);
+        break;
+    case BUNGEE_SAMPLE_I24:
+        for (size_t i = 0; i < count; i++, j += step) destination[i] = (float)(((const int32_t*)source)[j] / I24_SCALE);
+        break;
+    case BUNGEE_SAMPLE_I32:
+        for (size_t i = 0; i < count; i++, j += step) destination[i] = (float)(((const int32_t*)source)[j] / I32_SCALE);
+        break;
+    }
+}
+
+void bungee_samples_from_float(const float* source, size_t count, void* destination, bungee_sample_format_t format,
+                               size_t offset, size_t step) {
+    size_t j = offset;
+    switch (format) {
+    case BUNGEE_SAMPLE_F32:
+        for (size_t i = 0; i < count; i++, j += step) ((float*)destination)[j] = source[i];
+        break;
+    case BUNGEE_SAMPLE_F64:
+        for (size_t i = 0; i < count; i++, j += step) ((double*)destination)[j] = source[i];
+        break;
+    case BUNGEE_SAMPLE_I16:
+        for (size_t i = 0; i < count; i++, j += step) ((int16_t*)destination)[j] = (int16_t)to_integer(source[i], I16_SCALE);
+        break;
+    case BUNGEE_SAMPLE_I24:
+        for (size_t i = 0; i < count; i++, j += step) ((int32_t*)destination)[j] = (int32_t)to_integer(source[i], I24_SCALE);
+        break;
+    case BUNGEE_SAMPLE_I32:
+        for (size_t i = 0; i < count; i++, j += step) ((int32_t*)destination)[j] = (int32_t)to_integer(source[i], I32_SCALE);
+        break;
+    }
+}
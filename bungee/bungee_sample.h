@@ -0,0 +1,45 @@
+/**
+ * @file bungee_sample.h
+ * @brief Internal sample format conversion shared by the C layer implementations
+ */
+
+#ifndef BUNGEE_SAMPLE_H
+#define BUNGEE_SAMPLE_H
+
+#include "bungee_c.h"
+
+/**
+ * @brief Checks that a format is one of bungee_sample_format_t
+ *
+ * @param format Sample format from the caller
+ * @return BUNGEE_OK, or BUNGEE_INVALID_PARAM with the last error message set
+ */
+bungee_error_t bungee_check_sample_format(bungee_sample_format_t format);
+
+/**
+ * @brief Converts samples of any format to contiguous floats
+ *
+ * @param source Samples of the given format
+ * @param format Format of source
+ * @param offset Index of the first sample in source
+ * @param step Distance between successive samples in source
+ * @param destination Receives count floats
+ * @param count Number of samples to convert
+ */
+void bungee_samples_to_float(const void* source, bungee_sample_format_t format, size_t offset, size_t step,
+                             float* destination, size_t count);
+
+/**
+ * @brief Converts contiguous floats to samples of any format, clamping integers to full scale
+ *
+ * @param source count floats
+ * @param count Number of samples to convert
+ * @param destination Receives samples of the given format
+ * @param format Format of destination
+ * @param offset Index of the first sample in destination
+ * @param step Distance between successive samples in destination
+ */
+void bungee_samples_from_float(const float* source, size_t count, void* destination, bungee_sample_format_t format,
+                               size_t offset, size_t step);
+
+#endif // BUNGEE_SAMPLE_H
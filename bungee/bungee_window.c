@@ -0,0 +1,44 @@
+/**
+ * @file bungee_window.c
+ * @brief Validation of the window families of bungee_window_shape_t
+ */
+
+#include "bungee_window.h"
+#include "bungee_error.h"
+#include <math.h>
+
+bungee_error_t bungee_check_window(const bungee_window_t* window) {
+    if (!window) return BUNGEE_OK;
+
+    switch (window->shape) {
+    case BUNGEE_WINDOW_HANN:
+    case BUNGEE_WINDOW_BLACKMAN_HARRIS:
+        return BUNGEE_OK;
+
+    case BUNGEE_WINDOW_KAISER:
+        if (!(window->kaiser_beta >= 0.0f && window->kaiser_beta <= BUNGEE_MAX_KAISER_BETA)) {
+            return bungee_set_error(BUNGEE_INVALID_PARAM, "Kaiser window beta %g outside 0..%g",
+                                    (double)window->kaiser_beta, (double)BUNGEE_MAX_KAISER_BETA);
+        }
+        return BUNGEE_OK;
+
+    case BUNGEE_WINDOW_COSINE_SUM:
+        if (!window->coefficients || window->coefficient_count < 1 ||
+            window->coefficient_count > BUNGEE_MAX_WINDOW_COEFFICIENTS) {
+            return bungee_set_error(BUNGEE_INVALID_PARAM, "Cosine-sum window needs 1..%d coefficients, got %d",
+                                    BUNGEE_MAX_WINDOW_COEFFICIENTS, (int)window->coefficient_count);
+        }
+        for (int32_t i = 0; i < window->coefficient_count; i++) {
+            if (!isfinite(window->coefficients[i])) {
+                return bungee_set_error(BUNGEE_INVALID_PARAM, "Cosine-sum window coefficient %d is not finite", (int)i);
+            }
+        }
+        // a0 is the window's mean, which sets its gain
+        if (!(window->coefficients[0] > 0.0f)) {
+            return bungee_set_error(BUNGEE_INVALID_PARAM, "Cosine-sum window coefficient a0 %g is not positive",
+                                    (double)window->coefficients[0]);
+        }
+        return BUNGEE_OK;
+    }
+    return bungee_set_error(BUNGEE_INVALID_PARAM, "Invalid window shape: %d", (int)window->shape);
+}
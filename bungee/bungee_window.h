@@ -0,0 +1,19 @@
+/**
+ * @file bungee_window.h
+ * @brief Internal window option checks shared by the C layer implementations
+ */
+
+#ifndef BUNGEE_WINDOW_H
+#define BUNGEE_WINDOW_H
+
+#include "bungee_c.h"
+
+/**
+ * @brief Checks the window option of bungee_config_t
+ *
+ * @param window Window from the caller, or NULL for Hann windows
+ * @return BUNGEE_OK, or BUNGEE_INVALID_PARAM with the last error message set
+ */
+bungee_error_t bungee_check_window(const bungee_window_t* window);
+
+#endif // BUNGEE_WINDOW_H
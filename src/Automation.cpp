@@ -0,0 +1,52 @@
+// Copyright (C) 2020-2024 Parabola Research Limited
+// SPDX-License-Identifier: MPL-2.0
+
+#include "Automation.h"
+
+#include <algorithm>
+#include <cmath>
+
+namespace Bungee {
+
+double Envelope::operator()(double time) const
+{
+	const auto next = std::upper_bound(breakpoints.begin(), breakpoints.end(), time, [](double t, const Breakpoint &b) { return t < b.time; });
+	if (next == breakpoints.begin())
+		return breakpoints.front().value;
+	if (next == breakpoints.end())
+		return breakpoints.back().value;
+
+	const auto &a = next[-1];
+	const auto &b = next[0];
+	const double x = (time - a.time) / (b.time - a.time);
+
+	switch (a.curve)
+	{
+	case Bungee_Curve_hold:
+		return a.value;
+	case Bungee_Curve_exponential:
+		if (a.value * b.value > 0.)
+			return a.value * std::pow(b.value / a.value, x);
+		[[fallthrough]];
+	case Bungee_Curve_linear:
+	default:
+		return a.value + (b.value - a.value) * x;
+	}
+}
+
+void Automation::apply(Request &request, double outputPosition) const
+{
+	if (std::isnan(request.position))
+		return;
+
+	const auto time = [&](const Envelope &envelope) {
+		return envelope.timeBase == Bungee_TimeBase_output ? outputPosition : request.position;
+	};
+
+	if (!speed.empty())
+		request.speed = speed(time(speed));
+	if (!pitch.empty())
+		request.pitch = pitch(time(pitch));
+}
+
+} // namespace Bungee
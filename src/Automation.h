@@ -0,0 +1,44 @@
+// Copyright (C) 2020-2024 Parabola Research Limited
+// SPDX-License-Identifier: MPL-2.0
+
+#pragma once
+
+#include "bungee/Bungee.h"
+
+#include <vector>
+
+namespace Bungee {
+
+struct Envelope
+{
+	Bungee_TimeBase timeBase = Bungee_TimeBase_input;
+	std::vector<Breakpoint> breakpoints;
+
+	bool empty() const
+	{
+		return breakpoints.empty();
+	}
+
+	// Value at time, holding the first and last breakpoint values outside the envelope.
+	double operator()(double time) const;
+};
+
+struct Automation
+{
+	Envelope speed;
+	Envelope pitch;
+
+	// Output frames synthesised since the last grain with reset set, the clock for Bungee_TimeBase_output.
+	double outputPosition = 0.;
+
+	Envelope &operator[](Bungee_Parameter parameter)
+	{
+		return parameter == Bungee_Parameter_pitch ? pitch : speed;
+	}
+
+	// Sets request speed and pitch from their envelopes, if any, at the request's position
+	// or at the given output position depending on each envelope's time base.
+	void apply(Request &request, double outputPosition) const;
+};
+
+} // namespace Bungee
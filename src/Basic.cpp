@@ -19,9 +19,9 @@ const char *Bungee_Stretcher_version()
 	return Bungee::versionDescription;
 }
 
-void *Bungee_Stretcher_create(SampleRates sampleRates, int channelCount, int log2SynthesisHop)
+void *Bungee_Stretcher_create(SampleRates sampleRates, int channelCount, int log2SynthesisHop, int maxPitchOctaves, const Bungee_FourierCallbacks *fourier, const Bungee_Window *window)
 {
-	return new Basic(sampleRates, channelCount, log2SynthesisHop);
+	return new Basic(sampleRates, channelCount, log2SynthesisHop, maxPitchOctaves, fourier, window ? *window : Bungee_Window{});
 }
 
 void Bungee_Stretcher_destroy(void *implementation)
@@ -64,6 +64,49 @@ void Bungee_Stretcher_next(const void *implementation, Bungee_Request *request)
 	reinterpret_cast<const Basic *>(implementation)->next(*request);
 }
 
+int Bungee_Stretcher_maxOutputFrameCount(const void *implementation)
+{
+	return reinterpret_cast<const Basic *>(implementation)->maxOutputFrameCount(true);
+}
+
+void Bungee_Stretcher_setAutomation(void *implementation, Bungee_Parameter parameter, Bungee_TimeBase timeBase, const Bungee_Breakpoint *breakpoints, int count)
+{
+	auto &envelope = reinterpret_cast<Basic *>(implementation)->automation[parameter];
+	envelope.timeBase = timeBase;
+	envelope.breakpoints.assign(breakpoints, breakpoints + count);
+}
+
+void Bungee_Stretcher_setResampleMode(void *implementation, Bungee_ResampleMode resampleMode)
+{
+	reinterpret_cast<Basic *>(implementation)->resampleMode = static_cast<ResampleMode::Enum>(resampleMode);
+}
+
+void Bungee_Stretcher_setInterpolation(void *implementation, Bungee_Interpolation interpolation, int sincTaps)
+{
+	reinterpret_cast<Basic *>(implementation)->resampleKernel = Resample::Kernel::select(interpolation, sincTaps);
+}
+
+void Bungee_Stretcher_setTransientSensitivity(void *implementation, float sensitivity)
+{
+	reinterpret_cast<Basic *>(implementation)->transientSensitivity = sensitivity;
+}
+
+void Bungee_Stretcher_setChannelCoupling(void *implementation, Bungee_ChannelCoupling channelCoupling)
+{
+	reinterpret_cast<Basic *>(implementation)->requestedChannelCoupling = channelCoupling;
+}
+
+Bungee_Latency Bungee_Stretcher_latency(const void *implementation, double pitch)
+{
+	return reinterpret_cast<const Basic *>(implementation)->latency(pitch);
+}
+
+static_assert(int(Bungee_ResampleMode_autoOut) == ResampleMode::autoOut);
+static_assert(int(Bungee_ResampleMode_autoIn) == ResampleMode::autoIn);
+static_assert(int(Bungee_ResampleMode_autoInOut) == ResampleMode::autoInOut);
+static_assert(int(Bungee_ResampleMode_forceOut) == ResampleMode::forceOut);
+static_assert(int(Bungee_ResampleMode_forceIn) == ResampleMode::forceIn);
+
 Bungee_Stretcher_FunctionTable Bungee_Stretcher_getFunctionTable()
 {
 	return Bungee_Stretcher_FunctionTable{
@@ -77,18 +120,28 @@ Bungee_Stretcher_FunctionTable Bungee_Stretcher_getFunctionTable()
 		Bungee_Stretcher_analyseGrain,
 		Bungee_Stretcher_synthesiseGrain,
 		Bungee_Stretcher_isFlushed,
+		Bungee_Stretcher_maxOutputFrameCount,
+		Bungee_Stretcher_setAutomation,
+		Bungee_Stretcher_setResampleMode,
+		Bungee_Stretcher_setInterpolation,
+		Bungee_Stretcher_setTransientSensitivity,
+		Bungee_Stretcher_setChannelCoupling,
+		Bungee_Stretcher_latency,
 	};
 }
 
 namespace Bungee {
 
-Basic::Basic(SampleRates sampleRates, int channelCount, int log2SynthesisHopOverride) :
-	Timing(sampleRates, log2SynthesisHopOverride),
-	transforms(Fourier::transforms()),
-	input(log2SynthesisHop, channelCount, *transforms),
+Basic::Basic(SampleRates sampleRates, int channelCount, int log2SynthesisHopOverride, int maxPitchOctaves, const Bungee_FourierCallbacks *fourier, const Bungee_Window &window) :
+	Timing(sampleRates, log2SynthesisHopOverride, maxPitchOctaves),
+	transforms(fourier ? Fourier::transforms(*fourier) : Fourier::transforms()),
+	input(log2SynthesisHop, channelCount, *transforms, window),
 	grains(4),
-	output(*transforms, log2SynthesisHop, channelCount, maxOutputFrameCount(true), 0.25f, {1.f, 0.5f})
+	output(*transforms, log2SynthesisHop, channelCount, maxOutputFrameCount(true), window),
+	formants(log2SynthesisHop + 3, std::max(8, sampleRates.input / 1000))
 {
+	Window::normalise(log2SynthesisHop, input.analysisWindowBasic, output.synthesisWindow);
+
 	for (auto &grain : grains.vector)
 		grain = std::make_unique<Grain>(log2SynthesisHop, channelCount);
 }
@@ -99,9 +152,16 @@ InputChunk Basic::specifyGrain(const Request &request)
 
 	grains.rotate();
 
+	if (request.reset)
+	{
+		automation.outputPosition = 0.;
+		channelCoupling = requestedChannelCoupling;
+	}
+
 	auto &grain = grains[0];
+	grain.channelCoupling = channelCoupling;
 	auto &previous = grains[1];
-	return grain.specify(request, previous, sampleRates, log2SynthesisHop);
+	return grain.specify(request, previous, sampleRates, log2SynthesisHop, resampleMode, resampleKernel);
 }
 
 void Basic::analyseGrain(const float *data, std::ptrdiff_t stride)
@@ -110,10 +170,12 @@ void Basic::analyseGrain(const float *data, std::ptrdiff_t stride)
 
 	auto &grain = grains[0];
 	grain.validBinCount = 0;
+	for (auto &track : grain.tracks)
+		track.transient = false;
 	if (grain.valid())
 	{
 		auto m = grain.inputChunkMap(data, stride);
-		auto ref = grain.resampleInput(m, 8 << log2SynthesisHop);
+		auto ref = grain.resampleInput(m, log2SynthesisHop + 3);
 
 		auto log2TransformLength = input.applyAnalysisWindow(ref);
 
@@ -125,17 +187,43 @@ void Basic::analyseGrain(const float *data, std::ptrdiff_t stride)
 
 		grain.log2TransformLength = log2TransformLength;
 
-		for (int i = 0; i < grain.validBinCount; ++i)
+		if (grain.channelCoupling == Bungee_ChannelCoupling_midSide)
 		{
-			const auto x = grain.transformed.row(i).sum();
-			grain.energy[i] = x.real() * x.real() + x.imag() * x.imag();
-			grain.phase[i] = Phase::fromRadians(std::arg(x));
+			auto left = grain.transformed.col(0).head(grain.validBinCount);
+			auto right = grain.transformed.col(1).head(grain.validBinCount);
+			left = (left + right) * 0.5f;
+			right = left - right;
 		}
 
-		Partials::enumerate(grain.partials, grain.validBinCount, grain.energy);
-
-		if (grain.continuous)
-			Partials::suppressTransientPartials(grain.partials, grain.energy, grains[1].energy);
+		for (int t = 0; t < grain.trackCount(); ++t)
+		{
+			auto &track = grain.tracks[t];
+			auto &previousTrack = grains[1].tracks[t];
+			auto columns = grain.trackColumns(t);
+
+			for (int i = 0; i < grain.validBinCount; ++i)
+			{
+				const auto x = columns.row(i).sum();
+				track.energy[i] = x.real() * x.real() + x.imag() * x.imag();
+				track.phase[i] = Phase::fromRadians(std::arg(x));
+			}
+
+			Partials::enumerate(track.partials, grain.validBinCount, track.energy);
+
+			if (grain.continuous)
+			{
+				Partials::suppressTransientPartials(track.partials, track.energy, previousTrack.energy);
+
+				if (transientSensitivity > 0.f)
+				{
+					const auto n = std::min(grain.validBinCount, grains[1].validBinCount);
+					track.transient = Partials::spectralFlux(n, track.energy, previousTrack.energy) > 1.f - transientSensitivity;
+				}
+			}
+
+			if (grain.request.preserveFormants && grain.request.pitch != 1.)
+				formants.preserve(*transforms, grain.log2TransformLength, grain.validBinCount, track.energy, columns, grain.request.pitch);
+		}
 	}
 }
 
@@ -152,14 +240,28 @@ void Basic::synthesiseGrain(OutputChunk &outputChunk)
 
 		Synthesis::synthesise(log2SynthesisHop, grain, grains[1]);
 
-		BUNGEE_ASSERT2(!grain.passthrough || grain.rotation.topRows(grain.validBinCount).isZero());
+		for (int t = 0; t < grain.trackCount(); ++t)
+		{
+			const auto &track = grain.tracks[t];
+			auto columns = grain.trackColumns(t).topRows(grain.validBinCount);
 
-		auto theta = grain.rotation.topRows(grain.validBinCount).cast<float>() * (float(constants::pi) / 0x8000);
-		auto t = theta.cos() + theta.sin() * std::complex<float>{0, 1};
-		if (grain.reverse())
-			grain.transformed.topRows(grain.validBinCount) = grain.transformed.topRows(grain.validBinCount).conjugate().colwise() * t;
-		else
-			grain.transformed.topRows(grain.validBinCount).colwise() *= t;
+			BUNGEE_ASSERT2(!grain.passthrough || track.rotation.topRows(grain.validBinCount).isZero());
+
+			auto theta = track.rotation.topRows(grain.validBinCount).cast<float>() * (float(constants::pi) / 0x8000);
+			auto r = theta.cos() + theta.sin() * std::complex<float>{0, 1};
+			if (grain.reverse())
+				columns = columns.conjugate().colwise() * r;
+			else
+				columns.colwise() *= r;
+		}
+
+		if (grain.channelCoupling == Bungee_ChannelCoupling_midSide)
+		{
+			auto mid = grain.transformed.col(0).head(grain.validBinCount);
+			auto side = grain.transformed.col(1).head(grain.validBinCount);
+			mid += side;
+			side = mid - 2.f * side;
+		}
 
 		transforms->inverse(grain.log2TransformLength, output.inverseTransformed, grain.transformed);
 	}
@@ -176,6 +278,8 @@ void Basic::synthesiseGrain(OutputChunk &outputChunk)
 
 	outputChunk.request[OutputChunk::begin] = &grains[2].request;
 	outputChunk.request[OutputChunk::end] = &grains[1].request;
+
+	automation.outputPosition += outputChunk.frameCount;
 }
 
 } // namespace Bungee
@@ -3,6 +3,7 @@
 
 #pragma once
 
+#include "Formants.h"
 #include "Fourier.h"
 #include "Grains.h"
 #include "Input.h"
@@ -20,8 +21,12 @@ struct Basic :
 	Input input;
 	Grains grains;
 	Output output;
+	Formants::Envelope formants;
+	float transientSensitivity{};
+	Bungee_ChannelCoupling channelCoupling{};
+	Bungee_ChannelCoupling requestedChannelCoupling{};
 
-	Basic(SampleRates sampleRates, int channelCount, int log2SynthesisHopOverride);
+	Basic(SampleRates sampleRates, int channelCount, int log2SynthesisHopOverride, int maxPitchOctaves, const Bungee_FourierCallbacks *fourier = nullptr, const Bungee_Window &window = {});
 
 	InputChunk specifyGrain(const Request &request);
 
@@ -0,0 +1,69 @@
+// Copyright (C) 2020-2024 Parabola Research Limited
+// SPDX-License-Identifier: MPL-2.0
+
+#include "Formants.h"
+
+#include <algorithm>
+#include <cmath>
+#include <limits>
+#include <numbers>
+
+namespace Bungee::Formants {
+
+namespace {
+static constexpr float pi = std::numbers::pi_v<float>;
+
+// limits the correction to +/-40 dB so that near-silent regions of the envelope are not boosted into noise
+static constexpr float maxLogGain = 4.6f;
+
+// Completes bins 0..n of t into a sequence of length 2n that is even-symmetric, so that its forward transform is real.
+void mirror(Eigen::Ref<Eigen::ArrayXXf> t, int n)
+{
+	for (int j = 1; j < n; ++j)
+		t(2 * n - j, 0) = t(j, 0);
+}
+} // namespace
+
+Envelope::Envelope(int log2TransformLength, int lifterLength) :
+	lifterLength(lifterLength)
+{
+	Fourier::resize<false>(log2TransformLength, 1, cepstrum);
+	Fourier::resize<true>(log2TransformLength, 1, spectrum);
+	Fourier::resize<true>(log2TransformLength, 1, logEnergy);
+}
+
+void Envelope::preserve(const Fourier::Transforms &transforms, int log2TransformLength, int validBinCount, const Eigen::ArrayXf &energy, Eigen::Ref<Eigen::ArrayXXcf> transformed, double pitch)
+{
+	const int n = Fourier::binCount(log2TransformLength) - 1;
+	BUNGEE_ASSERT1(validBinCount > 0 && validBinCount <= n + 1);
+
+	// Log energy, holding the last valid bin up to Nyquist
+	const float floor = std::max(energy.head(validBinCount).maxCoeff() * 1e-10f, std::numeric_limits<float>::min());
+	for (int k = 0; k <= n; ++k)
+		cepstrum(k, 0) = std::log(std::max(energy[std::min(k, validBinCount - 1)], floor));
+	mirror(cepstrum, n);
+	transforms.forward(log2TransformLength, cepstrum, spectrum);
+
+	// Keep only the low quefrencies, those of the envelope rather than of the harmonics, with a raised-cosine taper
+	const float scale = 1.f / (2 * n);
+	for (int q = 0; q <= n; ++q)
+	{
+		const float lifter = q < lifterLength ? 0.5f + 0.5f * std::cos(pi * q / lifterLength) : 0.f;
+		cepstrum(q, 0) = spectrum(q, 0).real() * scale * lifter;
+	}
+	mirror(cepstrum, n);
+	transforms.forward(log2TransformLength, cepstrum, spectrum);
+	logEnergy.head(n + 1) = spectrum.col(0).head(n + 1).real();
+
+	// Bin k is heard at k * pitch once shifted, where it should carry the envelope found there
+	for (int k = 0; k < validBinCount; ++k)
+	{
+		const float x = std::min<float>(k * pitch, n);
+		const int i = std::min<int>(x, n - 1);
+		const float target = logEnergy[i] + (x - i) * (logEnergy[i + 1] - logEnergy[i]);
+		const float logGain = std::clamp(0.5f * (target - logEnergy[k]), -maxLogGain, maxLogGain);
+		transformed.row(k) *= std::exp(logGain);
+	}
+}
+
+} // namespace Bungee::Formants
@@ -0,0 +1,27 @@
+// Copyright (C) 2020-2024 Parabola Research Limited
+// SPDX-License-Identifier: MPL-2.0
+
+#pragma once
+
+#include "Fourier.h"
+
+#include <Eigen/Dense>
+
+namespace Bungee::Formants {
+
+// Cepstral estimate of a grain's spectral envelope, used to keep formants in place while pitch shifting.
+struct Envelope
+{
+	Eigen::ArrayXXf cepstrum;
+	Eigen::ArrayXXcf spectrum;
+	Eigen::ArrayXf logEnergy;
+	int lifterLength;
+
+	Envelope(int log2TransformLength, int lifterLength);
+
+	// Scales the valid bins of transformed so that, once the grain is shifted by pitch, its spectral
+	// envelope matches that estimated from energy.
+	void preserve(const Fourier::Transforms &transforms, int log2TransformLength, int validBinCount, const Eigen::ArrayXf &energy, Eigen::Ref<Eigen::ArrayXXcf> transformed, double pitch);
+};
+
+} // namespace Bungee::Formants
@@ -62,4 +62,59 @@ std::unique_ptr<Transforms> transforms()
 	return std::make_unique<Cache<Kiss, 16>>();
 }
 
+struct Callbacks :
+	Transforms
+{
+	const Bungee_FourierCallbacks callbacks;
+
+	Callbacks(const Bungee_FourierCallbacks &callbacks) :
+		callbacks(callbacks)
+	{
+		BUNGEE_ASSERT1(callbacks.forward && callbacks.inverse);
+	}
+
+	~Callbacks() override
+	{
+		if (callbacks.destroy)
+			callbacks.destroy(callbacks.context);
+	}
+
+	void prepareForward(int log2TransformLength) override
+	{
+		if (callbacks.prepare)
+			callbacks.prepare(callbacks.context, log2TransformLength, false);
+	}
+
+	void prepareInverse(int log2TransformLength) override
+	{
+		if (callbacks.prepare)
+			callbacks.prepare(callbacks.context, log2TransformLength, true);
+	}
+
+	void forward(int log2TransformLength, const Eigen::Ref<const Eigen::ArrayXXf> &t, Eigen::Ref<Eigen::ArrayXXcf> f) const override
+	{
+		BUNGEE_ASSERT1(t.cols() == f.cols());
+		BUNGEE_ASSERT1(t.cols() == 1 || !t.IsRowMajor);
+		BUNGEE_ASSERT1(f.cols() == 1 || !f.IsRowMajor);
+
+		for (int c = 0; c < f.cols(); ++c)
+			callbacks.forward(callbacks.context, log2TransformLength, t.col(c).data(), (float *)f.col(c).data());
+	}
+
+	void inverse(int log2TransformLength, Eigen::Ref<Eigen::ArrayXXf> t, const Eigen::Ref<const Eigen::ArrayXXcf> &f) const override
+	{
+		BUNGEE_ASSERT1(t.cols() == f.cols());
+		BUNGEE_ASSERT1(t.cols() == 1 || !t.IsRowMajor);
+		BUNGEE_ASSERT1(f.cols() == 1 || !f.IsRowMajor);
+
+		for (int c = 0; c < f.cols(); ++c)
+			callbacks.inverse(callbacks.context, log2TransformLength, t.col(c).data(), (const float *)f.col(c).data());
+	}
+};
+
+std::unique_ptr<Transforms> transforms(const Bungee_FourierCallbacks &callbacks)
+{
+	return std::make_unique<Callbacks>(callbacks);
+}
+
 } // namespace Bungee::Fourier
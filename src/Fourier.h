@@ -4,6 +4,7 @@
 #pragma once
 
 #include "Assert.h"
+#include "bungee/Bungee.h"
 
 #include <Eigen/Dense>
 
@@ -172,4 +173,7 @@ struct Cache :
 
 std::unique_ptr<Transforms> transforms();
 
+// Transforms delegated to an FFT supplied through the C API
+std::unique_ptr<Transforms> transforms(const Bungee_FourierCallbacks &callbacks);
+
 } // namespace Bungee::Fourier
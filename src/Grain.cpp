@@ -17,7 +17,15 @@ Grain::Grain(int log2SynthesisHop, int channelCount) :
 {
 	request.position = request.speed = std::numeric_limits<float>::quiet_NaN();
 	request.pitch = 1.;
+	request.reset = request.preserveFormants = false;
 	Fourier::resize<true>(log2TransformLength, channelCount, transformed);
+	tracks.reserve(channelCount);
+	for (int c = 0; c < channelCount; ++c)
+		tracks.emplace_back(log2TransformLength);
+}
+
+Grain::Track::Track(int log2TransformLength)
+{
 	Fourier::resize<true>(log2TransformLength, 1, phase);
 	Fourier::resize<true>(log2TransformLength, 1, energy);
 	Fourier::resize<true>(log2TransformLength, 1, rotation);
@@ -25,14 +33,14 @@ Grain::Grain(int log2SynthesisHop, int channelCount) :
 	partials.reserve(1 << log2TransformLength);
 }
 
-InputChunk Grain::specify(const Request &r, Grain &previous, SampleRates sampleRates, int log2SynthesisHop)
+InputChunk Grain::specify(const Request &r, Grain &previous, SampleRates sampleRates, int log2SynthesisHop, ResampleMode::Enum resampleMode, Resample::Kernel resampleKernel)
 {
 	request = r;
 	BUNGEE_ASSERT1(request.pitch > 0.);
 
 	const Assert::FloatingPointExceptions floatingPointExceptions(FE_INEXACT);
 
-	const auto unitHop = (1 << log2SynthesisHop) * resampleOperations.setup(sampleRates, request.pitch);
+	const auto unitHop = (1 << log2SynthesisHop) * resampleOperations.setup(sampleRates, request.pitch, resampleMode, resampleKernel);
 
 	requestHop = request.position - previous.request.position;
 	if (std::isnan(requestHop) || request.reset)
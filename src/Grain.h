@@ -47,19 +47,29 @@ struct Grain
 	InputChunk inputChunk{};
 	Analysis analysis{};
 
+	// Phase analysis and synthesis state of a group of channels that share one rotation per bin
+	struct Track
+	{
+		Eigen::ArrayX<Phase::Type> phase;
+		Eigen::ArrayXf energy;
+		Eigen::ArrayX<Phase::Type> rotation;
+		Eigen::ArrayX<Phase::Type> delta;
+		std::vector<Partials::Partial> partials;
+		bool transient{};
+
+		Track(int log2TransformLength);
+	};
+
 	Eigen::ArrayXXcf transformed;
-	Eigen::ArrayX<Phase::Type> phase;
-	Eigen::ArrayXf energy;
-	Eigen::ArrayX<Phase::Type> rotation;
-	Eigen::ArrayX<Phase::Type> delta;
-	std::vector<Partials::Partial> partials;
+	Bungee_ChannelCoupling channelCoupling{};
+	std::vector<Track> tracks;
 	Resample::Padded inputResampled;
 
 	Output::Segment segment;
 
 	Grain(int log2SynthesisHop, int channelCount);
 
-	InputChunk specify(const Request &request, Grain &previous, SampleRates sampleRates, int log2SynthesisHop);
+	InputChunk specify(const Request &request, Grain &previous, SampleRates sampleRates, int log2SynthesisHop, ResampleMode::Enum resampleMode, Resample::Kernel resampleKernel);
 
 	bool reverse() const
 	{
@@ -71,6 +81,18 @@ struct Grain
 		return !std::isnan(request.position);
 	}
 
+	// Number of tracks in use: one for all channels when sum-locked, else one per channel
+	int trackCount() const
+	{
+		return channelCoupling == Bungee_ChannelCoupling_sumLocked ? 1 : (int)transformed.cols();
+	}
+
+	// Columns of transformed whose phase is governed by the given track
+	auto trackColumns(int track)
+	{
+		return channelCoupling == Bungee_ChannelCoupling_sumLocked ? transformed.middleCols(0, transformed.cols()) : transformed.middleCols(track, 1);
+	}
+
 	void applyEnvelope();
 
 	auto inputChunkMap(const float *data, std::ptrdiff_t stride)
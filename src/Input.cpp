@@ -5,17 +5,11 @@
 #include "Grain.h"
 #include "log2.h"
 
-#include <numbers>
-
 namespace Bungee {
 
-namespace {
-static constexpr float pi = std::numbers::pi_v<float>;
-static constexpr float gain = (3 * pi) / (3 * pi + 8);
-} // namespace
-
-Input::Input(int log2SynthesisHop, int channelCount, Fourier::Transforms &transforms) :
-	analysisWindowBasic(Window::fromFrequencyDomainCoefficients(transforms, log2SynthesisHop + 3, gain / (8 << log2SynthesisHop), {1.f, 0.5f})),
+// The window's gain is set by Window::normalise once the synthesis window is known
+Input::Input(int log2SynthesisHop, int channelCount, Fourier::Transforms &transforms, const Bungee_Window &window) :
+	analysisWindowBasic(Window::fromShape(transforms, log2SynthesisHop + 3, window)),
 	windowedInput{(8 << log2SynthesisHop), channelCount}
 {
 	windowedInput.setZero();
@@ -6,6 +6,8 @@
 #include "Assert.h"
 #include "Fourier.h"
 
+#include "bungee/Bungee.h"
+
 #include <Eigen/Dense>
 
 namespace Bungee {
@@ -15,7 +17,7 @@ struct Input
 	Eigen::ArrayXf analysisWindowBasic;
 	Eigen::ArrayXXf windowedInput;
 
-	Input(int log2SynthesisHop, int channelCount, Fourier::Transforms &transforms);
+	Input(int log2SynthesisHop, int channelCount, Fourier::Transforms &transforms, const Bungee_Window &window);
 
 	int applyAnalysisWindow(const Eigen::Ref<const Eigen::ArrayXXf> &input);
 };
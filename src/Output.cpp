@@ -7,8 +7,8 @@
 
 namespace Bungee {
 
-Output::Output(Fourier::Transforms &transforms, int log2SynthesisHop, int channelCount, int maxOutputChunkSize, float windowGain, std::initializer_list<float> windowCoefficients) :
-	synthesisWindow{Window::fromFrequencyDomainCoefficients(transforms, log2SynthesisHop + 2, windowGain, windowCoefficients)},
+Output::Output(Fourier::Transforms &transforms, int log2SynthesisHop, int channelCount, int maxOutputChunkSize, const Bungee_Window &window) :
+	synthesisWindow{Window::fromShape(transforms, log2SynthesisHop + 2, window)},
 	inverseTransformed(8 << log2SynthesisHop, channelCount),
 	bufferResampled(maxOutputChunkSize, channelCount)
 {
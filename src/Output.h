@@ -11,8 +11,6 @@
 
 #include <Eigen/Dense>
 
-#include <initializer_list>
-
 namespace Bungee {
 
 struct Grains;
@@ -25,7 +23,7 @@ struct Output
 	float resampleOffset = 0.f;
 	Window::DispatchApply dispatchApply;
 
-	Output(Fourier::Transforms &transforms, int log2SynthesisHop, int channelCount, int maxOutputChunkSize, float windowGain, std::initializer_list<float> windowCoefficients);
+	Output(Fourier::Transforms &transforms, int log2SynthesisHop, int channelCount, int maxOutputChunkSize, const Bungee_Window &window);
 
 	void applySynthesisWindow(int log2SynthesisHop, Grains &grains, const Eigen::Ref<const Eigen::ArrayXf> &window);
 
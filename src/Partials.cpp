@@ -59,4 +59,12 @@ void suppressTransientPartials(std::vector<Partial> &partials, const Eigen::Ref<
 		}
 }
 
+float spectralFlux(int n, const Eigen::Ref<const Eigen::ArrayX<float>> energy, const Eigen::Ref<const Eigen::ArrayX<float>> previousEnergy)
+{
+	const auto total = energy.head(n).sum();
+	if (!(total > 0.f))
+		return 0.f;
+	return (energy.head(n) - previousEnergy.head(n)).max(0.f).sum() / total;
+}
+
 } // namespace Bungee::Partials
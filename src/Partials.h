@@ -22,4 +22,11 @@ void enumerate(std::vector<Partial> &partials, int n, Eigen::Ref<Eigen::ArrayX<f
 
 void suppressTransientPartials(std::vector<Partial> &partials, const Eigen::Ref<const Eigen::ArrayX<float>> energy, const Eigen::Ref<const Eigen::ArrayX<float>> previousEnergy);
 
+// Rectified spectral flux over the first n bins as a fraction of the grain's energy: 0 when no bin
+// gained energy since the previous grain, approaching 1 when all the energy is new.
+float spectralFlux(int n, const Eigen::Ref<const Eigen::ArrayX<float>> energy, const Eigen::Ref<const Eigen::ArrayX<float>> previousEnergy);
+
+// In a transient grain, partials whose energy has risen by more than this factor have their phase reset.
+static constexpr float transientRise = 2.f;
+
 } // namespace Bungee::Partials
@@ -10,6 +10,8 @@
 
 #include <Eigen/Dense>
 
+#include <cmath>
+#include <numbers>
 #include <type_traits>
 
 namespace Bungee::Resample {
@@ -17,6 +19,8 @@ namespace Bungee::Resample {
 typedef Eigen::Ref<Eigen::ArrayXXf> Ref;
 typedef Eigen::Block<Ref, 1, Eigen::Dynamic, false> Row;
 
+static constexpr int maxSincTaps = 32;
+
 struct FixedToVariable
 {
 	static inline float applyGain(float coefficient, float)
@@ -24,6 +28,12 @@ struct FixedToVariable
 		return coefficient;
 	}
 
+	// relative cutoff frequency that avoids aliasing when reading the fixed buffer at steps of ratio
+	static inline float cutoff(float ratio)
+	{
+		return std::min(1.f, 1.f / ratio);
+	}
+
 	template <bool first>
 	static inline void tap(float fixed, float &__restrict variable, float coefficient)
 	{
@@ -41,6 +51,12 @@ struct VariableToFixed
 		return coefficient * gain;
 	}
 
+	// relative cutoff frequency that avoids aliasing when writing the fixed buffer at steps of ratio
+	static inline float cutoff(float ratio)
+	{
+		return std::min(1.f, ratio);
+	}
+
 	template <bool>
 	static inline void tap(float &__restrict fixed, float variable, float coefficient)
 	{
@@ -82,10 +98,77 @@ struct Bilinear
 	}
 };
 
+// Catmull-Rom cubic Hermite spline through the four nearest samples
+struct CubicHermite
+{
+	template <class Mode>
+	static inline void step(float offset, Ref fixed, Row variable, float gain)
+	{
+		int x = int(offset);
+		float k = offset - x;
+		const float k2 = k * k;
+		const float k3 = k2 * k;
+		const float coefficients[4] = {
+			-0.5f * k3 + k2 - 0.5f * k,
+			1.5f * k3 - 2.5f * k2 + 1.f,
+			-1.5f * k3 + 2.f * k2 + 0.5f * k,
+			0.5f * k3 - 0.5f * k2,
+		};
+		for (int c = 0; c < fixed.cols(); ++c)
+		{
+			Mode::template tap<true>(fixed(x - 1, c), variable(0, c), Mode::applyGain(coefficients[0], gain));
+			for (int j = 1; j < 4; ++j)
+				Mode::template tap<false>(fixed(x - 1 + j, c), variable(0, c), Mode::applyGain(coefficients[j], gain));
+		}
+	}
+};
+
+// Blackman-windowed sinc spanning taps samples of the fixed buffer. The cutoff follows the
+// resample ratio so that downsampling is band-limited.
+template <int taps>
+struct WindowedSinc
+{
+	static_assert(taps >= 4 && taps <= maxSincTaps && taps % 2 == 0);
+
+	template <class Mode>
+	static inline void step(float offset, Ref fixed, Row variable, float gain)
+	{
+		constexpr int half = taps / 2;
+		constexpr float pi = std::numbers::pi_v<float>;
+
+		int x = int(offset);
+		float k = offset - x;
+		const float cutoff = Mode::cutoff(gain);
+
+		float coefficients[taps];
+		float sum = 0.f;
+		for (int j = 0; j < taps; ++j)
+		{
+			const float d = j - (half - 1) - k;
+			const float sinc = std::abs(d) < 1e-6f ? 1.f : std::sin(pi * cutoff * d) / (pi * cutoff * d);
+			const float t = pi * d / half;
+			const float window = std::abs(d) < half ? 0.42f + 0.5f * std::cos(t) + 0.08f * std::cos(2.f * t) : 0.f;
+			coefficients[j] = sinc * window;
+			sum += coefficients[j];
+		}
+
+		// normalise for unity gain at DC, which short kernels with a low cutoff would otherwise lose
+		for (auto &coefficient : coefficients)
+			coefficient /= sum;
+
+		for (int c = 0; c < fixed.cols(); ++c)
+		{
+			Mode::template tap<true>(fixed(x - (half - 1), c), variable(0, c), Mode::applyGain(coefficients[0], gain));
+			for (int j = 1; j < taps; ++j)
+				Mode::template tap<false>(fixed(x - (half - 1) + j, c), variable(0, c), Mode::applyGain(coefficients[j], gain));
+		}
+	}
+};
+
 struct Padded
 {
 	static constexpr auto align = std::max<int>(EIGEN_DEFAULT_ALIGN_BYTES / sizeof(float), 1);
-	static constexpr auto padding = (6 + align - 1) / align * align;
+	static constexpr auto padding = (maxSincTaps / 2 + 6 + align - 1) / align * align;
 
 	Eigen::ArrayXXf array;
 	int frameCount{};
@@ -170,6 +253,48 @@ inline int resample(Padded &fixedBuffer, float &fixedBufferOffset, Ref variableB
 
 typedef decltype(&resample<FixedToVariable, Nearest>) Function;
 
+// Resample functions of one Interpolation policy, for the input and output sides
+struct Kernel
+{
+	Function input = &resample<VariableToFixed, Bilinear>;
+	Function output = &resample<FixedToVariable, Bilinear>;
+
+	template <class Interpolation>
+	static constexpr Kernel make()
+	{
+		return {&resample<VariableToFixed, Interpolation>, &resample<FixedToVariable, Interpolation>};
+	}
+
+	// Returns Bilinear if interpolation or sincTaps is not supported; sincTaps may be 4, 8, 16 or 32.
+	static Kernel select(Bungee_Interpolation interpolation, int sincTaps)
+	{
+		switch (interpolation)
+		{
+		case Bungee_Interpolation_nearest:
+			return make<Nearest>();
+		case Bungee_Interpolation_cubicHermite:
+			return make<CubicHermite>();
+		case Bungee_Interpolation_windowedSinc:
+			switch (sincTaps)
+			{
+			case 4:
+				return make<WindowedSinc<4>>();
+			case 8:
+				return make<WindowedSinc<8>>();
+			case 16:
+				return make<WindowedSinc<16>>();
+			case 32:
+				return make<WindowedSinc<32>>();
+			}
+			BUNGEE_ASSERT1(!"Resample::Kernel::select: unsupported sincTaps");
+			break;
+		default:
+			break;
+		}
+		return make<Bilinear>();
+	}
+};
+
 struct Operation
 {
 	Function function;
@@ -180,22 +305,14 @@ struct Operations
 {
 	Operation input, output;
 
-	double setup(const SampleRates &sampleRates, double pitch, ResampleMode::Enum resampleMode = {})
+	double setup(const SampleRates &sampleRates, double pitch, ResampleMode::Enum resampleMode = {}, Kernel kernel = {})
 	{
 		const double resampleRatio = pitch * sampleRates.input / sampleRates.output;
 		input.ratio = 1.f / resampleRatio;
 		output.ratio = resampleRatio;
 
-		if constexpr (true)
-		{
-			input.function = &resample<VariableToFixed, Bilinear>;
-			output.function = &resample<FixedToVariable, Bilinear>;
-		}
-		else
-		{
-			input.function = &resample<VariableToFixed, Nearest>;
-			output.function = &resample<FixedToVariable, Nearest>;
-		}
+		input.function = kernel.input;
+		output.function = kernel.output;
 
 		if (resampleMode == ResampleMode::forceOut)
 			input.function = nullptr;
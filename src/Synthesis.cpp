@@ -16,32 +16,32 @@ static constexpr int flagReverse1 = 1 << 1;
 struct Temporal
 {
 	template <int index>
-	static void special(int log2SynthesisHop, Grain &grain, Grain &previous)
+	static void special(int log2SynthesisHop, const Grain &grain, const Grain &previous, Grain::Track &track, const Grain::Track &previousTrack)
 	{
 		typedef Stretch::Time<!!(index & flagReverse0), !!(index & flagReverse1)> StretchTime;
 
 		const StretchTime stretchTime(log2SynthesisHop, grain.analysis.hop, previous.analysis.hop);
 
-		BUNGEE_ASSERT1(grain.partials.back().end == grain.validBinCount);
+		BUNGEE_ASSERT1(track.partials.back().end == grain.validBinCount);
 
-		for (int i = 0; i < grain.partials.size(); ++i)
+		for (int i = 0; i < track.partials.size(); ++i)
 		{
-			const auto peak = grain.partials[i].peak;
+			const auto peak = track.partials[i].peak;
 
-			const Phase::Type offset = StretchTime::offset(grain.phase[peak], previous.phase[peak]);
-			const Phase::Type stretched = stretchTime.delta(grain.phase[peak], previous.phase[peak], peak);
-			grain.delta[i] = previous.rotation[peak] - offset + stretched;
-			BUNGEE_ASSERT2(!grain.passthrough || !grain.delta[i]);
+			const Phase::Type offset = StretchTime::offset(track.phase[peak], previousTrack.phase[peak]);
+			const Phase::Type stretched = stretchTime.delta(track.phase[peak], previousTrack.phase[peak], peak);
+			track.delta[i] = previousTrack.rotation[peak] - offset + stretched;
+			BUNGEE_ASSERT2(!grain.passthrough || !track.delta[i]);
 
-			grain.delta[i] -= grain.rotation[peak];
+			track.delta[i] -= track.rotation[peak];
 		}
 	}
 };
 
-void synthesise(int log2SynthesisHop, Grain &grain, Grain &previous)
+static void synthesiseTrack(int log2SynthesisHop, const Grain &grain, const Grain &previous, Grain::Track &track, const Grain::Track &previousTrack)
 {
-	Stretch::Frequency(grain.analysis.speed)(grain.validBinCount, grain.rotation, grain.phase);
-	BUNGEE_ASSERT2(!grain.passthrough || grain.rotation.topRows(grain.validBinCount).isZero());
+	Stretch::Frequency(grain.analysis.speed)(grain.validBinCount, track.rotation, track.phase);
+	BUNGEE_ASSERT2(!grain.passthrough || track.rotation.topRows(grain.validBinCount).isZero());
 
 	if (grain.continuous)
 	{
@@ -52,25 +52,39 @@ void synthesise(int log2SynthesisHop, Grain &grain, Grain &previous)
 			index |= flagReverse1;
 
 		static constexpr Dispatch<Temporal, 4> dispatch;
-		dispatch[index](log2SynthesisHop, grain, previous);
+		dispatch[index](log2SynthesisHop, grain, previous, track, previousTrack);
+
+		if (track.transient)
+			for (int i = 0; i < track.partials.size(); ++i)
+			{
+				const auto peak = track.partials[i].peak;
+				if (peak < previous.validBinCount && track.energy[peak] > Partials::transientRise * previousTrack.energy[peak])
+					track.delta[i] = -track.rotation[peak];
+			}
 	}
 	else
 	{
-		for (int i = 0; i < grain.partials.size(); ++i)
-			grain.delta[i] = -grain.rotation[grain.partials[i].peak];
+		for (int i = 0; i < track.partials.size(); ++i)
+			track.delta[i] = -track.rotation[track.partials[i].peak];
 	}
 
-	for (int i = 0, n = 0; i < grain.partials.size(); ++i)
+	for (int i = 0, n = 0; i < track.partials.size(); ++i)
 		do
 		{
-			grain.rotation[n] += grain.delta[i];
-			BUNGEE_ASSERT1(!grain.passthrough || !grain.rotation[n]);
-		} while (++n < grain.partials[i].end);
+			track.rotation[n] += track.delta[i];
+			BUNGEE_ASSERT1(!grain.passthrough || !track.rotation[n]);
+		} while (++n < track.partials[i].end);
 
-	BUNGEE_ASSERT2(!grain.passthrough || grain.rotation.topRows(grain.validBinCount).isZero());
+	BUNGEE_ASSERT2(!grain.passthrough || track.rotation.topRows(grain.validBinCount).isZero());
 
 	const auto mNyquist = Fourier::binCount(grain.log2TransformLength) - 1;
-	grain.rotation[mNyquist] = grain.rotation[mNyquist - 1];
+	track.rotation[mNyquist] = track.rotation[mNyquist - 1];
+}
+
+void synthesise(int log2SynthesisHop, Grain &grain, Grain &previous)
+{
+	for (int t = 0; t < grain.trackCount(); ++t)
+		synthesiseTrack(log2SynthesisHop, grain, previous, grain.tracks[t], previous.tracks[t]);
 }
 
 } // namespace Bungee::Synthesis
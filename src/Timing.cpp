@@ -8,20 +8,18 @@
 
 #include "bungee/Bungee.h"
 
+#include <cmath>
 #include <cstdint>
 
 namespace Bungee {
 
-Timing::Timing(SampleRates sampleRates, int log2SynthesisHopOverride) :
+Timing::Timing(SampleRates sampleRates, int log2SynthesisHopOverride, int maxPitchOctaves) :
 	log2SynthesisHop(log2SynthesisHopOverride ? log2SynthesisHopOverride : log2<true>(sampleRates.input) - 6),
+	maxPitchOctaves(maxPitchOctaves ? maxPitchOctaves : 2),
 	sampleRates(sampleRates)
 {
 }
 
-namespace {
-static constexpr auto maxPitchOctaves = 2;
-}
-
 int Timing::maxInputFrameCount(bool mayDownsampleInput) const
 {
 	const auto max = (int64_t(sampleRates.input) << (maxPitchOctaves + log2SynthesisHop + 3)) / sampleRates.output;
@@ -36,12 +34,32 @@ int Timing::maxOutputFrameCount(bool mayUpsampleOutput) const
 
 double Timing::calculateInputHop(const Request &request) const
 {
-	const double unitHop = (1 << log2SynthesisHop) * Resample::Operations().setup(sampleRates, request.pitch);
+	const double unitHop = (1 << log2SynthesisHop) * Resample::Operations().setup(sampleRates, request.pitch, resampleMode, resampleKernel);
 	return unitHop * request.speed;
 }
 
+Latency Timing::latency(double pitch) const
+{
+	Resample::Operations resampleOperations;
+	const double unitHop = (1 << log2SynthesisHop) * resampleOperations.setup(sampleRates, pitch, resampleMode, resampleKernel);
+
+	// Half the input of a grain, as Grain::specify
+	auto halfInputFrameCount = 4 << log2SynthesisHop;
+	if (resampleOperations.input.ratio != 1.f)
+		halfInputFrameCount = int(std::round(halfInputFrameCount / resampleOperations.input.ratio)) + 1;
+
+	// An output chunk starts at the grain two before the latest, whose input reaches half a grain beyond its position
+	const double outputPerInput = double(sampleRates.output) / sampleRates.input;
+	Latency latency;
+	latency.input = int(std::ceil(2 * unitHop)) + halfInputFrameCount;
+	latency.output = int(std::ceil((2 * unitHop + halfInputFrameCount) * outputPerInput));
+	latency.control = int(std::ceil(2 * unitHop * outputPerInput));
+	return latency;
+}
+
 void Timing::preroll(Request &request) const
 {
+	automation.apply(request, 0.);
 	request.position -= 4. * calculateInputHop(request);
 	request.reset = true;
 }
@@ -52,6 +70,7 @@ void Timing::next(Request &request) const
 	{
 		request.position += calculateInputHop(request);
 		request.reset = false;
+		automation.apply(request, automation.outputPosition);
 	}
 }
 
@@ -3,6 +3,10 @@
 
 #pragma once
 
+#include "Automation.h"
+#include "Modes.h"
+#include "Resample.h"
+
 #include "bungee/Bungee.h"
 
 namespace Bungee {
@@ -10,15 +14,21 @@ namespace Bungee {
 struct Timing
 {
 	const int log2SynthesisHop;
+	const int maxPitchOctaves;
 	const SampleRates sampleRates;
+	Automation automation;
+	ResampleMode::Enum resampleMode{};
+	Resample::Kernel resampleKernel{};
 
-	Timing(SampleRates sampleRates, int log2SynthesisHopOverride);
+	Timing(SampleRates sampleRates, int log2SynthesisHopOverride, int maxPitchOctaves);
 
 	int maxInputFrameCount(bool mayDownsampleInput) const;
 	int maxOutputFrameCount(bool mayUpsampleOutput) const;
 
 	double calculateInputHop(const Request &request) const;
 
+	Latency latency(double pitch) const;
+
 	void preroll(Request &request) const;
 
 	void next(Request &request) const;
@@ -11,23 +11,90 @@
 
 namespace Bungee::Window {
 
-Eigen::ArrayXf fromFrequencyDomainCoefficients(Fourier::Transforms &transforms, int log2Size, float gain, std::initializer_list<float> coefficients)
+namespace {
+
+// Coefficients of the frequency-domain bins that make the zero-phase form of a0 - a1 cos(2 pi n / N) + ...
+Eigen::ArrayXcf cosineSumBins(int log2Size, const float *coefficients, int count)
+{
+	Eigen::ArrayXcf bins(Fourier::binCount(log2Size));
+	bins.setZero();
+	for (int k = 0; k < count && k < bins.rows(); ++k)
+		bins[k] = k ? coefficients[k] * 0.5f : coefficients[k];
+	return bins;
+}
+
+// Modified Bessel function of the first kind, order zero
+double besselI0(double x)
 {
-	Eigen::ArrayXcf frequencyDomain(Fourier::binCount(log2Size));
+	double sum = 1., term = 1.;
+	for (int k = 1; term > sum * 1e-12; ++k)
+	{
+		term *= (x * x) / (4. * k * k);
+		sum += term;
+	}
+	return sum;
+}
+
+} // namespace
 
-	std::size_t row = 0;
-	for (auto c : coefficients)
-		if (row < frequencyDomain.rows())
-			frequencyDomain.coeffRef(row++) = c * gain;
+Eigen::ArrayXf fromShape(Fourier::Transforms &transforms, int log2Size, const Bungee_Window &shape)
+{
+	static constexpr float hann[] = {0.5f, 0.5f};
+	static constexpr float blackmanHarris[] = {0.35875f, 0.48829f, 0.14128f, 0.01168f};
 
-	frequencyDomain.bottomRows(frequencyDomain.rows() - row).setZero();
+	const float *coefficients = hann;
+	int count = 2;
+	switch (shape.shape)
+	{
+	case Bungee_WindowShape_hann:
+		break;
+	case Bungee_WindowShape_blackmanHarris:
+		coefficients = blackmanHarris;
+		count = 4;
+		break;
+	case Bungee_WindowShape_cosineSum:
+		coefficients = shape.coefficients;
+		count = shape.coefficientCount;
+		break;
+	case Bungee_WindowShape_kaiser: {
+		const auto n = 1 << log2Size;
+		Eigen::ArrayXf window(n);
+		const auto scale = 1. / besselI0(shape.kaiserBeta);
+		for (int i = 0; i < n; ++i)
+		{
+			const auto x = 2. * (i < n / 2 ? i : i - n) / n;
+			window[i] = float(besselI0(shape.kaiserBeta * std::sqrt(1. - x * x)) * scale);
+		}
+		return window;
+	}
+	}
 
+	Eigen::ArrayXcf frequencyDomain = cosineSumBins(log2Size, coefficients, count);
 	Eigen::ArrayXf window(Fourier::transformLength(log2Size));
 	transforms.prepareInverse(log2Size);
 	transforms.inverse(log2Size, window, frequencyDomain);
 	return window;
 }
 
+void normalise(int log2SynthesisHop, Eigen::ArrayXf &analysis, Eigen::ArrayXf &synthesis)
+{
+	const auto hop = 1 << log2SynthesisHop;
+	BUNGEE_ASSERT1(analysis.rows() == 8 * hop);
+	BUNGEE_ASSERT1(synthesis.rows() == 4 * hop);
+
+	// Lapped every hop, the synthesis windows alone sum to unity on average
+	synthesis *= float(hop / synthesis.cast<double>().sum());
+
+	// As do the products of both windows over the synthesis window's span of -2 to 2 hops
+	double product = 0.;
+	for (int i = 0; i < 2 * hop; ++i)
+		product += double(analysis[i]) * synthesis[i];
+	for (int i = 1; i <= 2 * hop; ++i)
+		product += double(analysis[8 * hop - i]) * synthesis[4 * hop - i];
+	BUNGEE_ASSERT1(product > 0.);
+	analysis *= float(hop / (product * analysis.rows()));
+}
+
 template <bool add>
 void Apply::special(const Eigen::Ref<const Eigen::ArrayXf> &window, const Eigen::Ref<const Eigen::ArrayXXf> &input, Eigen::Ref<Eigen::ArrayXXf> output)
 {
@@ -7,13 +7,18 @@
 #include "Dispatch.h"
 #include "Fourier.h"
 
-#include <Eigen/Dense>
+#include "bungee/Bungee.h"
 
-#include <initializer_list>
+#include <Eigen/Dense>
 
 namespace Bungee::Window {
 
-Eigen::ArrayXf fromFrequencyDomainCoefficients(Fourier::Transforms &transforms, int log2Size, float gain, std::initializer_list<float> coefficients);
+// Zero-phase window of 1 << log2Size samples, peaking at sample 0, of the given shape and arbitrary gain.
+Eigen::ArrayXf fromShape(Fourier::Transforms &transforms, int log2Size, const Bungee_Window &shape);
+
+// Scales an analysis window of 8 hops and a synthesis window of 4 hops, both zero-phase, so that grains windowed
+// by both, with the inverse transform's gain of 8 hops, and lapped every hop sum to unity on average.
+void normalise(int log2SynthesisHop, Eigen::ArrayXf &analysis, Eigen::ArrayXf &synthesis);
 
 struct Apply
 {